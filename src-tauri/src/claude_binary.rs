@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 /// Shared module for detecting Claude Code binary installations
 /// Supports NVM installations, aliased paths, and version-based selection
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use tauri::Manager;
 
@@ -33,10 +33,16 @@ pub struct ClaudeInstallation {
 /// Main function to find the Claude binary
 /// Checks database first for stored path and preference, then prioritizes accordingly
 pub fn find_claude_binary(app_handle: &tauri::AppHandle) -> Result<String, String> {
+    find_claude_binary_in(app_handle.path().app_data_dir().ok().as_deref())
+}
+
+/// Finds the Claude binary without a running app, reading the stored path
+/// from the agents database in `app_data_dir` when one is given
+pub fn find_claude_binary_in(app_data_dir: Option<&Path>) -> Result<String, String> {
     info!("Searching for claude binary...");
 
     // First check if we have a stored path and preference in the database
-    if let Ok(app_data_dir) = app_handle.path().app_data_dir() {
+    if let Some(app_data_dir) = app_data_dir {
         let db_path = app_data_dir.join("agents.db");
         if db_path.exists() {
             if let Ok(conn) = rusqlite::Connection::open(&db_path) {
@@ -348,7 +354,7 @@ fn get_claude_version(path: &str) -> Result<Option<String>, String> {
 }
 
 /// Extract version string from command output
-fn extract_version_from_output(stdout: &[u8]) -> Option<String> {
+pub(crate) fn extract_version_from_output(stdout: &[u8]) -> Option<String> {
     let output_str = String::from_utf8_lossy(stdout);
     
     // Debug log the raw output
@@ -409,7 +415,7 @@ fn select_best_installation(installations: Vec<ClaudeInstallation>) -> Option<Cl
 }
 
 /// Compare two version strings
-fn compare_versions(a: &str, b: &str) -> Ordering {
+pub(crate) fn compare_versions(a: &str, b: &str) -> Ordering {
     // Simple semantic version comparison
     let a_parts: Vec<u32> = a
         .split('.')
@@ -0,0 +1,788 @@
+use chrono::{Duration, Local, NaiveDate};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use crate::checkpoint::state::CheckpointState;
+use crate::commands::agents::{get_agent_in, list_agents_in, AgentParser};
+use crate::commands::claude::{
+    decode_project_path, get_project_path_from_sessions, list_checkpoints_in, list_projects_in,
+    restore_checkpoint_in,
+};
+use crate::commands::mcp::mcp_test_connection_in;
+use crate::commands::usage::{get_usage_by_date_range_in, get_usage_stats_in, UsageStats};
+use crate::context::CoreContext;
+
+/// Top-level commands handled without opening a window
+const COMMANDS: &[&str] = &[
+    "agents",
+    "usage",
+    "sessions",
+    "checkpoints",
+    "mcp",
+    "help",
+    "--help",
+    "-h",
+];
+
+/// Options that take a value; every other `--option` is a switch
+const VALUE_OPTIONS: &[&str] = &["since", "until", "project", "limit", "claude"];
+
+/// Default number of sessions listed by `sessions search`
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+/// Characters of context shown on each side of a search match
+const SNIPPET_CONTEXT: usize = 60;
+
+const USAGE: &str = "Usage: claudio <command> [options]
+
+Commands:
+  agents list                         List agents in ~/.claude/agents
+  agents show <name>                  Show an agent's definition
+  agents validate [file...]           Check agent files parse (all agents by default)
+  usage [--since 7d|YYYY-MM-DD] [--until YYYY-MM-DD]
+                                      Summarize token usage and cost
+  sessions search <query> [--project <id|path>] [--limit N]
+                                      Find sessions whose messages mention <query>
+  checkpoints list <session-id>       List a session's checkpoints
+  checkpoints restore <session-id> <checkpoint-id>
+                                      Restore a checkpoint
+  mcp test <name>                     Test the connection to an MCP server, as the app does
+
+Options:
+  --json                              Print machine-readable JSON
+  --claude <path>                     Use this claude binary instead of searching for one
+
+Run without a command to open the app.";
+
+/// Parsed command-line arguments
+#[derive(Debug, Default, PartialEq)]
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+    switches: Vec<String>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if arg == "-h" {
+                parsed.switches.push("help".to_string());
+            } else if let Some(name) = arg.strip_prefix("--") {
+                if let Some((name, value)) = name.split_once('=') {
+                    parsed.options.insert(name.to_string(), value.to_string());
+                } else if VALUE_OPTIONS.contains(&name) {
+                    let value = iter
+                        .next()
+                        .ok_or_else(|| format!("--{} needs a value", name))?;
+                    parsed.options.insert(name.to_string(), value.clone());
+                } else {
+                    parsed.switches.push(name.to_string());
+                }
+            } else {
+                parsed.positional.push(arg.clone());
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.switches.iter().any(|s| s == name)
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    fn arg(&self, index: usize, what: &str) -> Result<&str, String> {
+        self.positional
+            .get(index)
+            .map(String::as_str)
+            .ok_or_else(|| format!("missing {}", what))
+    }
+}
+
+/// An error from a CLI command, with the exit code it maps to
+enum CliError {
+    /// Bad arguments; prints the usage text and exits with 2
+    Usage(String),
+    /// The command ran and failed; exits with 1
+    Failed(String),
+}
+
+impl From<String> for CliError {
+    fn from(message: String) -> Self {
+        CliError::Failed(message)
+    }
+}
+
+/// Runs a CLI command if the arguments name one, returning the process exit
+/// code, or `None` when the app should start normally
+pub fn run(args: &[String]) -> Option<i32> {
+    let command = args.first()?;
+    if !COMMANDS.contains(&command.as_str()) {
+        return None;
+    }
+    attach_parent_console();
+
+    let parsed = match Args::parse(&args[1..]) {
+        Ok(parsed) => parsed,
+        Err(e) => return Some(usage_error(&e)),
+    };
+    if parsed.flag("help") || matches!(command.as_str(), "help" | "--help" | "-h") {
+        println!("{}", USAGE);
+        return Some(0);
+    }
+
+    let result = tauri::async_runtime::block_on(dispatch(command, &parsed));
+    Some(match result {
+        Ok(code) => code,
+        Err(CliError::Usage(message)) => usage_error(&message),
+        Err(CliError::Failed(message)) => {
+            eprintln!("claudio: {}", message);
+            1
+        }
+    })
+}
+
+/// Release builds on Windows use the GUI subsystem and get no console, so output
+/// would be lost; write to the console of the terminal that started us instead
+#[cfg(windows)]
+fn attach_parent_console() {
+    /// `(DWORD)-1`: the console of the parent process
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    // Fails when there's no parent console or we already have one; nothing to do then
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_parent_console() {}
+
+fn usage_error(message: &str) -> i32 {
+    eprintln!("claudio: {}\n\n{}", message, USAGE);
+    2
+}
+
+async fn dispatch(command: &str, args: &Args) -> Result<i32, CliError> {
+    let mut context = CoreContext::from_home()?;
+    if let Some(claude_path) = args.option("claude") {
+        context = context.with_claude_binary(claude_path);
+    }
+    let sub = args.positional.first().map(String::as_str);
+    match (command, sub) {
+        ("agents", Some("list")) => agents_list(&context, args),
+        ("agents", Some("show")) => agents_show(&context, args),
+        ("agents", Some("validate")) => agents_validate(&context, args),
+        ("usage", _) => usage(&context, args),
+        ("sessions", Some("search")) => sessions_search(&context, args),
+        ("checkpoints", Some("list")) => checkpoints_list(&context, args).await,
+        ("checkpoints", Some("restore")) => checkpoints_restore(&context, args).await,
+        ("mcp", Some("test")) => mcp_test(&context, args),
+        (_, Some(sub)) => Err(CliError::Usage(format!(
+            "unknown command: {} {}",
+            command, sub
+        ))),
+        (_, None) => Err(CliError::Usage(format!("{} needs a subcommand", command))),
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), CliError> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize output: {}", e))?;
+    println!("{}", json);
+    Ok(())
+}
+
+fn agents_list(context: &CoreContext, args: &Args) -> Result<i32, CliError> {
+    let agents = list_agents_in(context, None)?;
+    if args.flag("json") {
+        print_json(&agents)?;
+        return Ok(0);
+    }
+    if agents.is_empty() {
+        println!("No agents found");
+    }
+    for agent in &agents {
+        println!(
+            "{:<28} {:<10} {}",
+            agent.name,
+            agent.model,
+            agent.description.as_deref().unwrap_or("")
+        );
+    }
+    Ok(0)
+}
+
+fn agents_show(context: &CoreContext, args: &Args) -> Result<i32, CliError> {
+    let name = args.arg(1, "agent name").map_err(CliError::Usage)?;
+    let agent = get_agent_in(context, None, name)?;
+    if args.flag("json") {
+        print_json(&agent)?;
+    } else {
+        println!("{}", AgentParser::generate_markdown(&agent));
+    }
+    Ok(0)
+}
+
+/// Result of validating one agent file
+#[derive(Debug, Serialize)]
+struct AgentValidation {
+    path: PathBuf,
+    name: Option<String>,
+    error: Option<String>,
+}
+
+fn agents_validate(context: &CoreContext, args: &Args) -> Result<i32, CliError> {
+    let paths: Vec<PathBuf> = if args.positional.len() > 1 {
+        args.positional[1..].iter().map(PathBuf::from).collect()
+    } else {
+        let agents_dir = AgentParser::get_agents_directory(context, None)?;
+        let mut paths: Vec<PathBuf> = fs::read_dir(&agents_dir)
+            .map_err(|e| format!("Failed to read agents directory: {}", e))?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "md"))
+            .collect();
+        paths.sort();
+        paths
+    };
+
+    let results: Vec<AgentValidation> = paths
+        .into_iter()
+        .map(|path| {
+            let parsed = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read file: {}", e))
+                .and_then(|content| AgentParser::parse_file(&content));
+            match parsed {
+                Ok(agent) => AgentValidation {
+                    path,
+                    name: Some(agent.name),
+                    error: None,
+                },
+                Err(e) => AgentValidation {
+                    path,
+                    name: None,
+                    error: Some(e),
+                },
+            }
+        })
+        .collect();
+    let failed = results.iter().filter(|r| r.error.is_some()).count();
+
+    if args.flag("json") {
+        print_json(&results)?;
+    } else {
+        for result in &results {
+            match (&result.name, &result.error) {
+                (_, Some(error)) => println!("FAIL {}: {}", result.path.display(), error),
+                (Some(name), None) => println!("ok   {} ({})", result.path.display(), name),
+                (None, None) => {}
+            }
+        }
+        println!("{} checked, {} invalid", results.len(), failed);
+    }
+    Ok(if failed > 0 { 1 } else { 0 })
+}
+
+/// Where a `--since` value starts counting from
+#[derive(Debug, PartialEq)]
+enum Since {
+    Days(u32),
+    Date(NaiveDate),
+}
+
+/// The date `days` before today, if it's representable
+fn days_back(days: u32) -> Option<NaiveDate> {
+    Local::now()
+        .naive_local()
+        .date()
+        .checked_sub_signed(Duration::days(days as i64))
+}
+
+/// Parses `--since` as a number of days or weeks back ("7d", "2w") or a date
+fn parse_since(value: &str) -> Result<Since, String> {
+    let invalid = || format!("invalid --since '{}': use e.g. 7d, 2w or 2024-01-31", value);
+    let days = if let Some(days) = value.strip_suffix('d') {
+        days.parse::<u32>().ok()
+    } else if let Some(weeks) = value.strip_suffix('w') {
+        weeks
+            .parse::<u32>()
+            .ok()
+            .and_then(|weeks| weeks.checked_mul(7))
+    } else {
+        return NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map(Since::Date)
+            .map_err(|_| invalid());
+    };
+    // Reject counts that reach back before the earliest date
+    days.filter(|days| days_back(*days).is_some())
+        .map(Since::Days)
+        .ok_or_else(invalid)
+}
+
+fn usage(context: &CoreContext, args: &Args) -> Result<i32, CliError> {
+    let since = args
+        .option("since")
+        .map(parse_since)
+        .transpose()
+        .map_err(CliError::Usage)?;
+    let until = args.option("until");
+
+    let stats = match (since, until) {
+        (None, None) => get_usage_stats_in(context, None)?,
+        (Some(Since::Days(days)), None) => get_usage_stats_in(context, Some(days))?,
+        (since, until) => {
+            let today = Local::now().naive_local().date();
+            let start = match since {
+                Some(Since::Days(days)) => days_back(days).ok_or_else(|| {
+                    CliError::Usage(format!("--since {}d reaches back too far", days))
+                })?,
+                Some(Since::Date(date)) => date,
+                None => NaiveDate::MIN,
+            };
+            let end = until
+                .map(str::to_string)
+                .unwrap_or_else(|| today.format("%Y-%m-%d").to_string());
+            get_usage_by_date_range_in(context, &start.format("%Y-%m-%d").to_string(), &end)?
+        }
+    };
+
+    if args.flag("json") {
+        print_json(&stats)?;
+    } else {
+        print_usage(&stats);
+    }
+    Ok(0)
+}
+
+fn print_usage(stats: &UsageStats) {
+    println!("Cost:     ${:.2}", stats.total_cost);
+    println!(
+        "Tokens:   {} (input {}, output {}, cache write {}, cache read {})",
+        stats.total_tokens,
+        stats.total_input_tokens,
+        stats.total_output_tokens,
+        stats.total_cache_creation_tokens,
+        stats.total_cache_read_tokens
+    );
+    println!("Sessions: {}", stats.total_sessions);
+
+    if !stats.by_model.is_empty() {
+        println!("\nBy model:");
+        for model in &stats.by_model {
+            println!(
+                "  {:<32} ${:>9.2} {:>14} tokens {:>6} sessions",
+                model.model, model.total_cost, model.total_tokens, model.session_count
+            );
+        }
+    }
+    if !stats.by_project.is_empty() {
+        println!("\nBy project:");
+        for project in &stats.by_project {
+            println!(
+                "  {:<48} ${:>9.2} {:>14} tokens {:>6} sessions",
+                project.project_path,
+                project.total_cost,
+                project.total_tokens,
+                project.session_count
+            );
+        }
+    }
+}
+
+/// A session with messages matching a search
+#[derive(Debug, Serialize)]
+struct SessionMatch {
+    session_id: String,
+    project_id: String,
+    project_path: Option<String>,
+    /// Number of messages that matched
+    matches: usize,
+    /// Text around the first match
+    snippet: String,
+    last_activity: Option<String>,
+}
+
+/// Returns the text of a user or assistant message, skipping tool calls and results
+fn message_text(entry: &Value) -> Option<String> {
+    if !matches!(entry["type"].as_str(), Some("user" | "assistant")) {
+        return None;
+    }
+    match &entry["message"]["content"] {
+        Value::String(text) => Some(text.clone()),
+        Value::Array(blocks) => {
+            let text: Vec<&str> = blocks
+                .iter()
+                .filter(|block| block["type"] == "text")
+                .filter_map(|block| block["text"].as_str())
+                .collect();
+            (!text.is_empty()).then(|| text.join("\n"))
+        }
+        _ => None,
+    }
+}
+
+/// Finds the byte offset of `needle` (already lowercase) in `haystack`, ignoring case
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(0);
+    }
+    haystack.char_indices().map(|(i, _)| i).find(|&i| {
+        let mut rest = haystack[i..].chars().flat_map(char::to_lowercase);
+        needle.chars().all(|c| rest.next() == Some(c))
+    })
+}
+
+/// Cuts the text around a match down to one line with some context
+fn snippet(text: &str, at: usize) -> String {
+    let before: Vec<char> = text[..at].chars().collect();
+    let start = before.len().saturating_sub(SNIPPET_CONTEXT);
+    let mut snippet: String = before[start..].iter().collect();
+    if start > 0 {
+        snippet.insert(0, '…');
+    }
+    let mut after = text[at..].chars();
+    snippet.extend(after.by_ref().take(SNIPPET_CONTEXT * 2));
+    if after.next().is_some() {
+        snippet.push('…');
+    }
+    snippet.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Searches every session transcript under `projects_dir` for `query`,
+/// most recently active sessions first
+fn search_sessions(
+    projects_dir: &Path,
+    query: &str,
+    project_id: Option<&str>,
+    limit: usize,
+) -> Vec<SessionMatch> {
+    let needle = query.to_lowercase();
+    let mut results = Vec::new();
+
+    let Ok(projects) = fs::read_dir(projects_dir) else {
+        return results;
+    };
+    for project in projects.flatten() {
+        let project_dir = project.path();
+        let id = project.file_name().to_string_lossy().to_string();
+        if !project_dir.is_dir() || project_id.is_some_and(|p| p != id) {
+            continue;
+        }
+        let Ok(sessions) = fs::read_dir(&project_dir) else {
+            continue;
+        };
+        for session in sessions.flatten() {
+            let path = session.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("jsonl") {
+                continue;
+            }
+            let Ok(file) = fs::File::open(&path) else {
+                continue;
+            };
+
+            let mut found: Option<SessionMatch> = None;
+            let mut last_activity = None;
+            for line in BufReader::new(file).lines().map_while(Result::ok) {
+                let Ok(entry) = serde_json::from_str::<Value>(&line) else {
+                    continue;
+                };
+                if let Some(timestamp) = entry["timestamp"].as_str() {
+                    last_activity = Some(timestamp.to_string());
+                }
+                let Some(text) = message_text(&entry) else {
+                    continue;
+                };
+                let Some(at) = find_ignore_case(&text, &needle) else {
+                    continue;
+                };
+                match found.as_mut() {
+                    Some(found) => found.matches += 1,
+                    None => {
+                        found = Some(SessionMatch {
+                            session_id: path
+                                .file_stem()
+                                .map(|stem| stem.to_string_lossy().to_string())
+                                .unwrap_or_default(),
+                            project_id: id.clone(),
+                            project_path: entry["cwd"].as_str().map(str::to_string),
+                            matches: 1,
+                            snippet: snippet(&text, at),
+                            last_activity: None,
+                        })
+                    }
+                }
+            }
+            if let Some(mut found) = found {
+                found.last_activity = last_activity;
+                results.push(found);
+            }
+        }
+    }
+
+    results.sort_by(|a, b| b.last_activity.cmp(&a.last_activity));
+    results.truncate(limit);
+    results
+}
+
+/// Finds a project by its ID or by the path its sessions ran in
+fn find_project_id(context: &CoreContext, project: &str) -> Result<String, String> {
+    let path = Path::new(project);
+    list_projects_in(context)?
+        .into_iter()
+        .find(|candidate| candidate.id == project || Path::new(&candidate.path) == path)
+        .map(|candidate| candidate.id)
+        .ok_or_else(|| format!("Project not found: {}", project))
+}
+
+fn sessions_search(context: &CoreContext, args: &Args) -> Result<i32, CliError> {
+    let query = args.positional[1..].join(" ");
+    if query.is_empty() {
+        return Err(CliError::Usage("missing search query".to_string()));
+    }
+    let limit = match args.option("limit") {
+        Some(limit) => limit
+            .parse()
+            .map_err(|_| CliError::Usage(format!("invalid --limit '{}'", limit)))?,
+        None => DEFAULT_SEARCH_LIMIT,
+    };
+    let project_id = args
+        .option("project")
+        .map(|project| find_project_id(context, project))
+        .transpose()?;
+
+    let results = search_sessions(
+        &context.projects_dir(),
+        &query,
+        project_id.as_deref(),
+        limit,
+    );
+
+    if args.flag("json") {
+        print_json(&results)?;
+        return Ok(0);
+    }
+    if results.is_empty() {
+        println!("No sessions mention '{}'", query);
+    }
+    for result in &results {
+        println!(
+            "{}  {}  {} match{}",
+            result.session_id,
+            result
+                .project_path
+                .clone()
+                .unwrap_or_else(|| decode_project_path(&result.project_id)),
+            result.matches,
+            if result.matches == 1 { "" } else { "es" }
+        );
+        println!("    {}", result.snippet);
+    }
+    Ok(0)
+}
+
+/// Finds a session's project ID and path, and a checkpoint state that can load it
+async fn checkpoint_session(
+    context: &CoreContext,
+    session_id: &str,
+) -> Result<(CheckpointState, String, String), String> {
+    let transcript = context
+        .session_transcript(session_id)
+        .ok_or_else(|| format!("Session not found: {}", session_id))?;
+    let project_dir = transcript
+        .parent()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| format!("Session not found: {}", session_id))?;
+    let project_id = project_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let project_path = get_project_path_from_sessions(&project_dir)
+        .unwrap_or_else(|_| decode_project_path(&project_id));
+
+    let state = CheckpointState::new();
+    state.set_claude_dir(context.claude_dir.clone()).await;
+    Ok((state, project_id, project_path))
+}
+
+async fn checkpoints_list(context: &CoreContext, args: &Args) -> Result<i32, CliError> {
+    let session_id = args.arg(1, "session ID").map_err(CliError::Usage)?;
+    let (state, project_id, project_path) = checkpoint_session(context, session_id).await?;
+    let checkpoints =
+        list_checkpoints_in(&state, session_id.to_string(), project_id, project_path).await?;
+
+    if args.flag("json") {
+        print_json(&checkpoints)?;
+        return Ok(0);
+    }
+    if checkpoints.is_empty() {
+        println!("No checkpoints for session {}", session_id);
+    }
+    for checkpoint in &checkpoints {
+        let label = checkpoint
+            .description
+            .clone()
+            .unwrap_or_else(|| checkpoint.metadata.user_prompt.clone());
+        println!(
+            "{}  {}  message {:>4}  {}",
+            checkpoint.id,
+            checkpoint.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            checkpoint.message_index,
+            label.lines().next().unwrap_or("")
+        );
+    }
+    Ok(0)
+}
+
+async fn checkpoints_restore(context: &CoreContext, args: &Args) -> Result<i32, CliError> {
+    let session_id = args.arg(1, "session ID").map_err(CliError::Usage)?;
+    let checkpoint_id = args.arg(2, "checkpoint ID").map_err(CliError::Usage)?;
+    let (state, project_id, project_path) = checkpoint_session(context, session_id).await?;
+    let result = restore_checkpoint_in(
+        &state,
+        checkpoint_id.to_string(),
+        session_id.to_string(),
+        project_id,
+        project_path,
+    )
+    .await?;
+
+    if args.flag("json") {
+        print_json(&result)?;
+        return Ok(0);
+    }
+    println!(
+        "Restored checkpoint {} ({} files)",
+        result.checkpoint.id, result.files_processed
+    );
+    for warning in &result.warnings {
+        println!("warning: {}", warning);
+    }
+    Ok(0)
+}
+
+fn mcp_test(context: &CoreContext, args: &Args) -> Result<i32, CliError> {
+    let name = args.arg(1, "server name").map_err(CliError::Usage)?;
+    let result = mcp_test_connection_in(context, name);
+
+    if args.flag("json") {
+        print_json(&serde_json::json!({
+            "name": name,
+            "success": result.is_ok(),
+            "message": result.as_ref().ok(),
+            "error": result.as_ref().err(),
+        }))?;
+        return Ok(if result.is_ok() { 0 } else { 1 });
+    }
+    println!("{}", result?);
+    Ok(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_options_switches_and_positionals() {
+        let parsed = Args::parse(&args(&[
+            "search",
+            "flaky test",
+            "--limit",
+            "5",
+            "--json",
+            "--project=/a/b",
+        ]))
+        .unwrap();
+        assert_eq!(parsed.positional, args(&["search", "flaky test"]));
+        assert_eq!(parsed.option("limit"), Some("5"));
+        assert_eq!(parsed.option("project"), Some("/a/b"));
+        assert!(parsed.flag("json"));
+        assert!(Args::parse(&args(&["--since"])).is_err());
+    }
+
+    #[test]
+    fn leaves_app_arguments_alone() {
+        assert_eq!(run(&args(&[])), None);
+        assert_eq!(run(&args(&["-psn_0_12345"])), None);
+    }
+
+    #[test]
+    fn parses_since() {
+        assert_eq!(parse_since("7d"), Ok(Since::Days(7)));
+        assert_eq!(parse_since("2w"), Ok(Since::Days(14)));
+        assert_eq!(
+            parse_since("2024-01-31"),
+            Ok(Since::Date(NaiveDate::from_ymd_opt(2024, 1, 31).unwrap()))
+        );
+        assert!(parse_since("yesterday").is_err());
+        assert!(parse_since("700000000w").is_err());
+        assert!(parse_since("4000000000d").is_err());
+    }
+
+    #[test]
+    fn searches_message_text_ignoring_case() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("-work-app");
+        fs::create_dir_all(&project).unwrap();
+        let line = |kind: &str, content: Value, ts: &str| {
+            serde_json::json!({
+                "type": kind,
+                "cwd": "/work/app",
+                "timestamp": ts,
+                "message": { "content": content },
+            })
+            .to_string()
+        };
+        fs::write(
+            project.join("s1.jsonl"),
+            [
+                line("user", "Fix the Flaky login test".into(), "2024-01-01T00:00:00Z"),
+                line(
+                    "assistant",
+                    serde_json::json!([{ "type": "text", "text": "The flaky test waits on a timer" }]),
+                    "2024-01-01T00:01:00Z",
+                ),
+                line(
+                    "assistant",
+                    serde_json::json!([{ "type": "tool_use", "input": { "q": "flaky" } }]),
+                    "2024-01-01T00:02:00Z",
+                ),
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+        fs::write(
+            project.join("s2.jsonl"),
+            line("user", "Add a README".into(), "2024-01-02T00:00:00Z"),
+        )
+        .unwrap();
+
+        let results = search_sessions(dir.path(), "flaky", None, 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].session_id, "s1");
+        assert_eq!(results[0].project_path.as_deref(), Some("/work/app"));
+        assert_eq!(results[0].matches, 2);
+        assert_eq!(results[0].snippet, "Fix the Flaky login test");
+        assert_eq!(
+            results[0].last_activity.as_deref(),
+            Some("2024-01-01T00:02:00Z")
+        );
+        assert!(search_sessions(dir.path(), "flaky", Some("-other"), 10).is_empty());
+    }
+}
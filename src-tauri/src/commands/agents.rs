@@ -10,7 +10,9 @@ use std::fs;
 use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
 use std::sync::Mutex;
-use tauri::{AppHandle, Manager};
+use tauri::{AppHandle, Manager, State};
+
+use crate::context::CoreContext;
 use rusqlite::{params, Connection, Result as SqliteResult};
 
 /// Finds the full path to the claude binary
@@ -246,20 +248,14 @@ impl AgentParser {
     }
 
     /// Get the .claude/agents directory path
-    fn get_agents_directory(_project_path: Option<&str>) -> Result<PathBuf, String> {
+    pub(crate) fn get_agents_directory(
+        context: &CoreContext,
+        _project_path: Option<&str>,
+    ) -> Result<PathBuf, String> {
         // For Claudio, we always use global agents from ~/.claude/agents/
         // Project agents will be handled separately in the future
-        let home_dir = dirs::home_dir()
-            .ok_or_else(|| "Failed to get home directory".to_string())?;
-        let agents_dir = home_dir.join(".claude").join("agents");
+        let agents_dir = context.agents_dir()?;
         info!("Using global agents directory: {:?}", agents_dir);
-
-        // Create directory if it doesn't exist
-        if !agents_dir.exists() {
-            fs::create_dir_all(&agents_dir)
-                .map_err(|e| format!("Failed to create agents directory: {}", e))?;
-        }
-
         Ok(agents_dir)
     }
 
@@ -408,16 +404,132 @@ pub fn init_database(app: &tauri::AppHandle) -> SqliteResult<Connection> {
 
     let db_path = app_dir.join("agents.db");
     let conn = Connection::open(db_path)?;
+    init_schema(&conn)?;
 
     info!("Database initialized (agents use file-based storage, other features use SQLite)");
     Ok(conn)
 }
 
+/// Creates the tables that are missing and migrates those from earlier versions
+pub(crate) fn init_schema(conn: &Connection) -> SqliteResult<()> {
+    // User metadata for Claude Code sessions (titles, tags, notes)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_metadata (
+            session_id TEXT PRIMARY KEY,
+            project_id TEXT NOT NULL,
+            title TEXT,
+            tags TEXT NOT NULL DEFAULT '[]',
+            pinned INTEGER NOT NULL DEFAULT 0,
+            notes TEXT,
+            updated_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_session_metadata_project ON session_metadata(project_id)",
+        [],
+    )?;
+
+    // Git worktrees created to isolate sessions
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_worktrees (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            repo_path TEXT NOT NULL,
+            worktree_path TEXT NOT NULL,
+            branch TEXT NOT NULL,
+            base_commit TEXT NOT NULL,
+            session_id TEXT,
+            status TEXT NOT NULL DEFAULT 'active',
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    // Scheduled and queued headless runs
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS jobs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            project_path TEXT NOT NULL,
+            kind TEXT NOT NULL DEFAULT 'prompt',
+            prompt TEXT,
+            agent_name TEXT,
+            model TEXT,
+            schedule TEXT,
+            after_job_id INTEGER,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            status TEXT NOT NULL DEFAULT 'waiting',
+            next_run_at TEXT,
+            last_run_at TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+    // Columns added to jobs after it was first created
+    add_column_if_missing(conn, "jobs", "budget", "TEXT")?;
+    add_column_if_missing(conn, "jobs", "use_worktree", "INTEGER NOT NULL DEFAULT 0")?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS job_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            job_id INTEGER NOT NULL,
+            session_id TEXT,
+            status TEXT NOT NULL,
+            started_at TEXT NOT NULL,
+            finished_at TEXT,
+            exit_code INTEGER,
+            result TEXT,
+            cost_usd REAL,
+            error TEXT,
+            FOREIGN KEY (job_id) REFERENCES jobs(id)
+        )",
+        [],
+    )?;
+    add_column_if_missing(conn, "job_runs", "worktree_id", "INTEGER")?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_job_runs_job ON job_runs(job_id)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Adds a column to a table created by an earlier version, which `CREATE TABLE
+/// IF NOT EXISTS` leaves as it was
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> SqliteResult<()> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+        params![table, column],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )?;
+    }
+    Ok(())
+}
+
 /// List all agents from .claude/agents/*.md files
 #[tauri::command]
-pub async fn list_agents(project_path: Option<String>) -> Result<Vec<Agent>, String> {
+pub async fn list_agents(
+    context: State<'_, CoreContext>,
+    project_path: Option<String>,
+) -> Result<Vec<Agent>, String> {
+    list_agents_in(&context, project_path.as_deref())
+}
+
+/// Lists the agents in the context's agents directory
+pub fn list_agents_in(context: &CoreContext, project_path: Option<&str>) -> Result<Vec<Agent>, String> {
     info!("list_agents called with project_path: {:?}", project_path);
-    let agents_dir = AgentParser::get_agents_directory(project_path.as_deref())?;
+    let agents_dir = AgentParser::get_agents_directory(context, project_path)?;
     info!("Looking for agents in directory: {:?}", agents_dir);
     
     let mut agents = Vec::new();
@@ -484,7 +596,9 @@ pub async fn list_agents(project_path: Option<String>) -> Result<Vec<Agent>, Str
 
 /// Create a new agent file
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn create_agent(
+    context: State<'_, CoreContext>,
     project_path: Option<String>,
     name: String,
     icon: String,
@@ -499,20 +613,11 @@ pub async fn create_agent(
     tools: Option<String>,
     color: Option<String>,
 ) -> Result<Agent, String> {
-    let agents_dir = AgentParser::get_agents_directory(project_path.as_deref())?;
-    let filename = AgentParser::name_to_filename(&name);
-    let file_path = agents_dir.join(&filename);
-
-    // Check if agent already exists
-    if file_path.exists() {
-        return Err(format!("Agent '{}' already exists", name));
-    }
-
     let now = chrono::Utc::now().to_rfc3339();
     
     let agent = Agent {
         id: None,
-        name: name.clone(),
+        name,
         icon,
         system_prompt,
         default_task,
@@ -528,18 +633,38 @@ pub async fn create_agent(
         color,
     };
 
+    create_agent_in(&context, project_path.as_deref(), agent)
+}
+
+/// Writes a new agent file, failing if one with the same name exists
+pub fn create_agent_in(
+    context: &CoreContext,
+    project_path: Option<&str>,
+    agent: Agent,
+) -> Result<Agent, String> {
+    let agents_dir = AgentParser::get_agents_directory(context, project_path)?;
+    let filename = AgentParser::name_to_filename(&agent.name);
+    let file_path = agents_dir.join(&filename);
+
+    // Check if agent already exists
+    if file_path.exists() {
+        return Err(format!("Agent '{}' already exists", agent.name));
+    }
+
     let markdown_content = AgentParser::generate_markdown(&agent);
     
     fs::write(&file_path, markdown_content)
         .map_err(|e| format!("Failed to write agent file: {}", e))?;
 
-    info!("Created agent '{}' at {}", name, file_path.display());
+    info!("Created agent '{}' at {}", agent.name, file_path.display());
     Ok(agent)
 }
 
 /// Update an existing agent file
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn update_agent(
+    context: State<'_, CoreContext>,
     project_path: Option<String>,
     name: String,
     icon: String,
@@ -554,28 +679,11 @@ pub async fn update_agent(
     tools: Option<String>,
     color: Option<String>,
 ) -> Result<Agent, String> {
-    let agents_dir = AgentParser::get_agents_directory(project_path.as_deref())?;
-    let filename = AgentParser::name_to_filename(&name);
-    let file_path = agents_dir.join(&filename);
-
-    if !file_path.exists() {
-        return Err(format!("Agent '{}' not found", name));
-    }
-
-    // Get original creation time
-    let created_at = if let Ok(content) = fs::read_to_string(&file_path) {
-        if let Ok(original_agent) = AgentParser::parse_file(&content) {
-            original_agent.created_at
-        } else {
-            chrono::Utc::now().to_rfc3339()
-        }
-    } else {
-        chrono::Utc::now().to_rfc3339()
-    };
+    let now = chrono::Utc::now().to_rfc3339();
 
     let agent = Agent {
         id: None,
-        name: name.clone(),
+        name,
         icon,
         system_prompt,
         default_task,
@@ -584,27 +692,60 @@ pub async fn update_agent(
         enable_file_write: enable_file_write.unwrap_or(true),
         enable_network: enable_network.unwrap_or(false),
         hooks,
-        created_at,
-        updated_at: chrono::Utc::now().to_rfc3339(),
+        created_at: now.clone(),
+        updated_at: now,
         description,
         tools,
         color,
     };
 
+    update_agent_in(&context, project_path.as_deref(), agent)
+}
+
+/// Rewrites an existing agent file, keeping its creation time
+pub fn update_agent_in(
+    context: &CoreContext,
+    project_path: Option<&str>,
+    mut agent: Agent,
+) -> Result<Agent, String> {
+    let agents_dir = AgentParser::get_agents_directory(context, project_path)?;
+    let filename = AgentParser::name_to_filename(&agent.name);
+    let file_path = agents_dir.join(&filename);
+
+    if !file_path.exists() {
+        return Err(format!("Agent '{}' not found", agent.name));
+    }
+
+    // Get original creation time
+    if let Ok(content) = fs::read_to_string(&file_path) {
+        if let Ok(original_agent) = AgentParser::parse_file(&content) {
+            agent.created_at = original_agent.created_at;
+        }
+    }
+
     let markdown_content = AgentParser::generate_markdown(&agent);
     
     fs::write(&file_path, markdown_content)
         .map_err(|e| format!("Failed to update agent file: {}", e))?;
 
-    info!("Updated agent '{}' at {}", name, file_path.display());
+    info!("Updated agent '{}' at {}", agent.name, file_path.display());
     Ok(agent)
 }
 
 /// Delete an agent file
 #[tauri::command]
-pub async fn delete_agent(project_path: Option<String>, name: String) -> Result<(), String> {
-    let agents_dir = AgentParser::get_agents_directory(project_path.as_deref())?;
-    let filename = AgentParser::name_to_filename(&name);
+pub async fn delete_agent(
+    context: State<'_, CoreContext>,
+    project_path: Option<String>,
+    name: String,
+) -> Result<(), String> {
+    delete_agent_in(&context, project_path.as_deref(), &name)
+}
+
+/// Deletes an agent file by agent name
+pub fn delete_agent_in(context: &CoreContext, project_path: Option<&str>, name: &str) -> Result<(), String> {
+    let agents_dir = AgentParser::get_agents_directory(context, project_path)?;
+    let filename = AgentParser::name_to_filename(name);
     let file_path = agents_dir.join(&filename);
 
     if !file_path.exists() {
@@ -620,9 +761,18 @@ pub async fn delete_agent(project_path: Option<String>, name: String) -> Result<
 
 /// Get a single agent by name
 #[tauri::command]
-pub async fn get_agent(project_path: Option<String>, name: String) -> Result<Agent, String> {
-    let agents_dir = AgentParser::get_agents_directory(project_path.as_deref())?;
-    let filename = AgentParser::name_to_filename(&name);
+pub async fn get_agent(
+    context: State<'_, CoreContext>,
+    project_path: Option<String>,
+    name: String,
+) -> Result<Agent, String> {
+    get_agent_in(&context, project_path.as_deref(), &name)
+}
+
+/// Reads a single agent by name
+pub fn get_agent_in(context: &CoreContext, project_path: Option<&str>, name: &str) -> Result<Agent, String> {
+    let agents_dir = AgentParser::get_agents_directory(context, project_path)?;
+    let filename = AgentParser::name_to_filename(name);
     let file_path = agents_dir.join(&filename);
 
     if !file_path.exists() {
@@ -689,6 +839,9 @@ pub async fn list_agent_runs_with_metrics(
 }
 
 /// Execute a CC agent with streaming output (placeholder - needs Task tool integration)
+///
+/// Worktree isolation for agent runs comes with that integration; scheduled
+/// agent jobs already take `use_worktree`.
 #[tauri::command]
 pub async fn execute_agent(
     _app: AppHandle,
@@ -741,8 +894,17 @@ pub async fn stream_session_output(_app: AppHandle, _run_id: i64) -> Result<(),
 }
 
 #[tauri::command]
-pub async fn export_agent(project_path: Option<String>, name: String) -> Result<String, String> {
-    let agent = get_agent(project_path, name).await?;
+pub async fn export_agent(
+    context: State<'_, CoreContext>,
+    project_path: Option<String>,
+    name: String,
+) -> Result<String, String> {
+    export_agent_in(&context, project_path.as_deref(), &name)
+}
+
+/// Serializes an agent in the versioned JSON export format
+pub fn export_agent_in(context: &CoreContext, project_path: Option<&str>, name: &str) -> Result<String, String> {
+    let agent = get_agent_in(context, project_path, name)?;
     
     let export_data = AgentExport {
         version: 1,
@@ -766,12 +928,13 @@ pub async fn export_agent(project_path: Option<String>, name: String) -> Result<
 
 #[tauri::command]
 pub async fn export_agent_to_file(
+    context: State<'_, CoreContext>,
     project_path: Option<String>,
     name: String,
     file_path: String,
 ) -> Result<(), String> {
     // Get the source agent file path
-    let agents_dir = AgentParser::get_agents_directory(project_path.as_deref())?;
+    let agents_dir = AgentParser::get_agents_directory(&context, project_path.as_deref())?;
     let filename = AgentParser::name_to_filename(&name);
     let source_path = agents_dir.join(&filename);
 
@@ -788,9 +951,18 @@ pub async fn export_agent_to_file(
 }
 
 #[tauri::command]
-pub async fn import_agent(project_path: Option<String>, json_data: String) -> Result<Agent, String> {
+pub async fn import_agent(
+    context: State<'_, CoreContext>,
+    project_path: Option<String>,
+    json_data: String,
+) -> Result<Agent, String> {
+    import_agent_in(&context, project_path.as_deref(), &json_data)
+}
+
+/// Creates an agent from the JSON export format, renaming it if the name is taken
+pub fn import_agent_in(context: &CoreContext, project_path: Option<&str>, json_data: &str) -> Result<Agent, String> {
     let export_data: AgentExport =
-        serde_json::from_str(&json_data).map_err(|e| format!("Invalid JSON format: {}", e))?;
+        serde_json::from_str(json_data).map_err(|e| format!("Invalid JSON format: {}", e))?;
 
     if export_data.version != 1 {
         return Err(format!(
@@ -802,7 +974,7 @@ pub async fn import_agent(project_path: Option<String>, json_data: String) -> Re
     let agent_data = export_data.agent;
     
     // Check if agent already exists
-    let agents = list_agents(project_path.clone()).await?;
+    let agents = list_agents_in(context, project_path)?;
     let existing_names: Vec<String> = agents.iter().map(|a| a.name.clone()).collect();
     
     let final_name = if existing_names.contains(&agent_data.name) {
@@ -811,31 +983,36 @@ pub async fn import_agent(project_path: Option<String>, json_data: String) -> Re
         agent_data.name
     };
 
-    create_agent(
-        project_path,
-        final_name,
-        agent_data.icon,
-        agent_data.system_prompt,
-        agent_data.default_task,
-        Some(agent_data.model),
-        Some(true),  // enable_file_read
-        Some(true),  // enable_file_write
-        Some(false), // enable_network
-        agent_data.hooks,
-        agent_data.description,
-        agent_data.tools,
-        agent_data.color,
-    ).await
+    let now = chrono::Utc::now().to_rfc3339();
+    let agent = Agent {
+        id: None,
+        name: final_name,
+        icon: agent_data.icon,
+        system_prompt: agent_data.system_prompt,
+        default_task: agent_data.default_task,
+        model: agent_data.model,
+        enable_file_read: true,
+        enable_file_write: true,
+        enable_network: false,
+        hooks: agent_data.hooks,
+        created_at: now.clone(),
+        updated_at: now,
+        description: agent_data.description,
+        tools: agent_data.tools,
+        color: agent_data.color,
+    };
+    create_agent_in(context, project_path, agent)
 }
 
 #[tauri::command]
 pub async fn import_agent_from_file(
+    context: State<'_, CoreContext>,
     project_path: Option<String>,
     file_path: String,
 ) -> Result<Agent, String> {
     let json_data =
         fs::read_to_string(&file_path).map_err(|e| format!("Failed to read file: {}", e))?;
-    import_agent(project_path, json_data).await
+    import_agent_in(&context, project_path.as_deref(), &json_data)
 }
 
 // Remaining functions that depend on external APIs or complex process management
@@ -893,15 +1070,22 @@ pub async fn import_agent_from_github(
 }
 
 #[tauri::command]
-pub async fn load_agent_session_history(session_id: String) -> Result<Vec<serde_json::Value>, String> {
+pub async fn load_agent_session_history(
+    context: State<'_, CoreContext>,
+    session_id: String,
+) -> Result<Vec<serde_json::Value>, String> {
+    load_agent_session_history_in(&context, &session_id)
+}
+
+/// Reads a session's transcript from whichever project holds it
+pub fn load_agent_session_history_in(
+    context: &CoreContext,
+    session_id: &str,
+) -> Result<Vec<serde_json::Value>, String> {
     // This function can remain as-is since it deals with Claude Code session files
     log::info!("Loading agent session history for session: {}", session_id);
 
-    let claude_dir = dirs::home_dir()
-        .ok_or("Failed to get home directory")?
-        .join(".claude");
-
-    let projects_dir = claude_dir.join("projects");
+    let projects_dir = context.projects_dir();
     
     if !projects_dir.exists() {
         log::error!("Projects directory not found at: {:?}", projects_dir);
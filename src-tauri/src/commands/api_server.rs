@@ -0,0 +1,659 @@
+//! Local HTTP and WebSocket API for driving Claudio from editor plugins and
+//! scripts. It is off by default, binds only to 127.0.0.1 and requires the
+//! bearer token stored in the Claudio settings.
+//!
+//! Routes (all under `/api`):
+//! - `GET  /projects`, `GET /projects/{project_id}/sessions`,
+//!   `GET /projects/{project_id}/sessions/{session_id}` (transcript)
+//! - `GET  /runs` (running sessions), `POST /runs` (start, continue or
+//!   resume), `POST /runs/cancel`
+//! - `GET  /agents?project_path=`, `GET /usage?days=` or `?start_date=&end_date=`
+//! - `GET  /events?session_id=` upgrades to a WebSocket streaming run events
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, Request, State as AxumState};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tokio::sync::{broadcast, watch};
+use tokio::task::JoinHandle;
+
+use super::agents::{list_agents_in, AgentDb};
+use super::attachments::Attachment;
+use super::budget::RunBudget;
+use super::claude::{
+    cancel_claude_execution, get_project_sessions_in, list_projects_in, load_session_history_in,
+    start_headless_run, RunOptions,
+};
+use super::proxy::{get_claudio_settings, save_claudio_settings, ApiServerSettings};
+use super::stream_events::StreamEnvelope;
+use super::usage::{get_usage_by_date_range_in, get_usage_stats_in};
+use crate::context::CoreContext;
+
+/// How many run events a slow WebSocket client may fall behind before missing some
+const EVENT_BUFFER: usize = 1024;
+/// How long `POST /runs` waits for the CLI to report the new run's session
+const RUN_START_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long a stopping server may take to finish its requests before it's aborted
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The running server and the run events it streams
+pub struct ApiServerState {
+    events: broadcast::Sender<StreamEnvelope>,
+    server: Mutex<Option<RunningServer>>,
+}
+
+struct RunningServer {
+    address: SocketAddr,
+    /// Set to true to stop the server and close its WebSocket streams
+    shutdown: watch::Sender<bool>,
+    task: JoinHandle<()>,
+}
+
+impl Default for ApiServerState {
+    fn default() -> Self {
+        Self {
+            events: broadcast::channel(EVENT_BUFFER).0,
+            server: Mutex::new(None),
+        }
+    }
+}
+
+impl ApiServerState {
+    /// Forwards run events to connected WebSocket clients
+    pub fn publish(&self, envelopes: &[StreamEnvelope]) {
+        if self.events.receiver_count() == 0 {
+            return;
+        }
+        for envelope in envelopes {
+            let _ = self.events.send(envelope.clone());
+        }
+    }
+
+    fn address(&self) -> Option<SocketAddr> {
+        self.server
+            .lock()
+            .ok()?
+            .as_ref()
+            .map(|server| server.address)
+    }
+
+    /// Stops the running server, returning once it has released its port
+    async fn stop(&self) {
+        let Some(mut server) = self.server.lock().ok().and_then(|mut server| server.take()) else {
+            return;
+        };
+        log::info!("Stopping API server on {}", server.address);
+        let _ = server.shutdown.send(true);
+        if tokio::time::timeout(SHUTDOWN_TIMEOUT, &mut server.task)
+            .await
+            .is_err()
+        {
+            log::warn!(
+                "API server on {} didn't stop in time, aborting it",
+                server.address
+            );
+            server.task.abort();
+            let _ = server.task.await;
+        }
+    }
+}
+
+/// Whether the API is enabled and where it listens
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiServerStatus {
+    pub enabled: bool,
+    pub running: bool,
+    pub port: u16,
+    /// Base URL of the running server, e.g. "http://127.0.0.1:7878"
+    pub url: Option<String>,
+    pub token: Option<String>,
+}
+
+/// An error response, sent as `{"error": "..."}`
+struct ApiError(StatusCode, String);
+
+impl ApiError {
+    fn not_found(message: impl Into<String>) -> Self {
+        Self(StatusCode::NOT_FOUND, message.into())
+    }
+}
+
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        Self(StatusCode::INTERNAL_SERVER_ERROR, message)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+/// What each request handler gets
+#[derive(Clone)]
+struct ServerContext {
+    app: AppHandle,
+    token: String,
+    /// Becomes true when the server stops, e.g. because the token was replaced
+    shutdown: watch::Receiver<bool>,
+}
+
+impl ServerContext {
+    fn core(&self) -> CoreContext {
+        self.app.state::<CoreContext>().inner().clone()
+    }
+}
+
+/// Generates a random bearer token
+fn generate_token() -> String {
+    format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+/// Compares tokens in constant time
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Takes the token from the `Authorization: Bearer` header, or from a `token`
+/// query parameter for WebSocket clients that can't set headers
+fn request_token(request: &Request) -> Option<&str> {
+    let header_token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    header_token.or_else(|| {
+        request
+            .uri()
+            .query()?
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("token="))
+    })
+}
+
+/// Whether a decoded path parameter names an entry without leaving its directory
+fn is_plain_name(name: &str) -> bool {
+    !name.is_empty() && name != ".." && !name.contains(['/', '\\'])
+}
+
+async fn require_token(
+    AxumState(server): AxumState<ServerContext>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match request_token(&request) {
+        Some(token) if tokens_match(token, &server.token) => next.run(request).await,
+        _ => ApiError(
+            StatusCode::UNAUTHORIZED,
+            "Missing or invalid API token".to_string(),
+        )
+        .into_response(),
+    }
+}
+
+fn router(server: ServerContext) -> Router {
+    Router::new()
+        .route("/api/projects", get(list_projects_route))
+        .route(
+            "/api/projects/{project_id}/sessions",
+            get(list_sessions_route),
+        )
+        .route(
+            "/api/projects/{project_id}/sessions/{session_id}",
+            get(session_history_route),
+        )
+        .route("/api/runs", get(list_runs_route).post(start_run_route))
+        .route("/api/runs/cancel", post(cancel_run_route))
+        .route("/api/agents", get(list_agents_route))
+        .route("/api/usage", get(usage_route))
+        .route("/api/events", get(events_route))
+        .layer(middleware::from_fn_with_state(
+            server.clone(),
+            require_token,
+        ))
+        .with_state(server)
+}
+
+async fn list_projects_route(
+    AxumState(server): AxumState<ServerContext>,
+) -> ApiResult<Vec<super::claude::Project>> {
+    Ok(Json(list_projects_in(&server.core())?))
+}
+
+async fn list_sessions_route(
+    AxumState(server): AxumState<ServerContext>,
+    Path(project_id): Path<String>,
+) -> ApiResult<Vec<super::claude::Session>> {
+    let context = server.core();
+    if !is_plain_name(&project_id) || !context.project_dir(&project_id).is_dir() {
+        return Err(ApiError::not_found(format!(
+            "Project not found: {}",
+            project_id
+        )));
+    }
+    let db = server.app.state::<AgentDb>();
+    Ok(Json(get_project_sessions_in(&context, &db, &project_id)?))
+}
+
+async fn session_history_route(
+    AxumState(server): AxumState<ServerContext>,
+    Path((project_id, session_id)): Path<(String, String)>,
+) -> ApiResult<Vec<serde_json::Value>> {
+    let context = server.core();
+    if !is_plain_name(&project_id)
+        || !is_plain_name(&session_id)
+        || !context.session_path(&project_id, &session_id).is_file()
+    {
+        return Err(ApiError::not_found(format!(
+            "Session not found: {}",
+            session_id
+        )));
+    }
+    Ok(Json(load_session_history_in(
+        &context,
+        &session_id,
+        &project_id,
+    )?))
+}
+
+async fn list_runs_route(
+    AxumState(server): AxumState<ServerContext>,
+) -> ApiResult<Vec<crate::process::ProcessInfo>> {
+    let registry = server.app.state::<crate::process::ProcessRegistryState>();
+    Ok(Json(registry.0.get_running_claude_sessions()?))
+}
+
+/// Body of `POST /api/runs`: a new session, or with `session_id` or
+/// `continue` a follow-up in an existing one
+#[derive(Debug, Deserialize)]
+struct StartRunRequest {
+    project_path: String,
+    prompt: String,
+    model: String,
+    /// Resume this session
+    session_id: Option<String>,
+    /// Continue the project's most recent session
+    #[serde(default, rename = "continue")]
+    continue_session: bool,
+    budget: Option<RunBudget>,
+    profile: Option<String>,
+    use_worktree: Option<bool>,
+    attachments: Option<Vec<Attachment>>,
+}
+
+#[derive(Debug, Serialize)]
+struct StartRunResponse {
+    pid: u32,
+    /// The run's ID in the process registry, once the CLI reported its session
+    run_id: Option<i64>,
+    /// The session to follow on `/events` and to cancel with `/runs/cancel`;
+    /// missing if the CLI didn't report it in time or exited first
+    session_id: Option<String>,
+    /// The worktree a new session runs in, with `use_worktree`
+    worktree: Option<super::worktrees::SessionWorktree>,
+}
+
+/// Starts a background run, so runs started from the app or other clients are
+/// left alone, and responds once its session is known
+async fn start_run_route(
+    AxumState(server): AxumState<ServerContext>,
+    Json(request): Json<StartRunRequest>,
+) -> Result<(StatusCode, Json<StartRunResponse>), ApiError> {
+    log::info!("API request to start a run in {}", request.project_path);
+    let app = server.app.clone();
+
+    let options = RunOptions {
+        background: true,
+        budget: request.budget.unwrap_or_default(),
+        profile: request.profile,
+        attachments: request.attachments.unwrap_or_default(),
+    };
+    let args = if let Some(session_id) = request.session_id {
+        vec!["--resume".to_string(), session_id]
+    } else if request.continue_session {
+        vec!["-c".to_string()]
+    } else {
+        Vec::new()
+    };
+
+    let (handle, worktree) = if args.is_empty() && request.use_worktree.unwrap_or(false) {
+        let (worktree, handle) = super::worktrees::start_run_in_worktree(
+            &app,
+            &request.project_path,
+            request.prompt,
+            request.model,
+            options,
+        )
+        .await?;
+        (handle, Some(worktree))
+    } else {
+        let handle = start_headless_run(
+            app.clone(),
+            request.project_path,
+            request.prompt,
+            request.model,
+            args,
+            options,
+        )
+        .await?;
+        (handle, None)
+    };
+    if let Some(ref worktree) = worktree {
+        super::worktrees::bind_session_on_completion(&app, worktree.id, handle.outcome);
+    }
+
+    let started = match tokio::time::timeout(RUN_START_TIMEOUT, handle.started).await {
+        Ok(Ok(started)) => Some(started),
+        _ => {
+            log::warn!(
+                "Run {} started through the API reported no session",
+                handle.pid
+            );
+            None
+        }
+    };
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(StartRunResponse {
+            pid: handle.pid,
+            run_id: started.as_ref().map(|started| started.run_id),
+            session_id: started.map(|started| started.session_id),
+            worktree,
+        }),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+struct CancelRunRequest {
+    session_id: Option<String>,
+}
+
+/// Responds with how the run was stopped, or null if none was running
+async fn cancel_run_route(
+    AxumState(server): AxumState<ServerContext>,
+    Json(request): Json<CancelRunRequest>,
+) -> ApiResult<Option<crate::process::TerminationReport>> {
+    Ok(Json(
+        cancel_claude_execution(server.app.clone(), request.session_id).await?,
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+struct AgentsQuery {
+    project_path: Option<String>,
+}
+
+async fn list_agents_route(
+    AxumState(server): AxumState<ServerContext>,
+    Query(query): Query<AgentsQuery>,
+) -> ApiResult<Vec<super::agents::Agent>> {
+    Ok(Json(list_agents_in(
+        &server.core(),
+        query.project_path.as_deref(),
+    )?))
+}
+
+#[derive(Debug, Deserialize)]
+struct UsageQuery {
+    days: Option<u32>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+}
+
+async fn usage_route(
+    AxumState(server): AxumState<ServerContext>,
+    Query(query): Query<UsageQuery>,
+) -> ApiResult<super::usage::UsageStats> {
+    let context = server.core();
+    let stats = match (query.start_date, query.end_date) {
+        (Some(start), Some(end)) => get_usage_by_date_range_in(&context, &start, &end)?,
+        (None, None) => get_usage_stats_in(&context, query.days)?,
+        _ => {
+            return Err(ApiError(
+                StatusCode::BAD_REQUEST,
+                "start_date and end_date must be given together".to_string(),
+            ))
+        }
+    };
+    Ok(Json(stats))
+}
+
+#[derive(Debug, Deserialize)]
+struct EventsQuery {
+    /// Only stream events of this session
+    session_id: Option<String>,
+}
+
+async fn events_route(
+    AxumState(server): AxumState<ServerContext>,
+    Query(query): Query<EventsQuery>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let events = server.app.state::<ApiServerState>().events.subscribe();
+    let shutdown = server.shutdown.clone();
+    ws.on_upgrade(move |socket| stream_events(socket, events, shutdown, query.session_id))
+}
+
+/// Sends run events as JSON text messages until the client disconnects or the
+/// server stops, which closes the stream
+async fn stream_events(
+    mut socket: WebSocket,
+    mut events: broadcast::Receiver<StreamEnvelope>,
+    mut shutdown: watch::Receiver<bool>,
+    session_id: Option<String>,
+) {
+    loop {
+        tokio::select! {
+            _ = async { shutdown.wait_for(|stopped| *stopped).await.map(|_| ()) } => {
+                let _ = socket.send(Message::Close(None)).await;
+                break;
+            }
+            event = events.recv() => match event {
+                Ok(envelope) => {
+                    if session_id.is_some() && envelope.session_id != session_id {
+                        continue;
+                    }
+                    let Ok(text) = serde_json::to_string(&envelope) else {
+                        continue;
+                    };
+                    if socket.send(Message::Text(text.into())).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("API event stream client fell behind, skipped {} events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+/// Starts the server on 127.0.0.1, replacing a running one
+async fn start_server(app: &AppHandle, settings: &ApiServerSettings) -> Result<SocketAddr, String> {
+    let state = app.state::<ApiServerState>();
+    state.stop().await;
+
+    let token = settings
+        .token
+        .clone()
+        .ok_or("The API server has no token configured")?;
+    let listener = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, settings.port))
+        .await
+        .map_err(|e| format!("Failed to listen on port {}: {}", settings.port, e))?;
+    let address = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to get API server address: {}", e))?;
+
+    let (shutdown_tx, mut shutdown_rx) = watch::channel(false);
+    let router = router(ServerContext {
+        app: app.clone(),
+        token,
+        shutdown: shutdown_rx.clone(),
+    });
+    let task = tokio::spawn(async move {
+        let shutdown = async move {
+            let _ = shutdown_rx.wait_for(|stopped| *stopped).await;
+        };
+        if let Err(e) = axum::serve(listener, router)
+            .with_graceful_shutdown(shutdown)
+            .await
+        {
+            log::error!("API server stopped: {}", e);
+        }
+    });
+
+    log::info!("API server listening on http://{}", address);
+    if let Ok(mut server) = state.server.lock() {
+        *server = Some(RunningServer {
+            address,
+            shutdown: shutdown_tx,
+            task,
+        });
+    }
+    Ok(address)
+}
+
+fn status(app: &AppHandle, settings: &ApiServerSettings) -> ApiServerStatus {
+    let address = app.state::<ApiServerState>().address();
+    ApiServerStatus {
+        enabled: settings.enabled,
+        running: address.is_some(),
+        port: settings.port,
+        url: address.map(|address| format!("http://{}", address)),
+        token: settings.token.clone(),
+    }
+}
+
+/// Starts the API server at launch if the user enabled it
+pub async fn start_api_server_from_settings(app: AppHandle) {
+    let settings = match get_claudio_settings().await {
+        Ok(settings) => settings.api_server,
+        Err(e) => {
+            log::warn!("Failed to load API server settings: {}", e);
+            return;
+        }
+    };
+    if settings.enabled {
+        if let Err(e) = start_server(&app, &settings).await {
+            log::error!("Failed to start API server: {}", e);
+        }
+    }
+}
+
+/// Gets whether the local API is enabled, where it listens and its token
+#[tauri::command]
+pub async fn get_api_server_status(app: AppHandle) -> Result<ApiServerStatus, String> {
+    let settings = get_claudio_settings().await?.api_server;
+    Ok(status(&app, &settings))
+}
+
+/// Turns the local API on or off, optionally moving it to another port.
+/// A token is generated the first time it's enabled.
+#[tauri::command]
+pub async fn set_api_server_enabled(
+    app: AppHandle,
+    enabled: bool,
+    port: Option<u16>,
+) -> Result<ApiServerStatus, String> {
+    let mut claudio_settings = get_claudio_settings().await?;
+    let settings = &mut claudio_settings.api_server;
+    settings.enabled = enabled;
+    if let Some(port) = port {
+        settings.port = port;
+    }
+    if settings.token.is_none() {
+        settings.token = Some(generate_token());
+    }
+    let settings = settings.clone();
+
+    if enabled {
+        start_server(&app, &settings).await?;
+    } else {
+        app.state::<ApiServerState>().stop().await;
+    }
+    save_claudio_settings(claudio_settings).await?;
+    Ok(status(&app, &settings))
+}
+
+/// Replaces the API token, restarting the server so the old one stops working;
+/// event streams opened with it are closed
+#[tauri::command]
+pub async fn regenerate_api_server_token(app: AppHandle) -> Result<ApiServerStatus, String> {
+    let mut claudio_settings = get_claudio_settings().await?;
+    claudio_settings.api_server.token = Some(generate_token());
+    let settings = claudio_settings.api_server.clone();
+    save_claudio_settings(claudio_settings).await?;
+
+    if settings.enabled {
+        start_server(&app, &settings).await?;
+    }
+    Ok(status(&app, &settings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(uri: &str, authorization: Option<&str>) -> Request {
+        let mut builder = Request::builder().uri(uri);
+        if let Some(authorization) = authorization {
+            builder = builder.header(header::AUTHORIZATION, authorization);
+        }
+        builder.body(axum::body::Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn test_request_token() {
+        let bearer = request("/api/projects", Some("Bearer abc"));
+        assert_eq!(request_token(&bearer), Some("abc"));
+
+        let query = request("/api/events?session_id=s1&token=xyz", None);
+        assert_eq!(request_token(&query), Some("xyz"));
+
+        let basic = request("/api/projects", Some("Basic abc"));
+        assert_eq!(request_token(&basic), None);
+    }
+
+    #[test]
+    fn test_tokens_match() {
+        let token = generate_token();
+        assert_eq!(token.len(), 64);
+        assert!(tokens_match(&token, &token.clone()));
+        assert!(!tokens_match(&token[..63], &token));
+        assert!(!tokens_match(&generate_token(), &token));
+    }
+
+    #[test]
+    fn test_is_plain_name() {
+        assert!(is_plain_name("-Users-me-project"));
+        assert!(!is_plain_name(".."));
+        assert!(!is_plain_name("../secrets"));
+        assert!(!is_plain_name(""));
+    }
+}
@@ -0,0 +1,329 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Largest image accepted, matching the API's per-image limit
+const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+/// Largest text file accepted
+const MAX_TEXT_BYTES: usize = 256 * 1024;
+/// Maximum number of attachments per prompt
+const MAX_ATTACHMENTS: usize = 20;
+/// Attachment directories older than this are left over from crashed runs
+const STALE_AFTER: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A file attached to a prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Attachment {
+    /// An image or text file on disk
+    File { path: String },
+    /// Pasted image bytes, base64-encoded (a `data:` URL prefix is accepted)
+    ImageData {
+        data: String,
+        media_type: String,
+        name: Option<String>,
+    },
+    /// Pasted text
+    Text { name: String, content: String },
+}
+
+/// Attachments copied into a run's temporary directory
+#[derive(Debug)]
+pub struct PreparedAttachments {
+    /// Directory holding the copies; removed when the run ends
+    pub dir: PathBuf,
+    pub paths: Vec<PathBuf>,
+}
+
+/// Root of the temporary attachment area
+fn attachments_root() -> PathBuf {
+    std::env::temp_dir().join("claudio-attachments")
+}
+
+fn image_extension(media_type: &str) -> Option<&'static str> {
+    match media_type {
+        "image/png" => Some("png"),
+        "image/jpeg" => Some("jpg"),
+        "image/gif" => Some("gif"),
+        "image/webp" => Some("webp"),
+        _ => None,
+    }
+}
+
+/// Detects a supported image format from its leading bytes
+fn sniff_image(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// Keeps only characters that are safe in a file name
+fn sanitize_file_name(name: &str) -> String {
+    let name = Path::new(name)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("attachment");
+    let sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if sanitized.trim_matches('.').is_empty() {
+        "attachment".to_string()
+    } else {
+        sanitized
+    }
+}
+
+/// Validates an attachment and returns its file name and contents
+fn load_attachment(attachment: &Attachment) -> Result<(String, Vec<u8>), String> {
+    match attachment {
+        Attachment::File { path } => {
+            let path = Path::new(path);
+            let metadata =
+                fs::metadata(path).map_err(|e| format!("Cannot read {:?}: {}", path, e))?;
+            if !metadata.is_file() {
+                return Err(format!("{:?} is not a file", path));
+            }
+            let name = sanitize_file_name(&path.to_string_lossy());
+            let bytes = fs::read(path).map_err(|e| format!("Cannot read {:?}: {}", path, e))?;
+
+            if sniff_image(&bytes).is_some() {
+                if bytes.len() > MAX_IMAGE_BYTES {
+                    return Err(format!(
+                        "Image {} is larger than {} MB",
+                        name,
+                        MAX_IMAGE_BYTES / 1024 / 1024
+                    ));
+                }
+            } else {
+                validate_text(&name, &bytes)?;
+            }
+            Ok((name, bytes))
+        }
+        Attachment::ImageData {
+            data,
+            media_type,
+            name,
+        } => {
+            let extension = image_extension(media_type)
+                .ok_or_else(|| format!("Unsupported image type: {}", media_type))?;
+            let encoded = match data.split_once(";base64,") {
+                Some((prefix, encoded)) if prefix.starts_with("data:") => encoded,
+                _ => data.as_str(),
+            };
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(encoded.trim())
+                .map_err(|e| format!("Invalid image data: {}", e))?;
+
+            if sniff_image(&bytes) != Some(media_type.as_str()) {
+                return Err(format!("Image data is not a valid {}", media_type));
+            }
+            if bytes.len() > MAX_IMAGE_BYTES {
+                return Err(format!(
+                    "Pasted image is larger than {} MB",
+                    MAX_IMAGE_BYTES / 1024 / 1024
+                ));
+            }
+
+            let stem = name
+                .as_deref()
+                .map(sanitize_file_name)
+                .unwrap_or_else(|| "pasted-image".to_string());
+            let name = if stem.ends_with(&format!(".{}", extension)) {
+                stem
+            } else {
+                format!("{}.{}", stem, extension)
+            };
+            Ok((name, bytes))
+        }
+        Attachment::Text { name, content } => {
+            let name = sanitize_file_name(name);
+            validate_text(&name, content.as_bytes())?;
+            Ok((name, content.as_bytes().to_vec()))
+        }
+    }
+}
+
+fn validate_text(name: &str, bytes: &[u8]) -> Result<(), String> {
+    if bytes.len() > MAX_TEXT_BYTES {
+        return Err(format!(
+            "Text file {} is larger than {} KB",
+            name,
+            MAX_TEXT_BYTES / 1024
+        ));
+    }
+    if bytes.contains(&0) || std::str::from_utf8(bytes).is_err() {
+        return Err(format!(
+            "{} is neither a supported image (PNG, JPEG, GIF, WebP) nor a UTF-8 text file",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// Removes attachment directories left behind by runs that never finished.
+/// Directories named on the command line of a process that's still running
+/// (passed with `--add-dir`) are kept however old they are.
+fn remove_stale_attachments(root: &Path, running_command_lines: &[Vec<String>]) {
+    let Ok(entries) = fs::read_dir(root) else {
+        return;
+    };
+    let cutoff = SystemTime::now() - STALE_AFTER;
+    for entry in entries.flatten() {
+        let stale = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .is_ok_and(|modified| modified < cutoff);
+        let dir = entry.path();
+        let dir_name = dir.to_string_lossy();
+        let in_use = running_command_lines
+            .iter()
+            .flatten()
+            .any(|arg| arg.contains(dir_name.as_ref()));
+        if stale && !in_use {
+            let _ = fs::remove_dir_all(&dir);
+        }
+    }
+}
+
+/// Validates attachments and copies them into a new temporary directory
+///
+/// Returns None when there are no attachments. Nothing is written if any
+/// attachment is invalid. Directories of earlier runs are cleaned up unless one
+/// of `running_command_lines` still uses them.
+pub fn prepare_attachments(
+    attachments: &[Attachment],
+    running_command_lines: &[Vec<String>],
+) -> Result<Option<PreparedAttachments>, String> {
+    if attachments.is_empty() {
+        return Ok(None);
+    }
+    if attachments.len() > MAX_ATTACHMENTS {
+        return Err(format!(
+            "At most {} attachments can be added to a prompt",
+            MAX_ATTACHMENTS
+        ));
+    }
+
+    let loaded = attachments
+        .iter()
+        .map(load_attachment)
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let root = attachments_root();
+    remove_stale_attachments(&root, running_command_lines);
+
+    let dir = root.join(uuid::Uuid::new_v4().to_string());
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create attachment directory: {}", e))?;
+
+    let mut paths = Vec::new();
+    for (index, (name, bytes)) in loaded.into_iter().enumerate() {
+        // Prefix with the position so equal names don't collide
+        let path = dir.join(format!("{:02}-{}", index + 1, name));
+        if let Err(e) = fs::write(&path, bytes) {
+            let _ = fs::remove_dir_all(&dir);
+            return Err(format!("Failed to write attachment {}: {}", name, e));
+        }
+        paths.push(path);
+    }
+
+    log::info!("Prepared {} attachment(s) in {:?}", paths.len(), dir);
+    Ok(Some(PreparedAttachments { dir, paths }))
+}
+
+/// Appends references to attached files to a prompt
+///
+/// The CLI reads files (images included) from paths mentioned in the prompt.
+pub fn prompt_with_attachments(prompt: &str, paths: &[PathBuf]) -> String {
+    let mut prompt = prompt.to_string();
+    prompt.push_str("\n\nAttached files:");
+    for path in paths {
+        prompt.push_str(&format!("\n- {}", path.display()));
+    }
+    prompt
+}
+
+/// Removes a run's attachment directory
+pub fn remove_attachments(dir: &Path) {
+    if let Err(e) = fs::remove_dir_all(dir) {
+        log::warn!("Failed to remove attachments {:?}: {}", dir, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    #[test]
+    fn test_prepare_attachments_validates_and_copies() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let notes = temp_dir.path().join("notes.md");
+        fs::write(&notes, "# Bug\nThe button is misaligned").unwrap();
+        let binary = temp_dir.path().join("app.bin");
+        fs::write(&binary, [0u8, 1, 2, 3]).unwrap();
+
+        let pasted = Attachment::ImageData {
+            data: format!(
+                "data:image/png;base64,{}",
+                base64::engine::general_purpose::STANDARD.encode(PNG)
+            ),
+            media_type: "image/png".to_string(),
+            name: None,
+        };
+        let prepared = prepare_attachments(
+            &[
+                Attachment::File {
+                    path: notes.to_string_lossy().to_string(),
+                },
+                pasted,
+            ],
+            &[],
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(prepared.paths.len(), 2);
+        assert!(prepared.paths[0].ends_with("01-notes.md"));
+        assert!(prepared.paths[1].ends_with("02-pasted-image.png"));
+        assert_eq!(fs::read(&prepared.paths[1]).unwrap(), PNG);
+        remove_attachments(&prepared.dir);
+        assert!(!prepared.dir.exists());
+
+        assert!(prepare_attachments(
+            &[Attachment::File {
+                path: binary.to_string_lossy().to_string(),
+            }],
+            &[]
+        )
+        .is_err());
+        assert!(prepare_attachments(
+            &[Attachment::ImageData {
+                data: base64::engine::general_purpose::STANDARD.encode(PNG),
+                media_type: "image/jpeg".to_string(),
+                name: None,
+            }],
+            &[]
+        )
+        .is_err());
+        assert!(prepare_attachments(&[], &[]).unwrap().is_none());
+    }
+}
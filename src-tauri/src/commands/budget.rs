@@ -0,0 +1,242 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use super::usage::{calculate_cost, UsageData};
+
+/// Limits for a single Claude run; the run is stopped when any of them is crossed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunBudget {
+    /// Maximum cost in USD
+    pub max_cost_usd: Option<f64>,
+    /// Maximum tokens (input, output and cache) across all turns
+    pub max_tokens: Option<u64>,
+    /// Maximum wall-clock time in seconds
+    pub max_duration_secs: Option<u64>,
+    /// Maximum number of assistant turns
+    pub max_turns: Option<u32>,
+}
+
+impl RunBudget {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_cost_usd.is_none()
+            && self.max_tokens.is_none()
+            && self.max_duration_secs.is_none()
+            && self.max_turns.is_none()
+    }
+}
+
+/// Running totals of a Claude run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunUsage {
+    pub cost_usd: f64,
+    pub total_tokens: u64,
+    pub turns: u32,
+    pub elapsed_secs: u64,
+}
+
+/// Why a run was stopped, sent with `claude-budget-exceeded:{session_id}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetExceeded {
+    /// Which limit was crossed: "cost", "tokens", "duration" or "turns"
+    pub limit: String,
+    /// Human-readable reason
+    pub reason: String,
+    /// Totals at the time the limit was crossed
+    pub usage: RunUsage,
+}
+
+/// Tracks usage from stream-json messages against a budget
+pub struct BudgetTracker {
+    budget: RunBudget,
+    usage: RunUsage,
+    /// Assistant messages are streamed once per content block with the same
+    /// usage, so each message ID is only counted once
+    seen_messages: HashSet<String>,
+    started: Instant,
+    exceeded: Option<BudgetExceeded>,
+    finished: bool,
+}
+
+impl BudgetTracker {
+    pub fn new(budget: RunBudget) -> Self {
+        Self {
+            budget,
+            usage: RunUsage::default(),
+            seen_messages: HashSet::new(),
+            started: Instant::now(),
+            exceeded: None,
+            finished: false,
+        }
+    }
+
+    pub fn max_duration(&self) -> Option<Duration> {
+        self.budget.max_duration_secs.map(Duration::from_secs)
+    }
+
+    /// The limit that stopped the run, if any
+    pub fn exceeded(&self) -> Option<BudgetExceeded> {
+        self.exceeded.clone()
+    }
+
+    /// Marks the run as finished; no limit is reported afterwards
+    pub fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    /// Adds the usage of a stream-json message and checks the limits
+    ///
+    /// Returns the crossed limit the first time one is crossed.
+    pub fn record(&mut self, msg: &serde_json::Value) -> Option<BudgetExceeded> {
+        match msg["type"].as_str() {
+            Some("assistant") => {
+                let message = &msg["message"];
+                let id = message["id"].as_str().unwrap_or_default().to_string();
+                if id.is_empty() || self.seen_messages.insert(id) {
+                    self.usage.turns += 1;
+                    if let Ok(usage) = serde_json::from_value::<UsageData>(message["usage"].clone())
+                    {
+                        self.usage.total_tokens += usage.input_tokens.unwrap_or(0)
+                            + usage.output_tokens.unwrap_or(0)
+                            + usage.cache_creation_input_tokens.unwrap_or(0)
+                            + usage.cache_read_input_tokens.unwrap_or(0);
+                        let model = message["model"].as_str().unwrap_or_default();
+                        self.usage.cost_usd += calculate_cost(model, &usage);
+                    }
+                }
+            }
+            // The final result carries the CLI's own cost figure
+            Some("result") => {
+                if let Some(cost) = msg["total_cost_usd"].as_f64() {
+                    self.usage.cost_usd = cost;
+                }
+            }
+            _ => {}
+        }
+        self.check()
+    }
+
+    /// Checks the limits against the current totals
+    pub fn check(&mut self) -> Option<BudgetExceeded> {
+        if self.finished || self.exceeded.is_some() {
+            return None;
+        }
+        self.usage.elapsed_secs = self.started.elapsed().as_secs();
+
+        let (limit, reason) = if let Some(max) = self
+            .budget
+            .max_cost_usd
+            .filter(|max| self.usage.cost_usd > *max)
+        {
+            (
+                "cost",
+                format!(
+                    "Cost ${:.4} exceeded the limit of ${:.4}",
+                    self.usage.cost_usd, max
+                ),
+            )
+        } else if let Some(max) = self
+            .budget
+            .max_tokens
+            .filter(|max| self.usage.total_tokens > *max)
+        {
+            (
+                "tokens",
+                format!(
+                    "{} tokens exceeded the limit of {}",
+                    self.usage.total_tokens, max
+                ),
+            )
+        } else if let Some(max) = self.budget.max_turns.filter(|max| self.usage.turns > *max) {
+            (
+                "turns",
+                format!("{} turns exceeded the limit of {}", self.usage.turns, max),
+            )
+        } else if let Some(max) = self
+            .budget
+            .max_duration_secs
+            .filter(|max| self.started.elapsed() >= Duration::from_secs(*max))
+        {
+            (
+                "duration",
+                format!("Run time exceeded the limit of {}s", max),
+            )
+        } else {
+            return None;
+        };
+
+        let exceeded = BudgetExceeded {
+            limit: limit.to_string(),
+            reason,
+            usage: self.usage.clone(),
+        };
+        self.exceeded = Some(exceeded.clone());
+        Some(exceeded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn assistant(id: &str, output_tokens: u64) -> serde_json::Value {
+        json!({
+            "type": "assistant",
+            "message": {
+                "id": id,
+                "model": "claude-sonnet-4-20250514",
+                "usage": { "input_tokens": 100, "output_tokens": output_tokens }
+            }
+        })
+    }
+
+    #[test]
+    fn test_budget_tracker_counts_messages_once() {
+        let mut tracker = BudgetTracker::new(RunBudget {
+            max_tokens: Some(500),
+            ..Default::default()
+        });
+
+        // Two content blocks of the same message
+        assert!(tracker.record(&assistant("msg_1", 50)).is_none());
+        assert!(tracker.record(&assistant("msg_1", 50)).is_none());
+        assert_eq!(tracker.usage.turns, 1);
+        assert_eq!(tracker.usage.total_tokens, 150);
+
+        assert!(tracker.record(&assistant("msg_2", 200)).is_none());
+        let exceeded = tracker.record(&assistant("msg_3", 100)).unwrap();
+        assert_eq!(exceeded.limit, "tokens");
+        assert_eq!(exceeded.usage.total_tokens, 650);
+
+        // Only reported once
+        assert!(tracker.record(&assistant("msg_4", 100)).is_none());
+        assert!(tracker.exceeded().is_some());
+    }
+
+    #[test]
+    fn test_budget_tracker_cost_and_turns() {
+        let mut tracker = BudgetTracker::new(RunBudget {
+            max_turns: Some(1),
+            ..Default::default()
+        });
+        assert!(tracker.record(&assistant("msg_1", 10)).is_none());
+        assert_eq!(
+            tracker.record(&assistant("msg_2", 10)).unwrap().limit,
+            "turns"
+        );
+
+        let mut tracker = BudgetTracker::new(RunBudget {
+            max_cost_usd: Some(0.5),
+            ..Default::default()
+        });
+        assert!(tracker.record(&assistant("msg_1", 10)).is_none());
+        let exceeded = tracker
+            .record(&json!({ "type": "result", "total_cost_usd": 0.75 }))
+            .unwrap();
+        assert_eq!(exceeded.limit, "cost");
+
+        tracker.finish();
+        assert!(tracker.check().is_none());
+    }
+}
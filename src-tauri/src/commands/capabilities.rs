@@ -0,0 +1,756 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::io::Read;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Manager, State};
+
+use crate::claude_binary::{
+    compare_versions, create_command_with_env, extract_version_from_output, find_claude_binary,
+};
+
+/// A feature of the claude CLI that Claudio relies on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaudeFeature {
+    StreamJson,
+    Resume,
+    Continue,
+    AddDir,
+    SkipPermissions,
+    PermissionMode,
+    AppendSystemPrompt,
+    AllowedTools,
+    McpAddJson,
+    McpAddFromClaudeDesktop,
+    McpServe,
+}
+
+/// What the help output must show for a feature to be available
+enum Requirement {
+    Flag(&'static str),
+    FlagValue(&'static str, &'static str),
+    Subcommand(&'static str),
+}
+
+struct FeatureSpec {
+    feature: ClaudeFeature,
+    description: &'static str,
+    /// First claude release with the feature, used when help can't be parsed
+    /// and in error messages
+    min_version: &'static str,
+    requirement: Requirement,
+}
+
+/// How long a probe of the binary may take before it's killed
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+const FEATURES: &[FeatureSpec] = &[
+    FeatureSpec {
+        feature: ClaudeFeature::StreamJson,
+        description: "Streaming output",
+        min_version: "0.2.66",
+        requirement: Requirement::FlagValue("--output-format", "stream-json"),
+    },
+    FeatureSpec {
+        feature: ClaudeFeature::Resume,
+        description: "Resuming a session",
+        min_version: "0.2.47",
+        requirement: Requirement::Flag("--resume"),
+    },
+    FeatureSpec {
+        feature: ClaudeFeature::Continue,
+        description: "Continuing the last conversation",
+        min_version: "0.2.47",
+        requirement: Requirement::Flag("--continue"),
+    },
+    FeatureSpec {
+        feature: ClaudeFeature::AddDir,
+        description: "Attaching files",
+        min_version: "1.0.18",
+        requirement: Requirement::Flag("--add-dir"),
+    },
+    FeatureSpec {
+        feature: ClaudeFeature::SkipPermissions,
+        description: "Running without permission prompts",
+        min_version: "0.2.66",
+        requirement: Requirement::Flag("--dangerously-skip-permissions"),
+    },
+    FeatureSpec {
+        feature: ClaudeFeature::PermissionMode,
+        description: "Choosing a permission mode",
+        min_version: "1.0.33",
+        requirement: Requirement::Flag("--permission-mode"),
+    },
+    FeatureSpec {
+        feature: ClaudeFeature::AppendSystemPrompt,
+        description: "Agents with a system prompt",
+        min_version: "1.0.0",
+        requirement: Requirement::Flag("--append-system-prompt"),
+    },
+    FeatureSpec {
+        feature: ClaudeFeature::AllowedTools,
+        description: "Restricting an agent's tools",
+        min_version: "0.2.66",
+        requirement: Requirement::Flag("--allowedTools"),
+    },
+    FeatureSpec {
+        feature: ClaudeFeature::McpAddJson,
+        description: "Adding MCP servers from JSON",
+        min_version: "0.2.74",
+        requirement: Requirement::Subcommand("mcp add-json"),
+    },
+    FeatureSpec {
+        feature: ClaudeFeature::McpAddFromClaudeDesktop,
+        description: "Importing MCP servers from Claude Desktop",
+        min_version: "0.2.74",
+        requirement: Requirement::Subcommand("mcp add-from-claude-desktop"),
+    },
+    FeatureSpec {
+        feature: ClaudeFeature::McpServe,
+        description: "Serving Claude Code over MCP",
+        min_version: "0.2.66",
+        requirement: Requirement::Subcommand("mcp serve"),
+    },
+];
+
+fn spec(feature: ClaudeFeature) -> &'static FeatureSpec {
+    FEATURES
+        .iter()
+        .find(|spec| spec.feature == feature)
+        .expect("every feature has a spec")
+}
+
+/// Whether a feature is available in the selected claude binary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureSupport {
+    pub feature: ClaudeFeature,
+    pub description: String,
+    pub supported: bool,
+    pub min_version: String,
+    /// Shown in place of the feature when it isn't supported
+    pub message: Option<String>,
+}
+
+/// What the selected claude binary supports, from its help and version output
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClaudeCapabilities {
+    pub binary_path: String,
+    pub version: Option<String>,
+    /// Whether the help output could be parsed; if not, support is inferred
+    /// from the version
+    pub probed: bool,
+    /// Long options of the main command, e.g. "--resume"
+    pub flags: Vec<String>,
+    /// Subcommands, with nested ones as e.g. "mcp add-json"
+    pub subcommands: Vec<String>,
+    /// Values accepted by `--permission-mode`
+    pub permission_modes: Vec<String>,
+    /// Values accepted by `--output-format`
+    pub output_formats: Vec<String>,
+    pub features: Vec<FeatureSupport>,
+    /// Accepted values of options that list their choices
+    #[serde(skip)]
+    choices: BTreeMap<String, Vec<String>>,
+    /// Options that take a value
+    #[serde(skip)]
+    value_flags: BTreeSet<String>,
+    /// Long options by short alias, e.g. "-c" => "--continue"
+    #[serde(skip)]
+    aliases: BTreeMap<String, String>,
+}
+
+/// Options and commands listed by Commander-style `--help` output
+#[derive(Debug, Default)]
+struct ParsedHelp {
+    flags: BTreeSet<String>,
+    value_flags: BTreeSet<String>,
+    aliases: BTreeMap<String, String>,
+    choices: BTreeMap<String, Vec<String>>,
+    commands: Vec<String>,
+}
+
+/// Values of a "(choices: "a", "b")" list, or the quoted values in a
+/// description such as `"text" (default), "json" or "stream-json"`
+fn parse_choices(flag: &str, description: &str) -> Vec<String> {
+    let quoted = |text: &str| -> Vec<String> {
+        text.split('"')
+            .skip(1)
+            .step_by(2)
+            .filter(|value| !value.is_empty() && !value.contains(' '))
+            .map(str::to_string)
+            .collect()
+    };
+    if let Some(start) = description.find("(choices:") {
+        let rest = &description[start..];
+        let end = rest.find(')').unwrap_or(rest.len());
+        return quoted(&rest[..end]);
+    }
+    // Older releases only name the formats in the description
+    if flag == "--output-format" {
+        return quoted(description);
+    }
+    Vec::new()
+}
+
+fn parse_help(help: &str) -> ParsedHelp {
+    let mut parsed = ParsedHelp::default();
+    let mut section = String::new();
+    // Long options of the option being described, and its description so far
+    let mut current: Vec<String> = Vec::new();
+    let mut description = String::new();
+
+    let finish = |parsed: &mut ParsedHelp, flags: &[String], description: &str| {
+        for flag in flags {
+            let choices = parse_choices(flag, description);
+            if !choices.is_empty() {
+                parsed.choices.insert(flag.clone(), choices);
+            }
+        }
+    };
+
+    for line in help.lines() {
+        if !line.starts_with(' ') && line.trim_end().ends_with(':') {
+            finish(&mut parsed, &current, &description);
+            current.clear();
+            section = line.trim().trim_end_matches(':').to_lowercase();
+            continue;
+        }
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        match section.as_str() {
+            "options" if trimmed.starts_with('-') => {
+                finish(&mut parsed, &current, &description);
+                // The spec ends where the column of descriptions starts
+                let (spec, rest) = trimmed.split_once("  ").unwrap_or((trimmed, ""));
+                let takes_value = spec.contains('<') || spec.contains('[');
+                let names: Vec<&str> = spec
+                    .split([',', ' '])
+                    .filter(|token| token.starts_with('-'))
+                    .collect();
+                current = names
+                    .iter()
+                    .filter(|name| name.starts_with("--"))
+                    .map(|name| name.to_string())
+                    .collect();
+                if let Some(long) = current.first() {
+                    for short in names.iter().filter(|name| !name.starts_with("--")) {
+                        parsed.aliases.insert(short.to_string(), long.clone());
+                    }
+                }
+                for flag in &current {
+                    parsed.flags.insert(flag.clone());
+                    if takes_value {
+                        parsed.value_flags.insert(flag.clone());
+                    }
+                }
+                description = rest.trim().to_string();
+            }
+            // Wrapped description of the current option
+            "options" => {
+                description.push(' ');
+                description.push_str(trimmed);
+            }
+            "commands" => {
+                let name = trimmed.split_whitespace().next().unwrap_or_default();
+                let name = name.split('|').next().unwrap_or_default();
+                if !name.is_empty() && name != "help" && !name.starts_with('-') {
+                    parsed.commands.push(name.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    finish(&mut parsed, &current, &description);
+    parsed
+}
+
+impl ClaudeCapabilities {
+    fn from_output(
+        binary_path: &str,
+        version: Option<String>,
+        help: Option<&str>,
+        subcommand_help: &[(&str, Option<String>)],
+    ) -> Self {
+        let parsed = help.map(parse_help).unwrap_or_default();
+        let mut capabilities = ClaudeCapabilities {
+            binary_path: binary_path.to_string(),
+            version,
+            probed: !parsed.flags.is_empty(),
+            flags: parsed.flags.iter().cloned().collect(),
+            subcommands: parsed.commands.clone(),
+            permission_modes: parsed
+                .choices
+                .get("--permission-mode")
+                .cloned()
+                .unwrap_or_default(),
+            output_formats: parsed
+                .choices
+                .get("--output-format")
+                .cloned()
+                .unwrap_or_default(),
+            choices: parsed.choices,
+            value_flags: parsed.value_flags,
+            aliases: parsed.aliases,
+            ..Default::default()
+        };
+        for (command, help) in subcommand_help {
+            if !capabilities.subcommands.iter().any(|c| c == command) {
+                continue;
+            }
+            let nested = help.as_deref().map(parse_help).unwrap_or_default();
+            capabilities.subcommands.extend(
+                nested
+                    .commands
+                    .iter()
+                    .map(|nested| format!("{} {}", command, nested)),
+            );
+        }
+        capabilities.features = FEATURES
+            .iter()
+            .map(|spec| {
+                let supported = capabilities.supports(spec.feature);
+                FeatureSupport {
+                    feature: spec.feature,
+                    description: spec.description.to_string(),
+                    supported,
+                    min_version: spec.min_version.to_string(),
+                    message: (!supported).then(|| capabilities.requirement_message(spec)),
+                }
+            })
+            .collect();
+        capabilities
+    }
+
+    fn version_at_least(&self, min_version: &str) -> Option<bool> {
+        self.version
+            .as_deref()
+            .map(|version| compare_versions(version, min_version) != Ordering::Less)
+    }
+
+    /// Whether the binary has a feature. Without parsable help the version
+    /// decides, and without a version the feature is assumed to exist.
+    pub fn supports(&self, feature: ClaudeFeature) -> bool {
+        let spec = spec(feature);
+        if !self.probed {
+            return self.version_at_least(spec.min_version).unwrap_or(true);
+        }
+        match spec.requirement {
+            Requirement::Flag(flag) => self.flags.iter().any(|f| f == flag),
+            Requirement::FlagValue(flag, value) => {
+                self.flags.iter().any(|f| f == flag)
+                    && self
+                        .choices
+                        .get(flag)
+                        .is_none_or(|choices| choices.iter().any(|c| c == value))
+            }
+            Requirement::Subcommand(command) => {
+                // Nested commands are only known if the parent's help was read
+                let parent = command.split(' ').next().unwrap_or(command);
+                let nested_known = self
+                    .subcommands
+                    .iter()
+                    .any(|c| c.starts_with(&format!("{} ", parent)));
+                self.subcommands.iter().any(|c| c == command)
+                    || (!nested_known && self.subcommands.iter().any(|c| c == parent))
+            }
+        }
+    }
+
+    fn requirement_message(&self, spec: &FeatureSpec) -> String {
+        format!(
+            "{} requires claude >= {} (installed: {}). Update Claude Code with `claude update`.",
+            spec.description,
+            spec.min_version,
+            self.version.as_deref().unwrap_or("unknown version")
+        )
+    }
+
+    /// Fails with a "requires claude >= X" message if a feature is missing
+    pub fn require(&self, feature: ClaudeFeature) -> Result<(), String> {
+        if self.supports(feature) {
+            Ok(())
+        } else {
+            Err(self.requirement_message(spec(feature)))
+        }
+    }
+
+    /// The feature an option, or with a value the option value, belongs to
+    fn feature_for(flag: &str, value: Option<&str>) -> Option<&'static FeatureSpec> {
+        FEATURES.iter().find(|spec| match spec.requirement {
+            Requirement::Flag(f) => f == flag && value.is_none(),
+            Requirement::FlagValue(f, v) => f == flag && value == Some(v),
+            Requirement::Subcommand(_) => false,
+        })
+    }
+
+    /// Checks that the binary accepts every option of a command line. Options the
+    /// help output doesn't list are only rejected if they belong to a feature the
+    /// binary lacks; others may just be undocumented, so they're logged.
+    pub fn check_args(&self, args: &[String]) -> Result<(), String> {
+        let installed = self.version.as_deref().unwrap_or("unknown version");
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            // The prompt may look like an option
+            if arg == "-p" || arg == "--print" {
+                args.next();
+                continue;
+            }
+            if !arg.starts_with('-') || arg == "-" || arg == "--" {
+                continue;
+            }
+
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg.clone(), None),
+            };
+            let flag = self.aliases.get(&flag).cloned().unwrap_or(flag);
+
+            if !self.probed {
+                if let Some(spec) = Self::feature_for(&flag, None) {
+                    self.require(spec.feature)?;
+                }
+                continue;
+            }
+            if !self.flags.contains(&flag) {
+                if let Some(spec) = Self::feature_for(&flag, None) {
+                    return Err(self.requirement_message(spec));
+                }
+                log::warn!(
+                    "The installed claude ({}) doesn't list the {} option; passing it anyway",
+                    installed,
+                    flag
+                );
+                continue;
+            }
+
+            if !self.value_flags.contains(&flag) {
+                continue;
+            }
+            let Some(value) = inline_value.or_else(|| args.next().cloned()) else {
+                continue;
+            };
+            if let Some(choices) = self.choices.get(&flag) {
+                if !choices.contains(&value) {
+                    return Err(match Self::feature_for(&flag, Some(&value)) {
+                        Some(spec) => self.requirement_message(spec),
+                        None => format!(
+                            "The installed claude ({}) doesn't support {} {} (supported: {})",
+                            installed,
+                            flag,
+                            value,
+                            choices.join(", ")
+                        ),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the binary has a subcommand, e.g. "mcp add-json"
+    pub fn check_subcommand(&self, command: &str) -> Result<(), String> {
+        if let Some(spec) = FEATURES
+            .iter()
+            .find(|spec| matches!(spec.requirement, Requirement::Subcommand(c) if c == command))
+        {
+            return self.require(spec.feature);
+        }
+        // Only reject commands we know the binary lacks
+        let parent = command.split(' ').next().unwrap_or(command);
+        let nested_known = self
+            .subcommands
+            .iter()
+            .any(|c| c.starts_with(&format!("{} ", parent)));
+        if self.probed && nested_known && !self.subcommands.iter().any(|c| c == command) {
+            return Err(format!(
+                "The installed claude ({}) doesn't support `claude {}`",
+                self.version.as_deref().unwrap_or("unknown version"),
+                command
+            ));
+        }
+        Ok(())
+    }
+
+    /// Options that skip permission prompts, falling back to the bypass
+    /// permission mode on binaries without `--dangerously-skip-permissions`
+    pub fn skip_permissions_args(&self) -> Vec<String> {
+        if !self.supports(ClaudeFeature::SkipPermissions)
+            && self
+                .permission_modes
+                .iter()
+                .any(|m| m == "bypassPermissions")
+        {
+            return vec![
+                "--permission-mode".to_string(),
+                "bypassPermissions".to_string(),
+            ];
+        }
+        vec!["--dangerously-skip-permissions".to_string()]
+    }
+}
+
+/// Runs the binary and returns what it printed, if anything. A run that takes
+/// longer than `PROBE_TIMEOUT` (e.g. one waiting on a prompt) is killed.
+fn run_claude(claude_path: &str, args: &[&str]) -> Option<String> {
+    let mut child = create_command_with_env(claude_path)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| log::warn!("Failed to run {} {:?}: {}", claude_path, args, e))
+        .ok()?;
+
+    // Read on another thread so a full pipe can't stall the binary
+    let mut stdout_pipe = child.stdout.take()?;
+    let reader = std::thread::spawn(move || {
+        let mut stdout = Vec::new();
+        let _ = stdout_pipe.read_to_end(&mut stdout);
+        stdout
+    });
+
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if started.elapsed() < PROBE_TIMEOUT => {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            Ok(None) => {
+                log::warn!(
+                    "{} {:?} didn't finish within {}s, killing it",
+                    claude_path,
+                    args,
+                    PROBE_TIMEOUT.as_secs()
+                );
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+            Err(e) => {
+                log::warn!("Failed to wait for {} {:?}: {}", claude_path, args, e);
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    }
+
+    // Some releases exit with an error status after printing help
+    let stdout = String::from_utf8_lossy(&reader.join().ok()?).to_string();
+    (!stdout.trim().is_empty()).then_some(stdout)
+}
+
+/// Probes a claude binary's version, options and subcommands
+fn probe_capabilities(claude_path: &str) -> ClaudeCapabilities {
+    log::info!("Probing capabilities of claude binary: {}", claude_path);
+
+    let version = run_claude(claude_path, &["--version"])
+        .and_then(|output| extract_version_from_output(output.as_bytes()));
+    let help = run_claude(claude_path, &["--help"]);
+    let mcp_help = run_claude(claude_path, &["mcp", "--help"]);
+    let capabilities = ClaudeCapabilities::from_output(
+        claude_path,
+        version,
+        help.as_deref(),
+        &[("mcp", mcp_help)],
+    );
+
+    log::info!(
+        "claude {:?}: {} options, {} subcommands, {} unsupported features",
+        capabilities.version,
+        capabilities.flags.len(),
+        capabilities.subcommands.len(),
+        capabilities
+            .features
+            .iter()
+            .filter(|f| !f.supported)
+            .count()
+    );
+    capabilities
+}
+
+/// Probed capabilities by binary path, with the binary's modification time
+type CapabilitiesCache = HashMap<String, (Option<SystemTime>, ClaudeCapabilities)>;
+
+/// Shared cache of probed capabilities. Clones share the same cache.
+#[derive(Default, Clone)]
+pub struct ClaudeCapabilitiesState {
+    cache: Arc<Mutex<CapabilitiesCache>>,
+}
+
+impl ClaudeCapabilitiesState {
+    /// Gets the capabilities of a claude binary, probing it only the first time
+    /// or after the binary changed
+    pub fn get(&self, claude_path: &str) -> ClaudeCapabilities {
+        let modified = fs::metadata(claude_path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+
+        if let Ok(cache) = self.cache.lock() {
+            if let Some((cached_modified, capabilities)) = cache.get(claude_path) {
+                if *cached_modified == modified {
+                    return capabilities.clone();
+                }
+            }
+        }
+
+        let capabilities = probe_capabilities(claude_path);
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.insert(claude_path.to_string(), (modified, capabilities.clone()));
+        }
+        capabilities
+    }
+}
+
+/// Gets the capabilities of a claude binary through the app's shared cache
+pub fn claude_capabilities(app: &AppHandle, claude_path: &str) -> ClaudeCapabilities {
+    app.state::<ClaudeCapabilitiesState>().get(claude_path)
+}
+
+/// Async wrapper around `claude_capabilities` that probes on a blocking thread
+pub async fn claude_capabilities_async(
+    app: &AppHandle,
+    claude_path: &str,
+) -> Result<ClaudeCapabilities, String> {
+    let app = app.clone();
+    let claude_path = claude_path.to_string();
+    tokio::task::spawn_blocking(move || claude_capabilities(&app, &claude_path))
+        .await
+        .map_err(|e| format!("Failed to probe claude capabilities: {}", e))
+}
+
+/// Gets what the selected claude binary supports
+#[tauri::command]
+pub async fn get_claude_capabilities(
+    app: AppHandle,
+    state: State<'_, ClaudeCapabilitiesState>,
+    refresh: Option<bool>,
+) -> Result<ClaudeCapabilities, String> {
+    let claude_path = find_claude_binary(&app)?;
+    if refresh.unwrap_or(false) {
+        state
+            .cache
+            .lock()
+            .map_err(|e| e.to_string())?
+            .remove(&claude_path);
+    }
+    claude_capabilities_async(&app, &claude_path).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HELP: &str = r#"Usage: claude [options] [command] [prompt]
+
+Claude Code - starts an interactive session by default
+
+Arguments:
+  prompt                           Your prompt
+
+Options:
+  -d, --debug [filter]             Enable debug mode
+  -p, --print                      Print response and exit (useful for pipes)
+  --output-format <format>         Output format (only works with --print): "text" (default), "json" (single
+                                   result), or "stream-json" (realtime streaming) (choices: "text", "json",
+                                   "stream-json")
+  --allowedTools <tools...>        Comma or space-separated list of tool names to allow
+  --permission-mode <mode>         Permission mode to use for the session (choices: "acceptEdits",
+                                   "default", "plan")
+  -c, --continue                   Continue the most recent conversation
+  -r, --resume [sessionId]         Resume a conversation
+  --model <model>                  Model for the current session
+  -h, --help                       Display help for command
+
+Commands:
+  config                           Manage configuration
+  mcp                              Configure and manage MCP servers
+  update                           Check for updates and install if available
+"#;
+
+    const MCP_HELP: &str = r#"Usage: claude mcp [options] [command]
+
+Options:
+  -h, --help                                     Display help for command
+
+Commands:
+  serve [options]                                Start the Claude Code MCP server
+  add [options] <name> <commandOrUrl> [args...]  Add a server
+  remove [options] <name>                        Remove an MCP server
+  help [command]                                 display help for command
+"#;
+
+    fn capabilities() -> ClaudeCapabilities {
+        ClaudeCapabilities::from_output(
+            "claude",
+            Some("1.0.10".to_string()),
+            Some(HELP),
+            &[("mcp", Some(MCP_HELP.to_string()))],
+        )
+    }
+
+    #[test]
+    fn test_parse_help() {
+        let capabilities = capabilities();
+        assert!(capabilities.probed);
+        assert_eq!(capabilities.output_formats, ["text", "json", "stream-json"]);
+        assert_eq!(
+            capabilities.permission_modes,
+            ["acceptEdits", "default", "plan"]
+        );
+        assert!(capabilities.subcommands.contains(&"mcp serve".to_string()));
+        assert!(!capabilities.subcommands.iter().any(|c| c == "mcp help"));
+        assert!(capabilities.supports(ClaudeFeature::StreamJson));
+        assert!(capabilities.supports(ClaudeFeature::Resume));
+        assert!(!capabilities.supports(ClaudeFeature::AddDir));
+        assert!(!capabilities.supports(ClaudeFeature::McpAddJson));
+    }
+
+    #[test]
+    fn test_check_args() {
+        let capabilities = capabilities();
+        let args = |args: &[&str]| -> Vec<String> { args.iter().map(|a| a.to_string()).collect() };
+
+        assert!(capabilities
+            .check_args(&args(&[
+                "-p",
+                "--add-dir is in the prompt",
+                "-c",
+                "--output-format",
+                "stream-json",
+                "--model=sonnet"
+            ]))
+            .is_ok());
+        let error = capabilities
+            .check_args(&args(&["--add-dir", "/tmp/x"]))
+            .unwrap_err();
+        assert!(error.starts_with("Attaching files requires claude >= 1.0.18 (installed: 1.0.10)"));
+        let error = capabilities
+            .check_args(&args(&["--permission-mode", "bypassPermissions"]))
+            .unwrap_err();
+        assert!(error.contains("supported: acceptEdits, default, plan"));
+        // Options missing from the help output may just be undocumented
+        assert!(capabilities.check_args(&args(&["--verbose"])).is_ok());
+        assert!(capabilities.check_subcommand("mcp add-json").is_err());
+        assert!(capabilities.check_subcommand("mcp remove").is_ok());
+        assert_eq!(
+            capabilities.skip_permissions_args(),
+            ["--dangerously-skip-permissions"]
+        );
+
+        // Without help, support follows the version
+        let unprobed = ClaudeCapabilities::from_output("claude", Some("1.0.10".into()), None, &[]);
+        assert!(unprobed.check_args(&args(&["--resume", "abc"])).is_ok());
+        assert!(unprobed
+            .check_args(&args(&["--add-dir", "/tmp/x"]))
+            .is_err());
+    }
+}
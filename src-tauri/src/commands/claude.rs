@@ -2,7 +2,7 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Arc;
 use std::time::SystemTime;
@@ -10,6 +10,16 @@ use tauri::{AppHandle, Emitter, Manager};
 use tokio::process::{Child, Command};
 use tokio::sync::Mutex;
 
+use super::attachments::{
+    prepare_attachments, prompt_with_attachments, remove_attachments, Attachment,
+};
+use super::budget::{BudgetExceeded, BudgetTracker, RunBudget};
+use super::capabilities::claude_capabilities_async;
+use super::hook_activity::{emit_hook_activity, HookActivityState, HookLog};
+use super::notifications::{notify_run_event, permission_request, RunNotification};
+use super::stream_events::{emit_stream_events, StreamParser};
+use crate::context::CoreContext;
+
 
 /// Global state to track current Claude process
 pub struct ClaudeProcessState {
@@ -46,14 +56,18 @@ pub struct Session {
     pub project_id: String,
     /// The project path
     pub project_path: String,
-    /// Optional todo data associated with this session
-    pub todo_data: Option<serde_json::Value>,
+    /// The session's todo list from ~/.claude/todos, if it has one
+    pub todo_data: Option<Vec<crate::commands::todos::TodoItem>>,
     /// Unix timestamp when the session file was created
     pub created_at: u64,
     /// First user message content (if available)
     pub first_message: Option<String>,
     /// Timestamp of the first user message (if available)
     pub message_timestamp: Option<String>,
+    /// User metadata (title, tags, pinned flag, notes) stored by Claudio
+    pub metadata: Option<crate::commands::session_metadata::SessionMetadata>,
+    /// The git worktree the session was isolated in, if any
+    pub worktree: Option<crate::commands::worktrees::SessionWorktree>,
 }
 
 /// Represents a message entry in the JSONL file
@@ -125,6 +139,9 @@ pub struct FileEntry {
     pub size: u64,
     /// File extension (if applicable)
     pub extension: Option<String>,
+    /// Git status ("modified", "added", "deleted", "renamed", "untracked",
+    /// "ignored" or "conflicted"), if the entry is in a git work tree and changed
+    pub git_status: Option<String>,
 }
 
 /// Finds the full path to the claude binary
@@ -149,7 +166,7 @@ pub fn get_claude_dir() -> Result<PathBuf> {
 }
 
 /// Gets the actual project path by reading the cwd from the first JSONL entry
-fn get_project_path_from_sessions(project_dir: &PathBuf) -> Result<String, String> {
+pub(crate) fn get_project_path_from_sessions(project_dir: &PathBuf) -> Result<String, String> {
     // Try to read any JSONL file in the directory
     let entries = fs::read_dir(project_dir)
         .map_err(|e| format!("Failed to read project directory: {}", e))?;
@@ -180,7 +197,7 @@ fn get_project_path_from_sessions(project_dir: &PathBuf) -> Result<String, Strin
 /// Decodes a project directory name back to its original path
 /// The directory names in ~/.claude/projects are encoded paths
 /// DEPRECATED: Use get_project_path_from_sessions instead when possible
-fn decode_project_path(encoded: &str) -> String {
+pub(crate) fn decode_project_path(encoded: &str) -> String {
     // This is a fallback - the encoding isn't reversible when paths contain hyphens
     // For example: -Users-mufeedvh-dev-jsonl-viewer could be /Users/mufeedvh/dev/jsonl-viewer
     // or /Users/mufeedvh/dev/jsonl/viewer
@@ -292,11 +309,17 @@ fn create_system_command(
 
 /// Lists all projects in the ~/.claude/projects directory
 #[tauri::command]
-pub async fn list_projects() -> Result<Vec<Project>, String> {
+pub async fn list_projects(
+    context: tauri::State<'_, CoreContext>,
+) -> Result<Vec<Project>, String> {
+    list_projects_in(&context)
+}
+
+/// Lists the projects in the context's projects directory, newest first
+pub fn list_projects_in(context: &CoreContext) -> Result<Vec<Project>, String> {
     log::info!("Listing projects from ~/.claude/projects");
 
-    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
-    let projects_dir = claude_dir.join("projects");
+    let projects_dir = context.projects_dir();
 
     if !projects_dir.exists() {
         log::warn!("Projects directory does not exist: {:?}", projects_dir);
@@ -378,14 +401,90 @@ pub async fn list_projects() -> Result<Vec<Project>, String> {
     Ok(projects)
 }
 
+/// Builds a session entry from its JSONL file in ~/.claude/projects
+fn build_session(
+    path: &PathBuf,
+    session_id: &str,
+    project_id: &str,
+    project_path: &str,
+    todos_dir: &Path,
+) -> Result<Session, String> {
+    // Get file creation time
+    let metadata =
+        fs::metadata(path).map_err(|e| format!("Failed to read file metadata: {}", e))?;
+
+    let created_at = metadata
+        .created()
+        .or_else(|_| metadata.modified())
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    // Extract first user message and timestamp
+    let (first_message, message_timestamp) = extract_first_user_message(path);
+
+    // Try to load associated todo data
+    let todo_data = crate::commands::todos::load_session_todo_items(todos_dir, session_id);
+
+    Ok(Session {
+        id: session_id.to_string(),
+        project_id: project_id.to_string(),
+        project_path: project_path.to_string(),
+        todo_data,
+        created_at,
+        first_message,
+        message_timestamp,
+        metadata: None,
+        worktree: None,
+    })
+}
+
+/// Loads a single session by project and session ID (without user metadata)
+pub fn load_session(
+    claude_dir: &Path,
+    project_id: &str,
+    session_id: &str,
+) -> Result<Session, String> {
+    let project_dir = claude_dir.join("projects").join(project_id);
+    let session_path = project_dir.join(format!("{}.jsonl", session_id));
+
+    if !session_path.exists() {
+        return Err(format!("Session file not found: {}", session_id));
+    }
+
+    let project_path = get_project_path_from_sessions(&project_dir)
+        .unwrap_or_else(|_| decode_project_path(project_id));
+
+    build_session(
+        &session_path,
+        session_id,
+        project_id,
+        &project_path,
+        &claude_dir.join("todos"),
+    )
+}
+
 /// Gets sessions for a specific project
 #[tauri::command]
-pub async fn get_project_sessions(project_id: String) -> Result<Vec<Session>, String> {
+pub async fn get_project_sessions(
+    context: tauri::State<'_, CoreContext>,
+    db: tauri::State<'_, crate::commands::agents::AgentDb>,
+    project_id: String,
+) -> Result<Vec<Session>, String> {
+    get_project_sessions_in(&context, &db, &project_id)
+}
+
+/// Lists a project's sessions with their Claudio metadata, pinned first
+pub fn get_project_sessions_in(
+    context: &CoreContext,
+    db: &crate::commands::agents::AgentDb,
+    project_id: &str,
+) -> Result<Vec<Session>, String> {
     log::info!("Getting sessions for project: {}", project_id);
 
-    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
-    let project_dir = claude_dir.join("projects").join(&project_id);
-    let todos_dir = claude_dir.join("todos");
+    let project_dir = context.project_dir(project_id);
+    let todos_dir = context.todos_dir();
 
     if !project_dir.exists() {
         return Err(format!("Project directory not found: {}", project_id));
@@ -400,10 +499,28 @@ pub async fn get_project_sessions(project_id: String) -> Result<Vec<Session>, St
                 project_id,
                 e
             );
-            decode_project_path(&project_id)
+            decode_project_path(project_id)
         }
     };
 
+    // Load user metadata and worktrees from Claudio's database
+    let (mut session_metadata, mut worktrees) = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        let metadata = crate::commands::session_metadata::load_project_metadata(&conn, project_id)
+            .unwrap_or_else(|e| {
+                log::warn!("Failed to load session metadata for {}: {}", project_id, e);
+                Default::default()
+            });
+        let worktrees = crate::commands::worktrees::load_project_worktrees(&conn, &project_path)
+            .unwrap_or_else(|e| {
+                log::warn!("Failed to load worktrees for {}: {}", project_path, e);
+                Default::default()
+            });
+        (metadata, worktrees)
+    };
+    // A worktree whose session isn't recorded yet, because its run is still going
+    let pending_worktree = worktrees.remove("");
+
     let mut sessions = Vec::new();
 
     // Read all JSONL files in the project directory
@@ -416,46 +533,34 @@ pub async fn get_project_sessions(project_id: String) -> Result<Vec<Session>, St
 
         if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
             if let Some(session_id) = path.file_stem().and_then(|s| s.to_str()) {
-                // Get file creation time
-                let metadata = fs::metadata(&path)
-                    .map_err(|e| format!("Failed to read file metadata: {}", e))?;
-
-                let created_at = metadata
-                    .created()
-                    .or_else(|_| metadata.modified())
-                    .unwrap_or(SystemTime::UNIX_EPOCH)
-                    .duration_since(SystemTime::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_secs();
-
-                // Extract first user message and timestamp
-                let (first_message, message_timestamp) = extract_first_user_message(&path);
-
-                // Try to load associated todo data
-                let todo_path = todos_dir.join(format!("{}.json", session_id));
-                let todo_data = if todo_path.exists() {
-                    fs::read_to_string(&todo_path)
-                        .ok()
-                        .and_then(|content| serde_json::from_str(&content).ok())
-                } else {
-                    None
-                };
-
-                sessions.push(Session {
-                    id: session_id.to_string(),
-                    project_id: project_id.clone(),
-                    project_path: project_path.clone(),
-                    todo_data,
-                    created_at,
-                    first_message,
-                    message_timestamp,
-                });
+                let mut session =
+                    build_session(&path, session_id, project_id, &project_path, &todos_dir)?;
+                session.metadata = session_metadata.remove(session_id);
+                session.worktree = worktrees.remove(session_id);
+                sessions.push(session);
             }
         }
     }
 
-    // Sort sessions by creation time (newest first)
-    sessions.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    // The run that created the worktree started the first session in it
+    if let Some(pending_worktree) = pending_worktree {
+        if let Some(session) = sessions
+            .iter_mut()
+            .filter(|session| session.worktree.is_none())
+            .min_by_key(|session| session.created_at)
+        {
+            session.worktree = Some(pending_worktree);
+        }
+    }
+
+    // Sort sessions: pinned first, then by creation time (newest first)
+    sessions.sort_by(|a, b| {
+        let a_pinned = a.metadata.as_ref().is_some_and(|m| m.pinned);
+        let b_pinned = b.metadata.as_ref().is_some_and(|m| m.pinned);
+        b_pinned
+            .cmp(&a_pinned)
+            .then_with(|| b.created_at.cmp(&a.created_at))
+    });
 
     log::info!(
         "Found {} sessions for project {}",
@@ -649,10 +754,17 @@ pub async fn save_system_prompt(content: String) -> Result<String, String> {
 }
 
 /// Saves the Claude settings file
+///
+/// Settings that fail schema validation are refused unless `force` is set.
 #[tauri::command]
-pub async fn save_claude_settings(settings: serde_json::Value) -> Result<String, String> {
+pub async fn save_claude_settings(
+    settings: serde_json::Value,
+    force: Option<bool>,
+) -> Result<String, String> {
     log::info!("Saving Claude settings");
 
+    crate::commands::settings::check_settings_before_write(&settings, force.unwrap_or(false))?;
+
     let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
     let settings_path = claude_dir.join("settings.json");
 
@@ -786,8 +898,18 @@ pub async fn save_claude_md_file(file_path: String, content: String) -> Result<S
 /// Loads the JSONL history for a specific session
 #[tauri::command]
 pub async fn load_session_history(
+    context: tauri::State<'_, CoreContext>,
     session_id: String,
     project_id: String,
+) -> Result<Vec<serde_json::Value>, String> {
+    load_session_history_in(&context, &session_id, &project_id)
+}
+
+/// Reads a session's transcript entries
+pub fn load_session_history_in(
+    context: &CoreContext,
+    session_id: &str,
+    project_id: &str,
 ) -> Result<Vec<serde_json::Value>, String> {
     log::info!(
         "Loading session history for session: {} in project: {}",
@@ -795,11 +917,7 @@ pub async fn load_session_history(
         project_id
     );
 
-    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
-    let session_path = claude_dir
-        .join("projects")
-        .join(&project_id)
-        .join(format!("{}.jsonl", session_id));
+    let session_path = context.session_path(project_id, session_id);
 
     if !session_path.exists() {
         return Err(format!("Session file not found: {}", session_id));
@@ -825,34 +943,49 @@ pub async fn load_session_history(
 
 
 /// Execute a new interactive Claude Code session with streaming output
+///
+/// With `use_worktree`, the session runs in a new git worktree on its own
+/// branch, which is returned so its changes can be reviewed, merged or discarded.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn execute_claude_code(
     app: AppHandle,
     project_path: String,
     prompt: String,
     model: String,
-) -> Result<(), String> {
+    budget: Option<RunBudget>,
+    profile: Option<String>,
+    use_worktree: Option<bool>,
+    attachments: Option<Vec<Attachment>>,
+) -> Result<Option<crate::commands::worktrees::SessionWorktree>, String> {
     log::info!(
         "Starting new Claude Code session in: {} with model: {}",
         project_path,
         model
     );
 
-    let claude_path = find_claude_binary(&app)?;
-    
-    let args = vec![
-        "-p".to_string(),
-        prompt.clone(),
-        "--model".to_string(),
-        model.clone(),
-        "--output-format".to_string(),
-        "stream-json".to_string(),
-        "--verbose".to_string(),
-        "--dangerously-skip-permissions".to_string(),
-    ];
+    let options = RunOptions {
+        budget: budget.unwrap_or_default(),
+        profile,
+        attachments: attachments.unwrap_or_default(),
+        ..Default::default()
+    };
 
-    let cmd = create_system_command(&claude_path, args, &project_path);
-    spawn_claude_process(app, cmd, prompt, model, project_path).await
+    if !use_worktree.unwrap_or(false) {
+        start_headless_run(app, project_path, prompt, model, Vec::new(), options).await?;
+        return Ok(None);
+    }
+
+    let (worktree, handle) = crate::commands::worktrees::start_run_in_worktree(
+        &app,
+        &project_path,
+        prompt,
+        model,
+        options,
+    )
+    .await?;
+    crate::commands::worktrees::bind_session_on_completion(&app, worktree.id, handle.outcome);
+    Ok(Some(worktree))
 }
 
 /// Continue an existing Claude Code conversation with streaming output
@@ -862,6 +995,9 @@ pub async fn continue_claude_code(
     project_path: String,
     prompt: String,
     model: String,
+    budget: Option<RunBudget>,
+    profile: Option<String>,
+    attachments: Option<Vec<Attachment>>,
 ) -> Result<(), String> {
     log::info!(
         "Continuing Claude Code conversation in: {} with model: {}",
@@ -869,32 +1005,33 @@ pub async fn continue_claude_code(
         model
     );
 
-    let claude_path = find_claude_binary(&app)?;
-    
     let args = vec![
         "-c".to_string(), // Continue flag
-        "-p".to_string(),
-        prompt.clone(),
-        "--model".to_string(),
-        model.clone(),
-        "--output-format".to_string(),
-        "stream-json".to_string(),
-        "--verbose".to_string(),
-        "--dangerously-skip-permissions".to_string(),
     ];
 
-    let cmd = create_system_command(&claude_path, args, &project_path);
-    spawn_claude_process(app, cmd, prompt, model, project_path).await
+    let options = RunOptions {
+        budget: budget.unwrap_or_default(),
+        profile,
+        attachments: attachments.unwrap_or_default(),
+        ..Default::default()
+    };
+    start_headless_run(app, project_path, prompt, model, args, options)
+        .await
+        .map(|_| ())
 }
 
 /// Resume an existing Claude Code session by ID with streaming output
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn resume_claude_code(
     app: AppHandle,
     project_path: String,
     session_id: String,
     prompt: String,
     model: String,
+    budget: Option<RunBudget>,
+    profile: Option<String>,
+    attachments: Option<Vec<Attachment>>,
 ) -> Result<(), String> {
     log::info!(
         "Resuming Claude Code session: {} in: {} with model: {}",
@@ -903,57 +1040,55 @@ pub async fn resume_claude_code(
         model
     );
 
-    let claude_path = find_claude_binary(&app)?;
-    
-    let args = vec![
-        "--resume".to_string(),
-        session_id.clone(),
-        "-p".to_string(),
-        prompt.clone(),
-        "--model".to_string(),
-        model.clone(),
-        "--output-format".to_string(),
-        "stream-json".to_string(),
-        "--verbose".to_string(),
-        "--dangerously-skip-permissions".to_string(),
-    ];
+    let args = vec!["--resume".to_string(), session_id.clone()];
 
-    let cmd = create_system_command(&claude_path, args, &project_path);
-    spawn_claude_process(app, cmd, prompt, model, project_path).await
+    let options = RunOptions {
+        budget: budget.unwrap_or_default(),
+        profile,
+        attachments: attachments.unwrap_or_default(),
+        ..Default::default()
+    };
+    start_headless_run(app, project_path, prompt, model, args, options)
+        .await
+        .map(|_| ())
 }
 
 /// Cancel the currently running Claude Code execution
+///
+/// The run's process group gets SIGINT, then SIGTERM, then SIGKILL on the
+/// configured timeouts, so the CLI can clean up and the processes it started
+/// are stopped too. Returns the stage the run exited at, if one was running.
 #[tauri::command]
 pub async fn cancel_claude_execution(
     app: AppHandle,
     session_id: Option<String>,
-) -> Result<(), String> {
+) -> Result<Option<crate::process::TerminationReport>, String> {
     log::info!(
         "Cancelling Claude Code execution for session: {:?}",
         session_id
     );
 
-    let mut killed = false;
+    let timeouts = super::proxy::get_termination_timeouts().await;
+    let mut report = None;
     let mut attempted_methods = Vec::new();
 
-    // Method 1: Try to find and kill via ProcessRegistry using session ID
+    // Method 1: Try to find and stop via ProcessRegistry using session ID
     if let Some(sid) = &session_id {
         let registry = app.state::<crate::process::ProcessRegistryState>();
         match registry.0.get_claude_session_by_id(sid) {
             Ok(Some(process_info)) => {
                 log::info!("Found process in registry for session {}: run_id={}, PID={}", 
                     sid, process_info.run_id, process_info.pid);
-                match registry.0.kill_process(process_info.run_id).await {
-                    Ok(success) => {
-                        if success {
-                            log::info!("Successfully killed process via registry");
-                            killed = true;
-                        } else {
-                            log::warn!("Registry kill returned false");
-                        }
+                match registry.0.kill_process(process_info.run_id, timeouts).await {
+                    Ok(Some(result)) => {
+                        log::info!("Stopped process via registry at {:?}", result.stage);
+                        report = Some(result);
+                    }
+                    Ok(None) => {
+                        log::warn!("Registry no longer had the process");
                     }
                     Err(e) => {
-                        log::warn!("Failed to kill via registry: {}", e);
+                        log::warn!("Failed to stop via registry: {}", e);
                     }
                 }
                 attempted_methods.push("registry");
@@ -968,52 +1103,15 @@ pub async fn cancel_claude_execution(
     }
 
     // Method 2: Try the legacy approach via ClaudeProcessState
-    if !killed {
+    if !report.as_ref().is_some_and(|r| r.stopped()) {
         let claude_state = app.state::<ClaudeProcessState>();
         let mut current_process = claude_state.current_process.lock().await;
 
-        if let Some(mut child) = current_process.take() {
-            // Try to get the PID before killing
-            let pid = child.id();
-            log::info!("Attempting to kill Claude process via ClaudeProcessState with PID: {:?}", pid);
-
-            // Kill the process
-            match child.kill().await {
-                Ok(_) => {
-                    log::info!("Successfully killed Claude process via ClaudeProcessState");
-                    killed = true;
-                }
-                Err(e) => {
-                    log::error!("Failed to kill Claude process via ClaudeProcessState: {}", e);
-                    
-                    // Method 3: If we have a PID, try system kill as last resort
-                    if let Some(pid) = pid {
-                        log::info!("Attempting system kill as last resort for PID: {}", pid);
-                        let kill_result = if cfg!(target_os = "windows") {
-                            std::process::Command::new("taskkill")
-                                .args(["/F", "/PID", &pid.to_string()])
-                                .output()
-                        } else {
-                            std::process::Command::new("kill")
-                                .args(["-KILL", &pid.to_string()])
-                                .output()
-                        };
-                        
-                        match kill_result {
-                            Ok(output) if output.status.success() => {
-                                log::info!("Successfully killed process via system command");
-                                killed = true;
-                            }
-                            Ok(output) => {
-                                let stderr = String::from_utf8_lossy(&output.stderr);
-                                log::error!("System kill failed: {}", stderr);
-                            }
-                            Err(e) => {
-                                log::error!("Failed to execute system kill command: {}", e);
-                            }
-                        }
-                    }
-                }
+        if let Some(child) = current_process.take() {
+            log::info!("Stopping Claude process via ClaudeProcessState");
+            if let Some(result) = stop_child(child, timeouts).await {
+                log::info!("Stopped Claude process via ClaudeProcessState at {:?}", result.stage);
+                report = Some(result);
             }
             attempted_methods.push("claude_state");
         } else {
@@ -1021,7 +1119,7 @@ pub async fn cancel_claude_execution(
         }
     }
 
-    if !killed && attempted_methods.is_empty() {
+    if report.is_none() && attempted_methods.is_empty() {
         log::warn!("No active Claude process found to cancel");
     }
 
@@ -1037,13 +1135,17 @@ pub async fn cancel_claude_execution(
     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
     let _ = app.emit("claude-complete", false);
     
-    if killed {
-        log::info!("Claude process cancellation completed successfully");
-    } else if !attempted_methods.is_empty() {
-        log::warn!("Claude process cancellation attempted but process may have already exited. Attempted methods: {:?}", attempted_methods);
+    match &report {
+        Some(report) if report.stopped() => {
+            log::info!("Claude process cancellation completed at {:?}", report.stage)
+        }
+        _ if !attempted_methods.is_empty() => {
+            log::warn!("Claude process cancellation attempted but process may still be running. Attempted methods: {:?}", attempted_methods)
+        }
+        _ => {}
     }
     
-    Ok(())
+    Ok(report)
 }
 
 /// Get all running Claude sessions
@@ -1068,15 +1170,238 @@ pub async fn get_claude_session_output(
     }
 }
 
+/// Options for a run started through spawn_claude_process
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// Run alongside other runs instead of replacing the interactive run kept in
+    /// ClaudeProcessState. Background runs only emit session-scoped events.
+    pub background: bool,
+    /// Limits after which the run is stopped
+    pub budget: RunBudget,
+    /// Execution profile to use instead of the project's or the default one
+    pub profile: Option<String>,
+    /// Files attached to the prompt; copied to a temp directory for the run
+    pub attachments: Vec<Attachment>,
+}
+
+/// Outcome of a finished Claude run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunOutcome {
+    /// Claude's session ID, if the init message was received
+    pub session_id: Option<String>,
+    /// Whether the process exited successfully
+    pub success: bool,
+    /// The process exit code, if it exited normally
+    pub exit_code: Option<i32>,
+    /// The final `result` message from the stream, if the CLI emitted one
+    pub result: Option<serde_json::Value>,
+    /// The limit that stopped the run, if it was stopped for exceeding its budget
+    pub budget_exceeded: Option<BudgetExceeded>,
+    /// Whether the run was cancelled, by the user or the process monitor
+    pub cancelled: bool,
+}
+
+/// Identifies a run once the CLI has reported its session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunStarted {
+    pub session_id: String,
+    /// The run's ID in the process registry
+    pub run_id: i64,
+}
+
+/// Handle to a run started through spawn_claude_process
+pub struct RunHandle {
+    /// PID of the spawned Claude process
+    pub pid: u32,
+    /// Resolves once the init message has been received and the run registered;
+    /// dropped without a value if the process exits before that
+    pub started: tokio::sync::oneshot::Receiver<RunStarted>,
+    /// Resolves once the process has exited and its output has been drained
+    pub outcome: tokio::sync::oneshot::Receiver<RunOutcome>,
+}
+
+/// Starts a headless `claude -p` run with streaming JSON output
+///
+/// The run's execution profile (see `RunOptions::profile`) supplies environment
+/// overrides, extra CLI arguments and the model used when `model` is empty. With
+/// no model from either, the CLI's own default is used. Attachments are
+/// referenced from the prompt and removed once the process exits.
+pub(crate) async fn start_headless_run(
+    app: AppHandle,
+    project_path: String,
+    prompt: String,
+    model: String,
+    extra_args: Vec<String>,
+    options: RunOptions,
+) -> Result<RunHandle, String> {
+    let claude_path = find_claude_binary(&app)?;
+    let capabilities = claude_capabilities_async(&app, &claude_path).await?;
+    let profile =
+        super::profiles::resolve_execution_profile(options.profile.as_deref(), &project_path)
+            .await?;
+
+    let model = match profile.as_ref().and_then(|p| p.default_model.clone()) {
+        Some(default_model) if model.trim().is_empty() => default_model,
+        _ => model,
+    };
+
+    let running_command_lines = app
+        .state::<crate::process::ProcessRegistryState>()
+        .0
+        .command_lines();
+    let attachments = prepare_attachments(&options.attachments, &running_command_lines)?;
+    let full_prompt = match attachments {
+        Some(ref attachments) => prompt_with_attachments(&prompt, &attachments.paths),
+        None => prompt.clone(),
+    };
+
+    let mut args = vec!["-p".to_string(), full_prompt];
+    if let Some(ref attachments) = attachments {
+        // Let Claude read the attachments outside the project directory
+        args.push("--add-dir".to_string());
+        args.push(attachments.dir.to_string_lossy().to_string());
+    }
+    if !model.trim().is_empty() {
+        args.push("--model".to_string());
+        args.push(model.clone());
+    }
+    args.extend([
+        "--output-format".to_string(),
+        "stream-json".to_string(),
+        "--verbose".to_string(),
+    ]);
+    args.extend(capabilities.skip_permissions_args());
+    args.extend(extra_args);
+    if let Some(ref profile) = profile {
+        args.extend(profile.extra_args.iter().cloned());
+    }
+
+    // Report missing options clearly instead of as claude's usage error
+    if let Err(e) = capabilities.check_args(&args) {
+        if let Some(ref attachments) = attachments {
+            remove_attachments(&attachments.dir);
+        }
+        return Err(e);
+    }
+
+    let attachments_dir = attachments.map(|attachments| attachments.dir);
+    let mut cmd = create_system_command(&claude_path, args, &project_path);
+    if let Some(ref profile) = profile {
+        if let Err(e) = super::profiles::apply_profile_env(&mut cmd, profile).await {
+            if let Some(ref dir) = attachments_dir {
+                remove_attachments(dir);
+            }
+            return Err(e);
+        }
+    }
+    spawn_claude_process(
+        app,
+        cmd,
+        prompt,
+        model,
+        project_path,
+        options,
+        attachments_dir,
+    )
+    .await
+}
+
+/// Emits `claude-budget-exceeded` and terminates a run that crossed its budget,
+/// through the registry once the run is registered there. Returns whether the
+/// session-scoped event was emitted, which needs the session ID the CLI reports
+/// in its init message.
+fn stop_over_budget(
+    app: &AppHandle,
+    registry: Arc<crate::process::ProcessRegistry>,
+    run_id: Option<i64>,
+    pid: u32,
+    session_id: Option<String>,
+    background: bool,
+    exceeded: BudgetExceeded,
+) -> bool {
+    log::warn!("Stopping Claude process {}: {}", pid, exceeded.reason);
+
+    if let Some(ref session_id) = session_id {
+        let _ = app.emit(&format!("claude-budget-exceeded:{}", session_id), &exceeded);
+    }
+    // Also emit to the generic event for backward compatibility
+    if !background {
+        let _ = app.emit("claude-budget-exceeded", &exceeded);
+    }
+
+    tokio::spawn(async move {
+        let timeouts = super::proxy::get_termination_timeouts().await;
+        // The registry checks the PID still belongs to the run before signalling it
+        let result = match run_id {
+            Some(run_id) => registry.kill_process(run_id, timeouts).await.map(|_| ()),
+            None => tokio::task::spawn_blocking(move || {
+                crate::process::terminate_process(pid, &timeouts)
+            })
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|result| result.map(|_| ())),
+        };
+        if let Err(e) = result {
+            log::error!("Failed to stop Claude process {}: {}", pid, e);
+        }
+    });
+    session_id.is_some()
+}
+
+/// Stops a child taken out of ClaudeProcessState together with its process
+/// group (SIGINT, then SIGTERM, then SIGKILL), killing the child alone if that
+/// fails, and reaps it. Returns `None` if it had already exited or wasn't stopped
+/// through its group.
+async fn stop_child(
+    mut child: Child,
+    timeouts: crate::process::TerminationTimeouts,
+) -> Option<crate::process::TerminationReport> {
+    let Some(pid) = child.id() else {
+        log::info!("Claude process had already exited");
+        return None;
+    };
+    let result =
+        tokio::task::spawn_blocking(move || crate::process::terminate_process(pid, &timeouts))
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|result| result);
+    let report = match result {
+        Ok(report) => Some(report),
+        Err(e) => {
+            log::error!("Failed to stop Claude process {}, killing it: {}", pid, e);
+            let _ = child.kill().await;
+            None
+        }
+    };
+    // Reap the process now that it's out of the state
+    let _ = child.try_wait();
+    report
+}
+
 /// Helper function to spawn Claude process and handle streaming
-async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String, model: String, project_path: String) -> Result<(), String> {
+async fn spawn_claude_process(
+    app: AppHandle,
+    mut cmd: Command,
+    prompt: String,
+    model: String,
+    project_path: String,
+    options: RunOptions,
+    attachments_dir: Option<PathBuf>,
+) -> Result<RunHandle, String> {
     use tokio::io::{AsyncBufReadExt, BufReader};
     use std::sync::Mutex;
 
-    // Spawn the process
-    let mut child = cmd
-        .spawn()
-        .map_err(|e| format!("Failed to spawn Claude: {}", e))?;
+    // Spawn the process in its own group, so cancelling also stops what it started
+    crate::process::spawn_in_process_group(&mut cmd);
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            if let Some(ref dir) = attachments_dir {
+                remove_attachments(dir);
+            }
+            return Err(format!("Failed to spawn Claude: {}", e));
+        }
+    };
 
     // Get stdout and stderr
     let stdout = child.stdout.take().ok_or("Failed to get stdout")?;
@@ -1096,23 +1421,43 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
     // We'll extract the session ID from Claude's init message
     let session_id_holder: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
     let run_id_holder: Arc<Mutex<Option<i64>>> = Arc::new(Mutex::new(None));
-
-    // Store the child process in the global state (for backward compatibility)
+    let result_holder: Arc<Mutex<Option<serde_json::Value>>> = Arc::new(Mutex::new(None));
+    let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+    let mut started_tx = Some(started_tx);
+    let background = options.background;
+    let budget_tracker = (!options.budget.is_unlimited())
+        .then(|| Arc::new(Mutex::new(BudgetTracker::new(options.budget.clone()))));
+    let hook_log = Arc::new(Mutex::new(HookLog::default()));
+    let stream_parser = Arc::new(Mutex::new(StreamParser::default()));
+
+    // Interactive runs are kept in the global state (for backward compatibility);
+    // background runs own their child so they don't replace the interactive one
     let claude_state = app.state::<ClaudeProcessState>();
-    {
+    let background_child = if background {
+        Some(child)
+    } else {
         let mut current_process = claude_state.current_process.lock().await;
-        // If there's already a process running, kill it first
-        if let Some(mut existing_child) = current_process.take() {
-            log::warn!("Killing existing Claude process before starting new one");
-            let _ = existing_child.kill().await;
+        // If there's already a process running, stop it and what it started first
+        if let Some(existing_child) = current_process.take() {
+            log::warn!("Stopping existing Claude process before starting new one");
+            let timeouts = super::proxy::get_termination_timeouts().await;
+            stop_child(existing_child, timeouts).await;
         }
         *current_process = Some(child);
-    }
+        None
+    };
 
     // Spawn tasks to read stdout and stderr
     let app_handle = app.clone();
     let session_id_holder_clone = session_id_holder.clone();
     let run_id_holder_clone = run_id_holder.clone();
+    let result_holder_clone = result_holder.clone();
+    let budget_tracker_clone = budget_tracker.clone();
+    // Whether a budget stop reached `claude-budget-exceeded:{session_id}`
+    let budget_stop_announced = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let budget_stop_announced_stream = budget_stop_announced.clone();
+    let hook_log_clone = hook_log.clone();
+    let stream_parser_clone = stream_parser.clone();
     let registry = app.state::<crate::process::ProcessRegistryState>();
     let registry_clone = registry.0.clone();
     let project_path_clone = project_path.clone();
@@ -1120,17 +1465,55 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
     let model_clone = model.clone();
     let stdout_task = tokio::spawn(async move {
         let mut lines = stdout_reader.lines();
+        let mut permission_notified = false;
         while let Ok(Some(line)) = lines.next_line().await {
             log::debug!("Claude stdout: {}", line);
             
             // Parse the line to check for init message with session ID
             if let Ok(msg) = serde_json::from_str::<serde_json::Value>(&line) {
+                if let Some(ref tracker) = budget_tracker_clone {
+                    let exceeded = tracker.lock().unwrap().record(&msg);
+                    if let Some(exceeded) = exceeded {
+                        let session_id = session_id_holder_clone.lock().unwrap().clone();
+                        let run_id = *run_id_holder_clone.lock().unwrap();
+                        if stop_over_budget(
+                            &app_handle,
+                            registry_clone.clone(),
+                            run_id,
+                            pid,
+                            session_id,
+                            background,
+                            exceeded,
+                        ) {
+                            budget_stop_announced_stream
+                                .store(true, std::sync::atomic::Ordering::SeqCst);
+                        }
+                    }
+                }
+
+                let hook_activity = hook_log_clone.lock().unwrap().record_message(&msg, "stream");
+
+                // Notify once per run when a tool call needs a permission it doesn't have
+                if !permission_notified {
+                    if let Some(message) = permission_request(&msg) {
+                        permission_notified = true;
+                        let session_id = session_id_holder_clone.lock().unwrap().clone();
+                        notify_run_event(
+                            &app_handle,
+                            RunNotification::permission_required(session_id, &project_path_clone, message),
+                        );
+                    }
+                }
+
                 if msg["type"] == "system" && msg["subtype"] == "init" {
                     if let Some(claude_session_id) = msg["session_id"].as_str() {
                         let mut session_id_guard = session_id_holder_clone.lock().unwrap();
                         if session_id_guard.is_none() {
                             *session_id_guard = Some(claude_session_id.to_string());
                             log::info!("Extracted Claude session ID: {}", claude_session_id);
+                            app_handle
+                                .state::<HookActivityState>()
+                                .attach(claude_session_id, hook_log_clone.clone());
                             
                             // Now register with ProcessRegistry using Claude's session ID
                             match registry_clone.register_claude_session(
@@ -1144,6 +1527,12 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
                                     log::info!("Registered Claude session with run_id: {}", run_id);
                                     let mut run_id_guard = run_id_holder_clone.lock().unwrap();
                                     *run_id_guard = Some(run_id);
+                                    if let Some(started_tx) = started_tx.take() {
+                                        let _ = started_tx.send(RunStarted {
+                                            session_id: claude_session_id.to_string(),
+                                            run_id,
+                                        });
+                                    }
                                 }
                                 Err(e) => {
                                     log::error!("Failed to register Claude session: {}", e);
@@ -1151,7 +1540,12 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
                             }
                         }
                     }
+                } else if msg["type"] == "result" {
+                    *result_holder_clone.lock().unwrap() = Some(msg);
                 }
+
+                let session_id = session_id_holder_clone.lock().unwrap().clone();
+                emit_hook_activity(&app_handle, session_id.as_deref(), &hook_activity);
             }
             
             // Store live output in registry if we have a run_id
@@ -1164,7 +1558,16 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
                 let _ = app_handle.emit(&format!("claude-output:{}", session_id), &line);
             }
             // Also emit to the generic event for backward compatibility
-            let _ = app_handle.emit("claude-output", &line);
+            if !background {
+                let _ = app_handle.emit("claude-output", &line);
+            }
+
+            let session_id = session_id_holder_clone.lock().unwrap().clone();
+            let events = stream_parser_clone
+                .lock()
+                .unwrap()
+                .parse_line(&line, session_id.as_deref());
+            emit_stream_events(&app_handle, &events, background);
         }
     });
 
@@ -1174,54 +1577,151 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
         let mut lines = stderr_reader.lines();
         while let Ok(Some(line)) = lines.next_line().await {
             log::error!("Claude stderr: {}", line);
+            let hook_activity = hook_log.lock().unwrap().record_stderr(&line);
+            let session_id = session_id_holder_clone2.lock().unwrap().clone();
+            emit_hook_activity(&app_handle_stderr, session_id.as_deref(), &hook_activity);
             // Emit error lines to the frontend with session isolation if we have session ID
             if let Some(ref session_id) = *session_id_holder_clone2.lock().unwrap() {
                 let _ = app_handle_stderr.emit(&format!("claude-error:{}", session_id), &line);
             }
             // Also emit to the generic event for backward compatibility
-            let _ = app_handle_stderr.emit("claude-error", &line);
+            if !background {
+                let _ = app_handle_stderr.emit("claude-error", &line);
+            }
+
+            let events = stream_parser
+                .lock()
+                .unwrap()
+                .parse_stderr(&line, session_id.as_deref());
+            emit_stream_events(&app_handle_stderr, &events, background);
         }
     });
 
+    // Stop the run once its time limit is reached
+    if let Some(tracker) = budget_tracker.clone() {
+        let max_duration = tracker.lock().unwrap().max_duration();
+        if let Some(max_duration) = max_duration {
+            let app_handle_timer = app.clone();
+            let session_id_holder_timer = session_id_holder.clone();
+            let run_id_holder_timer = run_id_holder.clone();
+            let registry_timer = registry.0.clone();
+            let budget_stop_announced_timer = budget_stop_announced.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(max_duration).await;
+                let exceeded = tracker.lock().unwrap().check();
+                if let Some(exceeded) = exceeded {
+                    let session_id = session_id_holder_timer.lock().unwrap().clone();
+                    let run_id = *run_id_holder_timer.lock().unwrap();
+                    if stop_over_budget(
+                        &app_handle_timer,
+                        registry_timer,
+                        run_id,
+                        pid,
+                        session_id,
+                        background,
+                        exceeded,
+                    ) {
+                        budget_stop_announced_timer
+                            .store(true, std::sync::atomic::Ordering::SeqCst);
+                    }
+                }
+            });
+        }
+    }
+
     // Wait for the process to complete
+    let (outcome_tx, outcome_rx) = tokio::sync::oneshot::channel();
     let app_handle_wait = app.clone();
     let claude_state_wait = claude_state.current_process.clone();
     let session_id_holder_clone3 = session_id_holder.clone();
     let run_id_holder_clone2 = run_id_holder.clone();
     let registry_clone2 = registry.0.clone();
+    let project_path_wait = project_path.clone();
+    let run_started = std::time::Instant::now();
     tokio::spawn(async move {
         let _ = stdout_task.await;
         let _ = stderr_task.await;
 
-        // Get the child from the state to wait on it
-        let mut current_process = claude_state_wait.lock().await;
-        if let Some(mut child) = current_process.take() {
-            match child.wait().await {
-                Ok(status) => {
-                    log::info!("Claude process exited with status: {}", status);
-                    // Add a small delay to ensure all messages are processed
-                    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-                    if let Some(ref session_id) = *session_id_holder_clone3.lock().unwrap() {
-                        let _ = app_handle_wait.emit(
-                            &format!("claude-complete:{}", session_id),
-                            status.success(),
-                        );
-                    }
-                    // Also emit to the generic event for backward compatibility
+        // Get the child (from the state for interactive runs) to wait on it; an
+        // interactive run that's no longer there was taken out to be stopped, and
+        // a child with another PID belongs to the run that replaced this one
+        let wait_result = match background_child {
+            Some(mut child) => Some(child.wait().await),
+            None => {
+                let mut current_process = claude_state_wait.lock().await;
+                let own_child = current_process
+                    .as_ref()
+                    .is_some_and(|child| child.id() == Some(pid));
+                match own_child.then(|| current_process.take()).flatten() {
+                    Some(mut child) => Some(child.wait().await),
+                    None => None,
+                }
+            }
+        };
+
+        // No limit is enforced once the process is gone, so its PID can't be reused
+        let budget_exceeded = budget_tracker.as_ref().and_then(|tracker| {
+            let mut tracker = tracker.lock().unwrap();
+            tracker.finish();
+            tracker.exceeded()
+        });
+
+        let session_id = session_id_holder_clone3.lock().unwrap().clone();
+        // A limit crossed before the CLI reported its session only reached the
+        // generic event; tell the session why it stopped now that it's known
+        if let (Some(exceeded), Some(session_id)) = (&budget_exceeded, &session_id) {
+            if !budget_stop_announced.load(std::sync::atomic::Ordering::SeqCst) {
+                let _ = app_handle_wait
+                    .emit(&format!("claude-budget-exceeded:{}", session_id), exceeded);
+            }
+        }
+        let mut outcome = RunOutcome {
+            session_id: session_id.clone(),
+            success: false,
+            exit_code: None,
+            result: result_holder.lock().unwrap().take(),
+            budget_exceeded,
+            cancelled: wait_result.is_none(),
+        };
+        if let Some(run_id) = *run_id_holder_clone2.lock().unwrap() {
+            // A budget stop goes through the registry too, but isn't a cancellation
+            let stopped = registry_clone2.take_cancelled(run_id);
+            outcome.cancelled |= stopped && outcome.budget_exceeded.is_none();
+        }
+
+        match wait_result {
+            Some(Ok(status)) => {
+                log::info!("Claude process exited with status: {}", status);
+                outcome.success = status.success();
+                outcome.exit_code = status.code();
+                // Add a small delay to ensure all messages are processed
+                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+                if let Some(ref session_id) = session_id {
+                    let _ = app_handle_wait.emit(
+                        &format!("claude-complete:{}", session_id),
+                        status.success(),
+                    );
+                }
+                // Also emit to the generic event for backward compatibility
+                if !background {
                     let _ = app_handle_wait.emit("claude-complete", status.success());
                 }
-                Err(e) => {
-                    log::error!("Failed to wait for Claude process: {}", e);
-                    // Add a small delay to ensure all messages are processed
-                    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-                    if let Some(ref session_id) = *session_id_holder_clone3.lock().unwrap() {
-                        let _ = app_handle_wait
-                            .emit(&format!("claude-complete:{}", session_id), false);
-                    }
-                    // Also emit to the generic event for backward compatibility
+            }
+            Some(Err(e)) => {
+                log::error!("Failed to wait for Claude process: {}", e);
+                // Add a small delay to ensure all messages are processed
+                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+                if let Some(ref session_id) = session_id {
+                    let _ = app_handle_wait
+                        .emit(&format!("claude-complete:{}", session_id), false);
+                }
+                // Also emit to the generic event for backward compatibility
+                if !background {
                     let _ = app_handle_wait.emit("claude-complete", false);
                 }
             }
+            // The process was taken out of the state by a cancellation
+            None => {}
         }
 
         // Unregister from ProcessRegistry if we have a run_id
@@ -1229,11 +1729,22 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
             let _ = registry_clone2.unregister_process(run_id);
         }
 
-        // Clear the process from state
-        *current_process = None;
+        if let Some(ref dir) = attachments_dir {
+            remove_attachments(dir);
+        }
+
+        notify_run_event(
+            &app_handle_wait,
+            RunNotification::from_outcome(&outcome, &project_path_wait, run_started.elapsed()),
+        );
+        let _ = outcome_tx.send(outcome);
     });
 
-    Ok(())
+    Ok(RunHandle {
+        pid,
+        started: started_rx,
+        outcome: outcome_rx,
+    })
 }
 
 
@@ -1262,6 +1773,7 @@ pub async fn list_directory_contents(directory_path: String) -> Result<Vec<FileE
     }
 
     let mut entries = Vec::new();
+    let mut git_statuses = crate::commands::files::directory_git_status(&path);
 
     let dir_entries =
         fs::read_dir(&path).map_err(|e| format!("Failed to read directory: {}", e))?;
@@ -1296,6 +1808,7 @@ pub async fn list_directory_contents(directory_path: String) -> Result<Vec<FileE
         };
 
         entries.push(FileEntry {
+            git_status: git_statuses.remove(&name),
             name,
             path: entry_path.to_string_lossy().to_string(),
             is_directory: metadata.is_dir(),
@@ -1314,123 +1827,6 @@ pub async fn list_directory_contents(directory_path: String) -> Result<Vec<FileE
     Ok(entries)
 }
 
-/// Search for files and directories matching a pattern
-#[tauri::command]
-pub async fn search_files(base_path: String, query: String) -> Result<Vec<FileEntry>, String> {
-    log::info!("Searching files in '{}' for: '{}'", base_path, query);
-
-    // Check if path is empty
-    if base_path.trim().is_empty() {
-        log::error!("Base path is empty or whitespace");
-        return Err("Base path cannot be empty".to_string());
-    }
-
-    // Check if query is empty
-    if query.trim().is_empty() {
-        log::warn!("Search query is empty, returning empty results");
-        return Ok(Vec::new());
-    }
-
-    let path = PathBuf::from(&base_path);
-    log::debug!("Resolved search base path: {:?}", path);
-
-    if !path.exists() {
-        log::error!("Base path does not exist: {:?}", path);
-        return Err(format!("Path does not exist: {}", base_path));
-    }
-
-    let query_lower = query.to_lowercase();
-    let mut results = Vec::new();
-
-    search_files_recursive(&path, &path, &query_lower, &mut results, 0)?;
-
-    // Sort by relevance: exact matches first, then by name
-    results.sort_by(|a, b| {
-        let a_exact = a.name.to_lowercase() == query_lower;
-        let b_exact = b.name.to_lowercase() == query_lower;
-
-        match (a_exact, b_exact) {
-            (true, false) => std::cmp::Ordering::Less,
-            (false, true) => std::cmp::Ordering::Greater,
-            _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
-        }
-    });
-
-    // Limit results to prevent overwhelming the UI
-    results.truncate(50);
-
-    Ok(results)
-}
-
-fn search_files_recursive(
-    current_path: &PathBuf,
-    base_path: &PathBuf,
-    query: &str,
-    results: &mut Vec<FileEntry>,
-    depth: usize,
-) -> Result<(), String> {
-    // Limit recursion depth to prevent excessive searching
-    if depth > 5 || results.len() >= 50 {
-        return Ok(());
-    }
-
-    let entries = fs::read_dir(current_path)
-        .map_err(|e| format!("Failed to read directory {:?}: {}", current_path, e))?;
-
-    for entry in entries {
-        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-        let entry_path = entry.path();
-
-        // Skip hidden files/directories
-        if let Some(name) = entry_path.file_name().and_then(|n| n.to_str()) {
-            if name.starts_with('.') {
-                continue;
-            }
-
-            // Check if name matches query
-            if name.to_lowercase().contains(query) {
-                let metadata = entry
-                    .metadata()
-                    .map_err(|e| format!("Failed to read metadata: {}", e))?;
-
-                let extension = if metadata.is_file() {
-                    entry_path
-                        .extension()
-                        .and_then(|e| e.to_str())
-                        .map(|e| e.to_string())
-                } else {
-                    None
-                };
-
-                results.push(FileEntry {
-                    name: name.to_string(),
-                    path: entry_path.to_string_lossy().to_string(),
-                    is_directory: metadata.is_dir(),
-                    size: metadata.len(),
-                    extension,
-                });
-            }
-        }
-
-        // Recurse into directories
-        if entry_path.is_dir() {
-            // Skip common directories that shouldn't be searched
-            if let Some(dir_name) = entry_path.file_name().and_then(|n| n.to_str()) {
-                if matches!(
-                    dir_name,
-                    "node_modules" | "target" | ".git" | "dist" | "build" | ".next" | "__pycache__"
-                ) {
-                    continue;
-                }
-            }
-
-            search_files_recursive(&entry_path, base_path, query, results, depth + 1)?;
-        }
-    }
-
-    Ok(())
-}
-
 /// Creates a checkpoint for the current session state
 #[tauri::command]
 pub async fn create_checkpoint(
@@ -1440,6 +1836,27 @@ pub async fn create_checkpoint(
     project_path: String,
     message_index: Option<usize>,
     description: Option<String>,
+) -> Result<crate::checkpoint::CheckpointResult, String> {
+    create_checkpoint_in(
+        &app,
+        session_id,
+        project_id,
+        project_path,
+        message_index,
+        description,
+    )
+    .await
+}
+
+/// Creates a checkpoint through the given checkpoint state from the session
+/// file in its Claude directory
+pub async fn create_checkpoint_in(
+    app: &crate::checkpoint::state::CheckpointState,
+    session_id: String,
+    project_id: String,
+    project_path: String,
+    message_index: Option<usize>,
+    description: Option<String>,
 ) -> Result<crate::checkpoint::CheckpointResult, String> {
     log::info!(
         "Creating checkpoint for session: {} in project: {}",
@@ -1457,8 +1874,9 @@ pub async fn create_checkpoint(
         .map_err(|e| format!("Failed to get checkpoint manager: {}", e))?;
 
     // Always load current session messages from the JSONL file
-    let session_path = get_claude_dir()
-        .map_err(|e| e.to_string())?
+    let session_path = manager
+        .storage
+        .claude_dir
         .join("projects")
         .join(&project_id)
         .join(format!("{}.jsonl", session_id));
@@ -1499,6 +1917,17 @@ pub async fn restore_checkpoint(
     session_id: String,
     project_id: String,
     project_path: String,
+) -> Result<crate::checkpoint::CheckpointResult, String> {
+    restore_checkpoint_in(&app, checkpoint_id, session_id, project_id, project_path).await
+}
+
+/// Restores a checkpoint through the given checkpoint state and rewrites the session file
+pub async fn restore_checkpoint_in(
+    app: &crate::checkpoint::state::CheckpointState,
+    checkpoint_id: String,
+    session_id: String,
+    project_id: String,
+    project_path: String,
 ) -> Result<crate::checkpoint::CheckpointResult, String> {
     log::info!(
         "Restoring checkpoint: {} for session: {}",
@@ -1521,8 +1950,9 @@ pub async fn restore_checkpoint(
         .map_err(|e| format!("Failed to restore checkpoint: {}", e))?;
 
     // Update the session JSONL file with restored messages
-    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
-    let session_path = claude_dir
+    let session_path = manager
+        .storage
+        .claude_dir
         .join("projects")
         .join(&result.checkpoint.project_id)
         .join(format!("{}.jsonl", session_id));
@@ -1547,6 +1977,16 @@ pub async fn list_checkpoints(
     session_id: String,
     project_id: String,
     project_path: String,
+) -> Result<Vec<crate::checkpoint::Checkpoint>, String> {
+    list_checkpoints_in(&app, session_id, project_id, project_path).await
+}
+
+/// Lists a session's checkpoints through the given checkpoint state
+pub async fn list_checkpoints_in(
+    app: &crate::checkpoint::state::CheckpointState,
+    session_id: String,
+    project_id: String,
+    project_path: String,
 ) -> Result<Vec<crate::checkpoint::Checkpoint>, String> {
     log::info!(
         "Listing checkpoints for session: {} in project: {}",
@@ -1564,8 +2004,10 @@ pub async fn list_checkpoints(
 
 /// Forks a new timeline branch from a checkpoint
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn fork_from_checkpoint(
     app: tauri::State<'_, crate::checkpoint::state::CheckpointState>,
+    context: tauri::State<'_, CoreContext>,
     checkpoint_id: String,
     session_id: String,
     project_id: String,
@@ -1579,17 +2021,9 @@ pub async fn fork_from_checkpoint(
         new_session_id
     );
 
-    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
-
     // First, copy the session file to the new session
-    let source_session_path = claude_dir
-        .join("projects")
-        .join(&project_id)
-        .join(format!("{}.jsonl", session_id));
-    let new_session_path = claude_dir
-        .join("projects")
-        .join(&project_id)
-        .join(format!("{}.jsonl", new_session_id));
+    let source_session_path = context.session_path(&project_id, &session_id);
+    let new_session_path = context.session_path(&project_id, &new_session_id);
 
     if source_session_path.exists() {
         fs::copy(&source_session_path, &new_session_path)
@@ -1675,6 +2109,7 @@ pub async fn update_checkpoint_settings(
 /// Gets diff between two checkpoints
 #[tauri::command]
 pub async fn get_checkpoint_diff(
+    context: tauri::State<'_, CoreContext>,
     from_checkpoint_id: String,
     to_checkpoint_id: String,
     session_id: String,
@@ -1688,8 +2123,7 @@ pub async fn get_checkpoint_diff(
         to_checkpoint_id
     );
 
-    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
-    let storage = CheckpointStorage::new(claude_dir);
+    let storage = CheckpointStorage::new(context.claude_dir.clone());
 
     // Load both checkpoints
     let (from_checkpoint, from_files, _) = storage
@@ -1953,25 +2387,19 @@ pub async fn track_session_messages(
 
 /// Gets hooks configuration from settings at specified scope
 #[tauri::command]
-pub async fn get_hooks_config(scope: String, project_path: Option<String>) -> Result<serde_json::Value, String> {
+pub async fn get_hooks_config(
+    context: tauri::State<'_, CoreContext>,
+    scope: String,
+    project_path: Option<String>,
+) -> Result<serde_json::Value, String> {
     log::info!("Getting hooks config for scope: {}, project: {:?}", scope, project_path);
 
-    let settings_path = match scope.as_str() {
-        "user" => {
-            get_claude_dir()
-                .map_err(|e| e.to_string())?
-                .join("settings.json")
-        },
-        "project" => {
-            let path = project_path.ok_or("Project path required for project scope")?;
-            PathBuf::from(path).join(".claude").join("settings.json")
-        },
-        "local" => {
-            let path = project_path.ok_or("Project path required for local scope")?;
-            PathBuf::from(path).join(".claude").join("settings.local.json")
-        },
-        _ => return Err("Invalid scope".to_string())
-    };
+    let settings_path =
+        crate::commands::settings::scope_settings_path(
+            &context,
+            &scope,
+            project_path.as_deref(),
+        )?;
 
     if !settings_path.exists() {
         log::info!("Settings file does not exist at {:?}, returning empty hooks", settings_path);
@@ -1988,36 +2416,33 @@ pub async fn get_hooks_config(scope: String, project_path: Option<String>) -> Re
 }
 
 /// Updates hooks configuration in settings at specified scope
+///
+/// Hooks that fail schema validation are refused unless `force` is set.
 #[tauri::command]
 pub async fn update_hooks_config(
+    context: tauri::State<'_, CoreContext>,
     scope: String, 
     hooks: serde_json::Value,
-    project_path: Option<String>
+    project_path: Option<String>,
+    force: Option<bool>,
 ) -> Result<String, String> {
     log::info!("Updating hooks config for scope: {}, project: {:?}", scope, project_path);
 
-    let settings_path = match scope.as_str() {
-        "user" => {
-            get_claude_dir()
-                .map_err(|e| e.to_string())?
-                .join("settings.json")
-        },
-        "project" => {
-            let path = project_path.ok_or("Project path required for project scope")?;
-            let claude_dir = PathBuf::from(path).join(".claude");
-            fs::create_dir_all(&claude_dir)
-                .map_err(|e| format!("Failed to create .claude directory: {}", e))?;
-            claude_dir.join("settings.json")
-        },
-        "local" => {
-            let path = project_path.ok_or("Project path required for local scope")?;
-            let claude_dir = PathBuf::from(path).join(".claude");
-            fs::create_dir_all(&claude_dir)
-                .map_err(|e| format!("Failed to create .claude directory: {}", e))?;
-            claude_dir.join("settings.local.json")
-        },
-        _ => return Err("Invalid scope".to_string())
-    };
+    crate::commands::settings::check_settings_before_write(
+        &serde_json::json!({ "hooks": hooks }),
+        force.unwrap_or(false),
+    )?;
+
+    let settings_path =
+        crate::commands::settings::scope_settings_path(
+            &context,
+            &scope,
+            project_path.as_deref(),
+        )?;
+    if let Some(parent) = settings_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+    }
 
     // Read existing settings or create new
     let mut settings = if settings_path.exists() {
@@ -0,0 +1,346 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::task::JoinHandle;
+
+use crate::context::CoreContext;
+
+/// How often the projects directory is scanned for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(1000);
+/// A JSONL file modified within this window is considered an active session
+const ACTIVE_WINDOW: Duration = Duration::from_secs(120);
+/// A tracked session that has not been written for this long is dropped
+const IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// A Claude Code session being written by a process Claudio did not spawn
+/// (e.g. `claude` running in a terminal)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalSession {
+    /// The session ID (JSONL file name without extension)
+    pub session_id: String,
+    /// The project ID (directory name under ~/.claude/projects)
+    pub project_id: String,
+    /// The working directory of the session, once seen in the transcript
+    pub project_path: Option<String>,
+    /// Absolute path to the JSONL transcript
+    pub file_path: String,
+    /// Unix timestamp of the last write to the transcript
+    pub last_modified: u64,
+    /// Number of lines streamed since the session was detected
+    pub lines_streamed: u64,
+}
+
+/// Tail position for a tracked session
+struct TailState {
+    session: ExternalSession,
+    offset: u64,
+}
+
+/// Watches ~/.claude/projects for sessions written by terminal `claude` instances
+/// and streams their new lines as `claude-output:{session_id}` events
+#[derive(Default)]
+pub struct ExternalSessionWatcherState {
+    /// The polling task, aborted when the watcher stops
+    task: Mutex<Option<JoinHandle<()>>>,
+    sessions: Arc<Mutex<HashMap<String, TailState>>>,
+}
+
+/// A JSONL file found during a scan
+struct ScannedFile {
+    path: PathBuf,
+    project_id: String,
+    session_id: String,
+    len: u64,
+    modified: SystemTime,
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Finds session files modified after `since`
+fn scan_active_files(projects_dir: &Path, since: SystemTime) -> Vec<ScannedFile> {
+    let mut files = Vec::new();
+
+    let Ok(projects) = fs::read_dir(projects_dir) else {
+        return files;
+    };
+
+    for project in projects.flatten() {
+        let project_path = project.path();
+        let Some(project_id) = project_path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if !project_path.is_dir() || project_id.starts_with('.') {
+            continue;
+        }
+
+        let Ok(entries) = fs::read_dir(&project_path) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("jsonl") {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let Ok(modified) = metadata.modified() else {
+                continue;
+            };
+            if modified < since {
+                continue;
+            }
+            if let Some(session_id) = path.file_stem().and_then(|s| s.to_str()) {
+                files.push(ScannedFile {
+                    session_id: session_id.to_string(),
+                    project_id: project_id.to_string(),
+                    len: metadata.len(),
+                    modified,
+                    path,
+                });
+            }
+        }
+    }
+
+    files
+}
+
+/// Reads complete lines appended to a file since `offset`
+///
+/// Returns the new lines and the offset just past the last newline, so a
+/// line that is still being written is picked up on the next call.
+pub(crate) fn read_new_lines(path: &Path, offset: u64) -> std::io::Result<(Vec<String>, u64)> {
+    let mut file = fs::File::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer)?;
+
+    let Some(last_newline) = buffer.iter().rposition(|&b| b == b'\n') else {
+        return Ok((Vec::new(), offset));
+    };
+
+    let lines = String::from_utf8_lossy(&buffer[..last_newline])
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.to_string())
+        .collect();
+
+    Ok((lines, offset + last_newline as u64 + 1))
+}
+
+/// Runs one scan: detects new sessions, streams new lines and drops idle ones
+fn poll_once(
+    app: &AppHandle,
+    projects_dir: &Path,
+    sessions: &Mutex<HashMap<String, TailState>>,
+    initial_scan: bool,
+) {
+    let now = SystemTime::now();
+    let registry = app.state::<crate::process::ProcessRegistryState>();
+    let scanned = scan_active_files(projects_dir, now - ACTIVE_WINDOW);
+
+    let Ok(mut sessions) = sessions.lock() else {
+        return;
+    };
+
+    for file in scanned {
+        // Sessions spawned by Claudio are already streamed from their stdout
+        if let Ok(Some(_)) = registry.0.get_claude_session_by_id(&file.session_id) {
+            continue;
+        }
+
+        let tail = match sessions.get_mut(&file.session_id) {
+            Some(tail) => tail,
+            None => {
+                // Files already active when watching starts are tailed from their
+                // current end; the history is available through load_session_history.
+                // Files that show up later are new sessions and streamed from the start.
+                let offset = if initial_scan { file.len } else { 0 };
+                let session = ExternalSession {
+                    session_id: file.session_id.clone(),
+                    project_id: file.project_id.clone(),
+                    project_path: None,
+                    file_path: file.path.to_string_lossy().to_string(),
+                    last_modified: unix_secs(file.modified),
+                    lines_streamed: 0,
+                };
+                log::info!(
+                    "Detected external Claude session {} in {}",
+                    session.session_id,
+                    session.project_id
+                );
+                let _ = app.emit("external-session-detected", &session);
+                sessions
+                    .entry(file.session_id.clone())
+                    .or_insert(TailState { session, offset })
+            }
+        };
+
+        tail.session.last_modified = unix_secs(file.modified);
+
+        // The transcript was rewritten (e.g. a checkpoint restore); skip ahead
+        if file.len < tail.offset {
+            tail.offset = file.len;
+            continue;
+        }
+        if file.len == tail.offset {
+            continue;
+        }
+
+        match read_new_lines(&file.path, tail.offset) {
+            Ok((lines, new_offset)) => {
+                tail.offset = new_offset;
+                for line in lines {
+                    if tail.session.project_path.is_none() {
+                        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&line) {
+                            if let Some(cwd) = json.get("cwd").and_then(|v| v.as_str()) {
+                                tail.session.project_path = Some(cwd.to_string());
+                            }
+                        }
+                    }
+                    tail.session.lines_streamed += 1;
+                    let _ = app.emit(&format!("claude-output:{}", file.session_id), &line);
+                }
+            }
+            Err(e) => {
+                log::warn!("Failed to tail session file {:?}: {}", file.path, e);
+            }
+        }
+    }
+
+    // Drop sessions that have gone quiet
+    let idle_before = unix_secs(now - IDLE_TIMEOUT);
+    sessions.retain(|session_id, tail| {
+        if tail.session.last_modified < idle_before {
+            log::info!("External Claude session {} went idle", session_id);
+            let _ = app.emit("external-session-idle", &tail.session);
+            false
+        } else {
+            true
+        }
+    });
+}
+
+/// Starts watching for sessions written by terminal `claude` instances
+///
+/// Calling this while the watcher is already running has no effect.
+#[tauri::command]
+pub async fn start_external_session_watcher(
+    app: AppHandle,
+    context: State<'_, CoreContext>,
+    state: State<'_, ExternalSessionWatcherState>,
+) -> Result<(), String> {
+    let mut task = state.task.lock().map_err(|e| e.to_string())?;
+    if task.as_ref().is_some_and(|task| !task.is_finished()) {
+        return Ok(());
+    }
+
+    let projects_dir = context.projects_dir();
+    let sessions = state.sessions.clone();
+
+    log::info!("Starting external session watcher on {:?}", projects_dir);
+
+    *task = Some(tokio::spawn(async move {
+        let mut initial_scan = true;
+        loop {
+            poll_once(&app, &projects_dir, &sessions, initial_scan);
+            initial_scan = false;
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }));
+
+    Ok(())
+}
+
+/// Stops the external session watcher
+#[tauri::command]
+pub async fn stop_external_session_watcher(
+    state: State<'_, ExternalSessionWatcherState>,
+) -> Result<(), String> {
+    let task = state.task.lock().map_err(|e| e.to_string())?.take();
+    if let Some(task) = task {
+        // Polls are synchronous, so the task stops between them; wait for that
+        // so a poll in flight can't refill the sessions cleared below
+        task.abort();
+        let _ = task.await;
+        log::info!("External session watcher stopped");
+    }
+    state.sessions.lock().map_err(|e| e.to_string())?.clear();
+    Ok(())
+}
+
+/// Lists sessions currently being written outside Claudio
+#[tauri::command]
+pub async fn list_external_claude_sessions(
+    state: State<'_, ExternalSessionWatcherState>,
+) -> Result<Vec<ExternalSession>, String> {
+    let sessions = state.sessions.lock().map_err(|e| e.to_string())?;
+    let mut list: Vec<ExternalSession> =
+        sessions.values().map(|tail| tail.session.clone()).collect();
+    list.sort_by_key(|session| std::cmp::Reverse(session.last_modified));
+    Ok(list)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_read_new_lines_waits_for_complete_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("session.jsonl");
+        let mut file = fs::File::create(&path).unwrap();
+
+        write!(file, "{{\"a\":1}}\n{{\"b\":").unwrap();
+        file.flush().unwrap();
+
+        let (lines, offset) = read_new_lines(&path, 0).unwrap();
+        assert_eq!(lines, vec!["{\"a\":1}".to_string()]);
+        assert_eq!(offset, 8);
+
+        // The partial line is not consumed until it is terminated
+        let (lines, same_offset) = read_new_lines(&path, offset).unwrap();
+        assert!(lines.is_empty());
+        assert_eq!(same_offset, offset);
+
+        writeln!(file, "2}}").unwrap();
+        file.flush().unwrap();
+
+        let (lines, offset) = read_new_lines(&path, offset).unwrap();
+        assert_eq!(lines, vec!["{\"b\":2}".to_string()]);
+        assert_eq!(offset, fs::metadata(&path).unwrap().len());
+    }
+
+    #[test]
+    fn test_scan_active_files_skips_hidden_and_stale() {
+        let temp_dir = TempDir::new().unwrap();
+        let projects_dir = temp_dir.path();
+        fs::create_dir_all(projects_dir.join("-home-user-app")).unwrap();
+        fs::create_dir_all(projects_dir.join(".timelines")).unwrap();
+        fs::write(projects_dir.join("-home-user-app/abc.jsonl"), "{}\n").unwrap();
+        fs::write(projects_dir.join("-home-user-app/notes.txt"), "x").unwrap();
+        fs::write(projects_dir.join(".timelines/def.jsonl"), "{}\n").unwrap();
+
+        let since = SystemTime::now() - Duration::from_secs(60);
+        let files = scan_active_files(projects_dir, since);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].session_id, "abc");
+        assert_eq!(files[0].project_id, "-home-user-app");
+
+        let future = SystemTime::now() + Duration::from_secs(60);
+        assert!(scan_active_files(projects_dir, future).is_empty());
+    }
+}
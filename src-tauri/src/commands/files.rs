@@ -0,0 +1,587 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, State};
+
+use super::claude::FileEntry;
+
+/// Default number of fuzzy file search results
+const DEFAULT_FILE_RESULTS: usize = 50;
+/// Entries visited before a fuzzy file search gives up
+const MAX_WALK_ENTRIES: usize = 200_000;
+/// Default number of content search matches
+const DEFAULT_CONTENT_RESULTS: usize = 1000;
+/// Files larger than this are skipped by content search
+const MAX_CONTENT_FILE_BYTES: u64 = 4 * 1024 * 1024;
+/// Matched lines are cut to this many characters
+const MAX_LINE_CHARS: usize = 500;
+/// Matches sent per `file-search-results` event
+const EVENT_BATCH_SIZE: usize = 50;
+
+/// Cancellation flags of running content searches, by search ID
+#[derive(Default)]
+pub struct FileSearchState {
+    searches: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+/// Options for a content search
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContentSearchOptions {
+    /// Treat the query as a regular expression instead of literal text
+    #[serde(default)]
+    pub regex: bool,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Lines of context before and after each match
+    #[serde(default)]
+    pub context_lines: usize,
+    pub max_results: Option<usize>,
+    /// Only search files matching this glob, e.g. "*.rs" or "src/**"
+    pub glob: Option<String>,
+}
+
+/// A line matching a content search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentMatch {
+    pub path: String,
+    /// Path relative to the search base
+    pub relative_path: String,
+    /// 1-based line number
+    pub line_number: usize,
+    pub line: String,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+/// Totals of a finished content search, sent with `file-search-complete`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContentSearchSummary {
+    pub search_id: String,
+    pub files_searched: usize,
+    pub matches: usize,
+    /// Whether the search stopped at `max_results`
+    pub truncated: bool,
+    pub cancelled: bool,
+}
+
+/// Builds a walker that skips hidden files and anything matched by
+/// .gitignore, .ignore or git's exclude files
+fn walker(base_path: &Path) -> ignore::WalkBuilder {
+    let mut builder = ignore::WalkBuilder::new(base_path);
+    builder.hidden(true).require_git(false);
+    builder
+}
+
+fn is_boundary(prev: Option<char>, c: char) -> bool {
+    match prev {
+        None => true,
+        Some(prev) => {
+            matches!(prev, '/' | '\\' | '_' | '-' | '.' | ' ')
+                || (prev.is_lowercase() && c.is_uppercase())
+        }
+    }
+}
+
+/// Scores a path against a fuzzy query; higher is better
+///
+/// Returns None unless all query characters appear in the path in order.
+/// Matches at word boundaries, consecutive matches and matches in the file
+/// name score higher; shorter paths win ties.
+fn fuzzy_score(query: &str, path: &str) -> Option<i64> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if query.is_empty() {
+        return None;
+    }
+
+    let name_start = path.rfind(['/', '\\']).map(|i| i + 1).unwrap_or(0);
+    let mut score = 0i64;
+    let mut query_index = 0;
+    let mut prev: Option<char> = None;
+    let mut last_match: Option<usize> = None;
+
+    for (index, c) in path.char_indices() {
+        let matches = query
+            .get(query_index)
+            .is_some_and(|q| c.to_lowercase().eq(std::iter::once(*q)));
+        if matches {
+            score += 1;
+            if is_boundary(prev, c) {
+                score += 8;
+            }
+            match last_match {
+                Some(last) if index == last + prev.map_or(1, char::len_utf8) => score += 5,
+                Some(last) => score -= ((index - last) as i64).min(10),
+                None => {}
+            }
+            if index >= name_start {
+                score += 2;
+            }
+            last_match = Some(index);
+            query_index += 1;
+        }
+        prev = Some(c);
+    }
+    if query_index < query.len() {
+        return None;
+    }
+
+    let query: String = query.into_iter().collect();
+    let name = path[name_start..].to_lowercase();
+    if name == query {
+        score += 100;
+    } else if name.starts_with(&query) {
+        score += 50;
+    } else if name.contains(&query) {
+        score += 30;
+    }
+    Some(score - path.len() as i64 / 8)
+}
+
+fn file_entry(path: &Path, metadata: &fs::Metadata) -> FileEntry {
+    FileEntry {
+        name: path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("")
+            .to_string(),
+        path: path.to_string_lossy().to_string(),
+        is_directory: metadata.is_dir(),
+        size: metadata.len(),
+        extension: if metadata.is_file() {
+            path.extension()
+                .and_then(|e| e.to_str())
+                .map(|e| e.to_string())
+        } else {
+            None
+        },
+        git_status: None,
+    }
+}
+
+/// Fuzzy-searches file and directory paths under a base path, skipping ignored files
+#[tauri::command]
+pub async fn search_files(
+    base_path: String,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<FileEntry>, String> {
+    log::info!("Searching files in '{}' for: '{}'", base_path, query);
+
+    if base_path.trim().is_empty() {
+        return Err("Base path cannot be empty".to_string());
+    }
+    if query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let base = PathBuf::from(&base_path);
+    if !base.exists() {
+        return Err(format!("Path does not exist: {}", base_path));
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_FILE_RESULTS);
+    tokio::task::spawn_blocking(move || {
+        let mut scored = Vec::new();
+        for entry in walker(&base)
+            .build()
+            .flatten()
+            .skip(1)
+            .take(MAX_WALK_ENTRIES)
+        {
+            let Ok(relative) = entry.path().strip_prefix(&base) else {
+                continue;
+            };
+            if let Some(score) = fuzzy_score(&query, &relative.to_string_lossy()) {
+                scored.push((score, entry));
+            }
+        }
+
+        scored.sort_by(|(a_score, a), (b_score, b)| {
+            b_score
+                .cmp(a_score)
+                .then_with(|| a.path().as_os_str().len().cmp(&b.path().as_os_str().len()))
+        });
+
+        Ok(scored
+            .into_iter()
+            .filter_map(|(_, entry)| {
+                let metadata = entry.metadata().ok()?;
+                Some(file_entry(entry.path(), &metadata))
+            })
+            .take(limit)
+            .collect())
+    })
+    .await
+    .map_err(|e| format!("File search task failed: {}", e))?
+}
+
+/// Reads a file for content search, skipping large and binary files
+fn read_text_file(path: &Path) -> Option<String> {
+    let metadata = fs::metadata(path).ok()?;
+    if metadata.len() > MAX_CONTENT_FILE_BYTES {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(metadata.len() as usize);
+    fs::File::open(path).ok()?.read_to_end(&mut bytes).ok()?;
+    if bytes[..bytes.len().min(8192)].contains(&0) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn truncate_line(line: &str) -> String {
+    match line.char_indices().nth(MAX_LINE_CHARS) {
+        Some((index, _)) => format!("{}…", &line[..index]),
+        None => line.to_string(),
+    }
+}
+
+/// Finds the lines of a file matching a pattern, with context
+fn search_lines(
+    content: &str,
+    pattern: &regex::Regex,
+    context_lines: usize,
+    max_matches: usize,
+) -> Vec<(usize, String, Vec<String>, Vec<String>)> {
+    let lines: Vec<&str> = content.lines().collect();
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| pattern.is_match(line))
+        .take(max_matches)
+        .map(|(index, line)| {
+            let before = lines[index.saturating_sub(context_lines)..index]
+                .iter()
+                .map(|l| truncate_line(l))
+                .collect();
+            let after = lines[index + 1..(index + 1 + context_lines).min(lines.len())]
+                .iter()
+                .map(|l| truncate_line(l))
+                .collect();
+            (index + 1, truncate_line(line), before, after)
+        })
+        .collect()
+}
+
+/// Searches file contents under a base path, skipping ignored and binary files
+///
+/// Matches are streamed in batches as `file-search-results:{search_id}` events
+/// and the totals are sent as `file-search-complete:{search_id}` when done.
+#[tauri::command]
+pub async fn search_file_contents(
+    app: AppHandle,
+    state: State<'_, FileSearchState>,
+    search_id: String,
+    base_path: String,
+    query: String,
+    options: Option<ContentSearchOptions>,
+) -> Result<ContentSearchSummary, String> {
+    log::info!(
+        "Searching file contents in '{}' for: '{}' ({})",
+        base_path,
+        query,
+        search_id
+    );
+
+    let options = options.unwrap_or_default();
+    if query.is_empty() {
+        return Err("Search query cannot be empty".to_string());
+    }
+    let base = PathBuf::from(&base_path);
+    if !base.is_dir() {
+        return Err(format!("Directory does not exist: {}", base_path));
+    }
+
+    let pattern = if options.regex {
+        query.clone()
+    } else {
+        regex::escape(&query)
+    };
+    let pattern = regex::RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .build()
+        .map_err(|e| format!("Invalid regex: {}", e))?;
+
+    let mut builder = walker(&base);
+    if let Some(ref glob) = options.glob {
+        let mut overrides = ignore::overrides::OverrideBuilder::new(&base);
+        overrides
+            .add(glob)
+            .map_err(|e| format!("Invalid glob '{}': {}", glob, e))?;
+        builder.overrides(
+            overrides
+                .build()
+                .map_err(|e| format!("Invalid glob '{}': {}", glob, e))?,
+        );
+    }
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    state
+        .searches
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(search_id.clone(), cancelled.clone());
+
+    let max_results = options.max_results.unwrap_or(DEFAULT_CONTENT_RESULTS);
+    let context_lines = options.context_lines;
+    let id = search_id.clone();
+    let app_handle = app.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let event = format!("file-search-results:{}", id);
+        let mut summary = ContentSearchSummary {
+            search_id: id,
+            ..Default::default()
+        };
+        let mut batch = Vec::new();
+
+        for entry in builder.build().flatten() {
+            if cancelled.load(Ordering::Relaxed) {
+                summary.cancelled = true;
+                break;
+            }
+            if summary.matches >= max_results {
+                summary.truncated = true;
+                break;
+            }
+            if !entry.file_type().is_some_and(|t| t.is_file()) {
+                continue;
+            }
+            let Some(content) = read_text_file(entry.path()) else {
+                continue;
+            };
+            summary.files_searched += 1;
+
+            let relative_path = entry
+                .path()
+                .strip_prefix(&base)
+                .unwrap_or(entry.path())
+                .to_string_lossy()
+                .to_string();
+            let remaining = max_results - summary.matches;
+            for (line_number, line, before, after) in
+                search_lines(&content, &pattern, context_lines, remaining)
+            {
+                batch.push(ContentMatch {
+                    path: entry.path().to_string_lossy().to_string(),
+                    relative_path: relative_path.clone(),
+                    line_number,
+                    line,
+                    before,
+                    after,
+                });
+                summary.matches += 1;
+            }
+
+            if batch.len() >= EVENT_BATCH_SIZE {
+                let _ = app_handle.emit(&event, std::mem::take(&mut batch));
+            }
+        }
+
+        if !batch.is_empty() {
+            let _ = app_handle.emit(&event, batch);
+        }
+        summary
+    })
+    .await
+    .map_err(|e| format!("Content search task failed: {}", e));
+
+    if let Ok(mut searches) = state.searches.lock() {
+        searches.remove(&search_id);
+    }
+    let summary = result?;
+
+    log::info!(
+        "Content search {} found {} matches in {} files",
+        search_id,
+        summary.matches,
+        summary.files_searched
+    );
+    let _ = app.emit(&format!("file-search-complete:{}", search_id), &summary);
+    Ok(summary)
+}
+
+/// Cancels a running content search
+#[tauri::command]
+pub async fn cancel_file_search(
+    state: State<'_, FileSearchState>,
+    search_id: String,
+) -> Result<bool, String> {
+    let searches = state.searches.lock().map_err(|e| e.to_string())?;
+    match searches.get(&search_id) {
+        Some(cancelled) => {
+            cancelled.store(true, Ordering::Relaxed);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+fn status_label(xy: &str) -> &'static str {
+    let mut codes = xy.chars();
+    let (x, y) = (codes.next().unwrap_or(' '), codes.next().unwrap_or(' '));
+    match (x, y) {
+        ('!', '!') => "ignored",
+        ('?', '?') => "untracked",
+        ('U', _) | (_, 'U') | ('A', 'A') | ('D', 'D') => "conflicted",
+        ('A', _) => "added",
+        ('R', _) | ('C', _) => "renamed",
+        ('D', _) | (_, 'D') => "deleted",
+        _ => "modified",
+    }
+}
+
+fn status_rank(status: &str) -> u8 {
+    match status {
+        "conflicted" => 5,
+        "modified" | "added" | "renamed" | "deleted" => 4,
+        "untracked" => 2,
+        _ => 1,
+    }
+}
+
+/// Maps the entries of a directory to their git status, from the output of
+/// `git status --porcelain=v1 -z` run in that directory
+///
+/// `relative_dir` is the directory's path from the repository root ("" for
+/// the root). A directory with changes inside is "modified", or "untracked"
+/// if it only contains untracked files.
+fn parse_directory_status(output: &str, relative_dir: &str) -> HashMap<String, String> {
+    let prefix = if relative_dir.is_empty() {
+        String::new()
+    } else {
+        format!("{}/", relative_dir.trim_end_matches('/'))
+    };
+
+    let mut statuses: HashMap<String, String> = HashMap::new();
+    let mut records = output.split('\0');
+    while let Some(record) = records.next() {
+        if record.len() < 4 {
+            continue;
+        }
+        let (xy, path) = (&record[..2], &record[3..]);
+        if xy.starts_with('R') || xy.starts_with('C') {
+            // The original path of a rename follows
+            records.next();
+        }
+
+        let Some(remainder) = path.strip_prefix(&prefix) else {
+            continue;
+        };
+        let remainder = remainder.trim_end_matches('/');
+        let (name, nested) = match remainder.split_once('/') {
+            Some((name, _)) => (name, true),
+            None => (remainder, false),
+        };
+        if name.is_empty() {
+            continue;
+        }
+
+        let status = match (status_label(xy), nested) {
+            (status, false) => status,
+            ("ignored", true) => continue,
+            ("untracked", true) => "untracked",
+            ("conflicted", true) => "conflicted",
+            (_, true) => "modified",
+        };
+        let current = statuses.get(name).map(|s| status_rank(s)).unwrap_or(0);
+        if status_rank(status) > current {
+            statuses.insert(name.to_string(), status.to_string());
+        }
+    }
+    statuses
+}
+
+/// Gets the git status of each entry of a directory, by entry name
+///
+/// Returns an empty map when the directory is not in a git work tree.
+pub fn directory_git_status(dir: &Path) -> HashMap<String, String> {
+    let run = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+    };
+
+    let Some(toplevel) = run(&["rev-parse", "--show-toplevel"]) else {
+        return HashMap::new();
+    };
+    let relative_dir = match (
+        dir.canonicalize(),
+        Path::new(toplevel.trim()).canonicalize(),
+    ) {
+        (Ok(dir), Ok(root)) => match dir.strip_prefix(&root) {
+            Ok(relative) => relative.to_string_lossy().replace('\\', "/"),
+            Err(_) => return HashMap::new(),
+        },
+        _ => return HashMap::new(),
+    };
+
+    match run(&[
+        "status",
+        "--porcelain=v1",
+        "-z",
+        "--ignored=matching",
+        "--untracked-files=normal",
+        "--",
+        ".",
+    ]) {
+        Some(output) => parse_directory_status(&output, &relative_dir),
+        None => HashMap::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score_ranking() {
+        assert!(fuzzy_score("xyz", "src/main.rs").is_none());
+
+        let score = |path| fuzzy_score("main", path).unwrap();
+        assert!(score("src/main.rs") > score("src/domain/mapping.rs"));
+        assert!(score("main.rs") > score("src/deeply/nested/main.rs"));
+        assert!(
+            fuzzy_score("smr", "src/main.rs").unwrap()
+                > fuzzy_score("smr", "docs/summary.txt").unwrap()
+        );
+        assert!(fuzzy_score("apiTs", "src/lib/api.ts").is_some());
+    }
+
+    #[test]
+    fn test_parse_directory_status() {
+        let output = [
+            " M src/commands/claude.rs",
+            "?? src/commands/files.rs",
+            "?? src/new/",
+            "!! src/target/",
+            "R  src/lib.rs",
+            "src/old_lib.rs",
+            "A  README.md",
+            "",
+        ]
+        .join("\0");
+
+        let src = parse_directory_status(&output, "src");
+        assert_eq!(src["commands"], "modified");
+        assert_eq!(src["new"], "untracked");
+        assert_eq!(src["target"], "ignored");
+        assert_eq!(src["lib.rs"], "renamed");
+        assert!(!src.contains_key("old_lib.rs"));
+
+        let root = parse_directory_status(&output, "");
+        assert_eq!(root["src"], "modified");
+        assert_eq!(root["README.md"], "added");
+    }
+}
@@ -0,0 +1,627 @@
+use chrono::{SecondsFormat, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, State};
+
+use super::hooks::decode_decision;
+use crate::context::CoreContext;
+
+/// Hooks running at least this long are flagged as slow
+const SLOW_HOOK_MS: u64 = 5_000;
+
+/// Hook events Claude Code fires
+const HOOK_EVENTS: &[&str] = &[
+    "PreToolUse",
+    "PostToolUse",
+    "Notification",
+    "UserPromptSubmit",
+    "Stop",
+    "SubagentStop",
+    "PreCompact",
+    "SessionStart",
+    "SessionEnd",
+];
+
+/// A hook execution seen in a session's output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookActivity {
+    /// Hook event, e.g. "PreToolUse"
+    pub event: String,
+    /// Tool the hook ran for (tool events only)
+    pub tool_name: Option<String>,
+    pub tool_use_id: Option<String>,
+    pub command: Option<String>,
+    /// "success", "blocked", "error" or "cancelled"
+    pub status: String,
+    pub exit_code: Option<i32>,
+    pub duration_ms: Option<u64>,
+    /// Whether the hook ran for at least 5 seconds
+    pub slow: bool,
+    /// "allow", "deny", "ask", "approve" or "block", if the hook decided
+    pub decision: Option<String>,
+    /// Reason, error output or context reported for the hook
+    pub message: Option<String>,
+    /// "stream" (stdout of a run), "stderr" or "transcript"
+    pub source: String,
+    pub timestamp: String,
+}
+
+/// Hook activity of a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookActivityReport {
+    pub session_id: String,
+    /// Whether the session was run by Claudio, so its output was captured
+    pub captured: bool,
+    /// Activity in chronological order
+    pub entries: Vec<HookActivity>,
+    pub failed: usize,
+    pub blocked: usize,
+    pub slow: usize,
+}
+
+/// Hook activity collected from one run's output
+#[derive(Debug, Default)]
+pub struct HookLog {
+    entries: Vec<HookActivity>,
+    /// Tool names by tool_use ID, from the assistant's tool calls
+    tools: HashMap<String, String>,
+    /// Start times of running hooks, by hook ID or hook name
+    started: HashMap<String, Instant>,
+    /// Hook named by the last "Executing hooks for" debug line
+    current_hook: Option<String>,
+}
+
+/// Hook activity of runs started by Claudio, by session ID
+#[derive(Default)]
+pub struct HookActivityState {
+    logs: Mutex<HashMap<String, Vec<Arc<Mutex<HookLog>>>>>,
+}
+
+impl HookActivityState {
+    /// Files a run's log under its session, once the session ID is known
+    pub fn attach(&self, session_id: &str, log: Arc<Mutex<HookLog>>) {
+        if let Ok(mut logs) = self.logs.lock() {
+            logs.entry(session_id.to_string()).or_default().push(log);
+        }
+    }
+
+    fn entries(&self, session_id: &str) -> Option<Vec<HookActivity>> {
+        let logs = self.logs.lock().ok()?;
+        let runs = logs.get(session_id)?;
+        Some(
+            runs.iter()
+                .filter_map(|log| log.lock().ok().map(|log| log.entries.clone()))
+                .flatten()
+                .collect(),
+        )
+    }
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn text(value: &Value) -> Option<String> {
+    value
+        .as_str()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// Splits a hook name such as "PreToolUse:Bash" into event and tool
+fn split_hook_name(hook_name: &str) -> (String, Option<String>) {
+    match hook_name.split_once(':') {
+        Some((event, tool)) if matches!(event, "PreToolUse" | "PostToolUse") => {
+            (event.to_string(), Some(tool.to_string()))
+        }
+        Some((event, _)) => (event.to_string(), None),
+        None => (hook_name.to_string(), None),
+    }
+}
+
+fn status_for_exit_code(exit_code: Option<i32>) -> &'static str {
+    match exit_code {
+        Some(0) => "success",
+        Some(2) => "blocked",
+        _ => "error",
+    }
+}
+
+/// Matches `[DEBUG]`-style stderr lines naming a hook, e.g. "PreToolUse:Bash hook error"
+fn hook_name_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(&format!(r"\b({})(?::([^\s\]]+))?", HOOK_EVENTS.join("|"))).unwrap()
+    })
+}
+
+/// Matches transcript notes such as "PostToolUse:Edit [npm run lint] completed successfully"
+fn hook_note_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^(\w+(?::\S+)?) \[(.*)\] (.*)$").unwrap())
+}
+
+impl HookLog {
+    /// Records hook activity found in a stream-json or transcript message and
+    /// returns the new entries
+    pub fn record_message(&mut self, msg: &Value, source: &str) -> Vec<HookActivity> {
+        let timestamp = text(&msg["timestamp"]).unwrap_or_else(now);
+        let new = match msg["type"].as_str() {
+            Some("assistant") => {
+                for block in msg["message"]["content"].as_array().into_iter().flatten() {
+                    if let (Some(id), Some(name)) = (block["id"].as_str(), block["name"].as_str()) {
+                        self.tools.insert(id.to_string(), name.to_string());
+                    }
+                }
+                Vec::new()
+            }
+            Some("attachment") => self.parse_attachment(&msg["attachment"]),
+            Some("system") => self.parse_system(msg),
+            Some("user") => self.parse_tool_results(msg),
+            _ => Vec::new(),
+        };
+        self.push(new, source, &timestamp)
+    }
+
+    /// Records hook activity reported on stderr and returns the new entries
+    pub fn record_stderr(&mut self, line: &str) -> Vec<HookActivity> {
+        let line = line.trim();
+        let line = line.strip_prefix("[DEBUG]").unwrap_or(line).trim();
+
+        if let Some(hook_name) = line.strip_prefix("Executing hooks for ") {
+            let hook_name = hook_name.trim().to_string();
+            self.started.insert(hook_name.clone(), Instant::now());
+            self.current_hook = Some(hook_name);
+            return Vec::new();
+        }
+
+        let new = if let Some(rest) = line.strip_prefix("Hook command completed with status ") {
+            let (code, command) = rest.split_once(':').unwrap_or((rest, ""));
+            let exit_code = code.trim().parse::<i32>().ok();
+            let hook_name = self.current_hook.clone().unwrap_or_default();
+            let (event, tool_name) = split_hook_name(&hook_name);
+            vec![HookActivity {
+                tool_name,
+                command: Some(command.trim().to_string()).filter(|c| !c.is_empty()),
+                status: status_for_exit_code(exit_code).to_string(),
+                exit_code,
+                duration_ms: self.elapsed(&hook_name, false),
+                ..activity(event)
+            }]
+        } else {
+            let lower = line.to_lowercase();
+            let failed = ["error", "failed", "timed out", "blocked"]
+                .iter()
+                .any(|word| lower.contains(word));
+            match hook_name_regex().captures(line) {
+                Some(caps) if lower.contains("hook") && failed => {
+                    let event = caps[1].to_string();
+                    let tool_name = caps
+                        .get(2)
+                        .filter(|_| matches!(event.as_str(), "PreToolUse" | "PostToolUse"))
+                        .map(|m| m.as_str().to_string());
+                    vec![HookActivity {
+                        tool_name,
+                        status: "error".to_string(),
+                        message: Some(line.to_string()),
+                        ..activity(event)
+                    }]
+                }
+                _ => Vec::new(),
+            }
+        };
+        self.push(new, "stderr", &now())
+    }
+
+    /// Time since a hook started, from its start message or debug line
+    fn elapsed(&mut self, key: &str, remove: bool) -> Option<u64> {
+        let started = if remove {
+            self.started.remove(key)?
+        } else {
+            *self.started.get(key)?
+        };
+        Some(started.elapsed().as_millis() as u64)
+    }
+
+    /// Hook attachments, as written to transcripts
+    fn parse_attachment(&mut self, attachment: &Value) -> Vec<HookActivity> {
+        let Some(kind) = attachment["type"]
+            .as_str()
+            .and_then(|kind| kind.strip_prefix("hook_"))
+        else {
+            return Vec::new();
+        };
+        let hook_name = text(&attachment["hookName"]).unwrap_or_default();
+        let (event, tool_name) = split_hook_name(&hook_name);
+        let event = text(&attachment["hookEvent"]).unwrap_or(event);
+        let exit_code = attachment["exitCode"].as_i64().map(|code| code as i32);
+        let stdout = attachment["stdout"].as_str().unwrap_or_default();
+        let stderr = attachment["stderr"].as_str().unwrap_or_default();
+
+        let status = match kind {
+            "blocking_error" | "stopped_continuation" => "blocked",
+            "non_blocking_error" | "error_during_execution" => "error",
+            "cancelled" => "cancelled",
+            _ => "success",
+        };
+        let decision = exit_code.map(|code| decode_decision(&event, Some(code), stdout, stderr));
+        let blocking_error = &attachment["blockingError"];
+        let message = text(&blocking_error["blockingError"])
+            .or_else(|| text(blocking_error))
+            .or_else(|| text(&attachment["content"]))
+            .or_else(|| decision.as_ref().and_then(|d| d.reason.clone()))
+            .or_else(|| text(&attachment["stderr"]));
+
+        vec![HookActivity {
+            tool_use_id: text(&attachment["toolUseID"]),
+            command: text(&attachment["command"]).or_else(|| text(&blocking_error["command"])),
+            status: status.to_string(),
+            exit_code,
+            duration_ms: attachment["durationMs"].as_u64(),
+            decision: text(&attachment["decision"]).or_else(|| decision.and_then(|d| d.decision)),
+            message,
+            tool_name: tool_name.or_else(|| text(&attachment["toolName"])),
+            ..activity(event)
+        }]
+    }
+
+    fn parse_system(&mut self, msg: &Value) -> Vec<HookActivity> {
+        match msg["subtype"].as_str() {
+            Some("hook_started") => {
+                let key = text(&msg["hook_id"]).or_else(|| text(&msg["hook_name"]));
+                if let Some(key) = key {
+                    self.started.insert(key, Instant::now());
+                }
+                Vec::new()
+            }
+            Some("hook_response") => {
+                let hook_name = text(&msg["hook_name"]).unwrap_or_default();
+                let (event, tool_name) = split_hook_name(&hook_name);
+                let event = text(&msg["hook_event"]).unwrap_or(event);
+                let exit_code = msg["exit_code"].as_i64().map(|code| code as i32);
+                let stdout = msg["stdout"].as_str().unwrap_or_default();
+                let stderr = msg["stderr"].as_str().unwrap_or_default();
+                let decision = decode_decision(&event, exit_code, stdout, stderr);
+                let status = match msg["outcome"].as_str() {
+                    Some("cancelled") => "cancelled",
+                    Some("success") if exit_code.is_none() => "success",
+                    _ => status_for_exit_code(exit_code),
+                };
+                let key = text(&msg["hook_id"]).unwrap_or_else(|| hook_name.clone());
+                vec![HookActivity {
+                    tool_name,
+                    tool_use_id: text(&msg["tool_use_id"]),
+                    command: text(&msg["command"]),
+                    status: status.to_string(),
+                    exit_code,
+                    duration_ms: msg["duration_ms"]
+                        .as_u64()
+                        .or_else(|| self.elapsed(&key, true)),
+                    decision: decision.decision,
+                    message: decision
+                        .reason
+                        .or(decision.additional_context)
+                        .or(decision.stop_reason),
+                    ..activity(event)
+                }]
+            }
+            Some("stop_hook_summary") => {
+                let errors: Vec<String> = msg["hookErrors"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|error| text(error).or_else(|| text(&error["error"])))
+                    .collect();
+                let status = if msg["preventedContinuation"] == Value::Bool(true) {
+                    "blocked"
+                } else if !errors.is_empty() {
+                    "error"
+                } else {
+                    "success"
+                };
+                let message = text(&msg["stopReason"])
+                    .or_else(|| Some(errors.join("\n")).filter(|m| !m.is_empty()));
+                let event = text(&msg["hookEvent"]).unwrap_or_else(|| "Stop".to_string());
+                msg["hookInfos"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|info| HookActivity {
+                        tool_use_id: text(&msg["toolUseID"]),
+                        command: text(&info["command"]),
+                        status: status.to_string(),
+                        message: message.clone(),
+                        ..activity(event.clone())
+                    })
+                    .collect()
+            }
+            _ => {
+                let Some(content) = msg["content"].as_str() else {
+                    return Vec::new();
+                };
+                let Some(caps) = hook_note_regex().captures(content.trim()) else {
+                    return Vec::new();
+                };
+                let (event, tool_name) = split_hook_name(&caps[1]);
+                if !HOOK_EVENTS.contains(&event.as_str()) {
+                    return Vec::new();
+                }
+                let outcome = caps[3].to_lowercase();
+                let status = if outcome.starts_with("completed successfully") {
+                    "success"
+                } else if outcome.contains("non-blocking") {
+                    "error"
+                } else if outcome.contains("block") {
+                    "blocked"
+                } else if outcome.contains("cancel") {
+                    "cancelled"
+                } else {
+                    "error"
+                };
+                vec![HookActivity {
+                    tool_name,
+                    tool_use_id: text(&msg["toolUseID"]),
+                    command: Some(caps[2].to_string()),
+                    status: status.to_string(),
+                    message: Some(caps[3].to_string()),
+                    ..activity(event)
+                }]
+            }
+        }
+    }
+
+    /// Tool calls rejected by a hook
+    fn parse_tool_results(&mut self, msg: &Value) -> Vec<HookActivity> {
+        let mut new = Vec::new();
+        for block in msg["message"]["content"].as_array().into_iter().flatten() {
+            if block["type"] != "tool_result" || block["is_error"] != Value::Bool(true) {
+                continue;
+            }
+            let content = match &block["content"] {
+                Value::String(content) => content.clone(),
+                Value::Array(parts) => parts
+                    .iter()
+                    .filter_map(|part| part["text"].as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                _ => continue,
+            };
+            let lower = content.to_lowercase();
+            if !lower.contains("hook") {
+                continue;
+            }
+            let tool_use_id = text(&block["tool_use_id"]);
+            let event = hook_name_regex()
+                .captures(&content)
+                .map(|caps| caps[1].to_string())
+                .unwrap_or_else(|| "PreToolUse".to_string());
+            // Already reported by a hook message
+            if self
+                .entries
+                .iter()
+                .any(|e| e.tool_use_id == tool_use_id && e.event == event)
+            {
+                continue;
+            }
+            new.push(HookActivity {
+                tool_name: tool_use_id
+                    .as_ref()
+                    .and_then(|id| self.tools.get(id).cloned()),
+                tool_use_id,
+                status: "blocked".to_string(),
+                decision: (event == "PreToolUse").then(|| "deny".to_string()),
+                message: Some(content.trim().to_string()),
+                ..activity(event)
+            });
+        }
+        new
+    }
+
+    fn push(&mut self, new: Vec<HookActivity>, source: &str, timestamp: &str) -> Vec<HookActivity> {
+        let new: Vec<HookActivity> = new
+            .into_iter()
+            .map(|mut entry| {
+                if entry.tool_name.is_none() {
+                    entry.tool_name = entry
+                        .tool_use_id
+                        .as_ref()
+                        .and_then(|id| self.tools.get(id).cloned());
+                }
+                entry.slow = entry.duration_ms.is_some_and(|ms| ms >= SLOW_HOOK_MS);
+                entry.source = source.to_string();
+                entry.timestamp = timestamp.to_string();
+                entry
+            })
+            .collect();
+        self.entries.extend(new.iter().cloned());
+        new
+    }
+}
+
+/// An entry for an event with everything else unset
+fn activity(event: String) -> HookActivity {
+    HookActivity {
+        event,
+        tool_name: None,
+        tool_use_id: None,
+        command: None,
+        status: String::new(),
+        exit_code: None,
+        duration_ms: None,
+        slow: false,
+        decision: None,
+        message: None,
+        source: String::new(),
+        timestamp: String::new(),
+    }
+}
+
+/// Whether a transcript entry reports the same hook run as a captured one
+fn same_run(captured: &HookActivity, recorded: &HookActivity) -> bool {
+    captured.event == recorded.event
+        && captured.status == recorded.status
+        && captured.tool_use_id == recorded.tool_use_id
+        && (captured.command.is_none()
+            || recorded.command.is_none()
+            || captured.command == recorded.command)
+}
+
+/// Adds transcript entries that weren't captured from the run's output
+fn merge_transcript(captured: Vec<HookActivity>, recorded: Vec<HookActivity>) -> Vec<HookActivity> {
+    let mut used = vec![false; captured.len()];
+    let mut merged = captured.clone();
+    for entry in recorded {
+        let duplicate = captured
+            .iter()
+            .enumerate()
+            .position(|(i, c)| !used[i] && same_run(c, &entry));
+        match duplicate {
+            Some(i) => {
+                used[i] = true;
+                // The transcript may know what the stream didn't
+                let existing = &mut merged[i];
+                existing.command = existing.command.take().or(entry.command);
+                existing.duration_ms = existing.duration_ms.or(entry.duration_ms);
+                existing.slow |= entry.slow;
+            }
+            None => merged.push(entry),
+        }
+    }
+    merged.sort_by_key(|entry| entry.timestamp.clone());
+    merged
+}
+
+/// Emits new hook activity to the frontend as `hook-activity:{session_id}`
+pub fn emit_hook_activity(app: &AppHandle, session_id: Option<&str>, entries: &[HookActivity]) {
+    let Some(session_id) = session_id else {
+        return;
+    };
+    for entry in entries {
+        let _ = app.emit(&format!("hook-activity:{}", session_id), entry);
+    }
+}
+
+/// Gets the hooks that fired in a session, with their timing and decisions.
+///
+/// Combines what was captured from the output of runs started by Claudio with
+/// the hook entries in the session's transcript.
+#[tauri::command]
+pub async fn get_hook_activity(
+    state: State<'_, HookActivityState>,
+    context: State<'_, CoreContext>,
+    session_id: String,
+) -> Result<HookActivityReport, String> {
+    log::info!("Getting hook activity for session: {}", session_id);
+
+    let captured = state.entries(&session_id);
+    let mut transcript_log = HookLog::default();
+    if let Some(transcript) = context.session_transcript(&session_id) {
+        let file =
+            fs::File::open(&transcript).map_err(|e| format!("Failed to open transcript: {}", e))?;
+        for line in BufReader::new(file).lines().map_while(Result::ok) {
+            if let Ok(msg) = serde_json::from_str::<Value>(&line) {
+                transcript_log.record_message(&msg, "transcript");
+            }
+        }
+    }
+
+    let entries = merge_transcript(captured.clone().unwrap_or_default(), transcript_log.entries);
+    Ok(HookActivityReport {
+        session_id,
+        captured: captured.is_some(),
+        failed: entries.iter().filter(|e| e.status == "error").count(),
+        blocked: entries.iter().filter(|e| e.status == "blocked").count(),
+        slow: entries.iter().filter(|e| e.slow).count(),
+        entries,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_record_stream_and_stderr() {
+        let mut log = HookLog::default();
+        log.record_message(
+            &json!({"type": "assistant", "message": {"content": [
+                {"type": "tool_use", "id": "toolu_1", "name": "Bash", "input": {}}
+            ]}}),
+            "stream",
+        );
+        let new = log.record_message(
+            &json!({"type": "attachment", "attachment": {
+                "type": "hook_blocking_error",
+                "hookName": "PreToolUse:Bash",
+                "toolUseID": "toolu_1",
+                "blockingError": {"blockingError": "rm is not allowed", "command": "guard.sh"}
+            }}),
+            "stream",
+        );
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].event, "PreToolUse");
+        assert_eq!(new[0].tool_name.as_deref(), Some("Bash"));
+        assert_eq!(new[0].status, "blocked");
+        assert_eq!(new[0].command.as_deref(), Some("guard.sh"));
+        assert_eq!(new[0].message.as_deref(), Some("rm is not allowed"));
+
+        // The rejected tool result repeats the same block
+        let new = log.record_message(
+            &json!({"type": "user", "message": {"content": [{
+                "type": "tool_result", "tool_use_id": "toolu_1", "is_error": true,
+                "content": "PreToolUse:Bash hook error: rm is not allowed"
+            }]}}),
+            "stream",
+        );
+        assert!(new.is_empty());
+
+        assert!(log
+            .record_stderr("[DEBUG] Executing hooks for PostToolUse:Edit")
+            .is_empty());
+        let new = log.record_stderr("[DEBUG] Hook command completed with status 1: npm run lint");
+        assert_eq!(new[0].event, "PostToolUse");
+        assert_eq!(new[0].tool_name.as_deref(), Some("Edit"));
+        assert_eq!(new[0].status, "error");
+        assert_eq!(new[0].exit_code, Some(1));
+        assert_eq!(new[0].command.as_deref(), Some("npm run lint"));
+        assert!(new[0].duration_ms.is_some());
+        assert!(log.record_stderr("Compiling project").is_empty());
+    }
+
+    #[test]
+    fn test_merge_transcript_skips_captured_runs() {
+        let mut captured = HookLog::default();
+        captured.record_message(
+            &json!({"type": "system", "subtype": "hook_response",
+                    "hook_name": "SessionStart:startup", "hook_event": "SessionStart",
+                    "exit_code": 0, "stdout": "", "stderr": "",
+                    "timestamp": "2025-01-01T00:00:01.000Z"}),
+            "stream",
+        );
+        let mut recorded = HookLog::default();
+        for msg in [
+            json!({"type": "system", "content": "SessionStart:startup [./setup.sh] completed successfully",
+                   "timestamp": "2025-01-01T00:00:01.000Z"}),
+            json!({"type": "attachment", "timestamp": "2025-01-01T00:00:00.000Z",
+                   "attachment": {"type": "hook_success", "hookName": "UserPromptSubmit",
+                                  "command": "./ctx.sh", "exitCode": 0, "durationMs": 7200}}),
+        ] {
+            recorded.record_message(&msg, "transcript");
+        }
+
+        let merged = merge_transcript(captured.entries, recorded.entries);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].event, "UserPromptSubmit");
+        assert!(merged[0].slow);
+        assert_eq!(merged[1].source, "stream");
+        assert_eq!(merged[1].command.as_deref(), Some("./setup.sh"));
+    }
+}
@@ -0,0 +1,488 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+
+use crate::context::CoreContext;
+
+/// Claude Code's default hook timeout
+const DEFAULT_HOOK_TIMEOUT_SECS: u64 = 60;
+
+/// Tools tried, in order, when no tool is given for a tool event
+const SAMPLE_TOOLS: &[&str] = &[
+    "Bash",
+    "Edit",
+    "Write",
+    "Read",
+    "MultiEdit",
+    "Glob",
+    "Grep",
+    "WebFetch",
+    "Task",
+];
+
+/// A hook to run against a sample event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookTestRequest {
+    /// Hook event, e.g. "PreToolUse", "PostToolUse", "UserPromptSubmit" or "Stop"
+    pub event: String,
+    pub command: String,
+    /// Tool name matcher of the hook (tool events only)
+    pub matcher: Option<String>,
+    /// Directory the hook runs in, as CLAUDE_PROJECT_DIR
+    pub project_path: String,
+    /// Tool to simulate; defaults to the first sample tool the matcher accepts
+    pub tool_name: Option<String>,
+    /// Tool input to send instead of a sample one
+    pub tool_input: Option<Value>,
+    /// Prompt sent with UserPromptSubmit
+    pub prompt: Option<String>,
+    /// Session whose transcript supplies a real tool call and the transcript path
+    pub session_id: Option<String>,
+    pub timeout_secs: Option<u64>,
+}
+
+/// What Claude Code would do with a hook's result
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HookDecision {
+    /// "success", "blocking_error" (exit code 2), "non_blocking_error" or "timeout"
+    pub outcome: String,
+    /// "allow", "deny", "ask", "approve" or "block", if the hook decided
+    pub decision: Option<String>,
+    pub reason: Option<String>,
+    /// Text added to Claude's context
+    pub additional_context: Option<String>,
+    /// False if the hook asked Claude to stop
+    pub continue_session: bool,
+    pub stop_reason: Option<String>,
+    /// The hook's stdout, if it was a JSON object
+    pub json_output: Option<Value>,
+}
+
+/// Result of running a hook against a sample event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookTestResult {
+    /// Whether the matcher accepts the simulated tool; the hook only runs if it does
+    pub matched: bool,
+    /// The JSON sent to the hook on stdin
+    pub payload: Value,
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub duration_ms: u64,
+    pub decision: HookDecision,
+}
+
+fn is_tool_event(event: &str) -> bool {
+    matches!(event, "PreToolUse" | "PostToolUse")
+}
+
+/// Whether a hook matcher accepts a tool name ("" and "*" accept every tool)
+pub(crate) fn matcher_matches(matcher: Option<&str>, tool_name: &str) -> Result<bool, String> {
+    match matcher {
+        None | Some("") | Some("*") => Ok(true),
+        Some(matcher) => regex::Regex::new(&format!("^(?:{})$", matcher))
+            .map(|re| re.is_match(tool_name))
+            .map_err(|e| format!("Invalid matcher regex: {}", e)),
+    }
+}
+
+/// A representative input and response for a tool
+fn sample_tool_call(tool_name: &str, cwd: &str) -> (Value, Value) {
+    let file_path = Path::new(cwd).join("src").join("example.ts");
+    let file_path = file_path.to_string_lossy();
+    match tool_name {
+        "Bash" => (
+            json!({ "command": "npm test", "description": "Run the test suite" }),
+            json!({ "stdout": "All tests passed", "stderr": "", "interrupted": false }),
+        ),
+        "Edit" | "MultiEdit" => (
+            json!({
+                "file_path": file_path,
+                "old_string": "const answer = 41;",
+                "new_string": "const answer = 42;"
+            }),
+            json!({ "filePath": file_path, "success": true }),
+        ),
+        "Write" => (
+            json!({ "file_path": file_path, "content": "export const answer = 42;\n" }),
+            json!({ "filePath": file_path, "success": true }),
+        ),
+        "Read" => (
+            json!({ "file_path": file_path }),
+            json!({ "type": "text", "file": { "filePath": file_path, "numLines": 1 } }),
+        ),
+        "Glob" => (
+            json!({ "pattern": "**/*.ts" }),
+            json!({ "filenames": [file_path], "numFiles": 1 }),
+        ),
+        "Grep" => (
+            json!({ "pattern": "answer", "path": cwd }),
+            json!({ "filenames": [file_path], "numFiles": 1 }),
+        ),
+        "WebFetch" => (
+            json!({ "url": "https://example.com", "prompt": "Summarize the page" }),
+            json!({ "result": "Example Domain" }),
+        ),
+        _ => (json!({}), json!({})),
+    }
+}
+
+/// Finds the last tool call in a transcript accepted by the matcher, with its result
+fn find_transcript_tool_call(
+    transcript: &Path,
+    matcher: Option<&str>,
+    tool_name: Option<&str>,
+) -> Option<(String, Value, Value)> {
+    let file = fs::File::open(transcript).ok()?;
+    let mut last_call: Option<(String, String, Value)> = None;
+    let mut last_response: Option<Value> = None;
+
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        let Ok(entry) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        let Some(blocks) = entry["message"]["content"].as_array() else {
+            continue;
+        };
+        for block in blocks {
+            match block["type"].as_str() {
+                Some("tool_use") => {
+                    let name = block["name"].as_str().unwrap_or_default();
+                    let wanted = match tool_name {
+                        Some(tool_name) => name == tool_name,
+                        None => matcher_matches(matcher, name).unwrap_or(false),
+                    };
+                    if wanted {
+                        let id = block["id"].as_str().unwrap_or_default().to_string();
+                        last_call = Some((id, name.to_string(), block["input"].clone()));
+                        last_response = None;
+                    }
+                }
+                Some("tool_result") => {
+                    if let Some((ref id, _, _)) = last_call {
+                        if block["tool_use_id"].as_str() == Some(id.as_str()) {
+                            last_response = Some(
+                                entry
+                                    .get("toolUseResult")
+                                    .cloned()
+                                    .unwrap_or_else(|| block["content"].clone()),
+                            );
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    last_call.map(|(_, name, input)| (name, input, last_response.unwrap_or(Value::Null)))
+}
+
+/// Builds the JSON Claude Code sends to a hook on stdin
+///
+/// Returns the payload and whether the hook's matcher accepts the event.
+fn build_payload(
+    context: &CoreContext,
+    request: &HookTestRequest,
+) -> Result<(Value, bool), String> {
+    let session_id = request
+        .session_id
+        .clone()
+        .unwrap_or_else(|| "00000000-0000-0000-0000-000000000000".to_string());
+    let transcript = request
+        .session_id
+        .as_deref()
+        .and_then(|session_id| context.session_transcript(session_id));
+    let transcript_path = transcript
+        .as_ref()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut payload = json!({
+        "session_id": session_id,
+        "transcript_path": transcript_path,
+        "cwd": request.project_path,
+        "hook_event_name": request.event,
+        "permission_mode": "default",
+    });
+    let mut matched = true;
+
+    match request.event.as_str() {
+        "PreToolUse" | "PostToolUse" => {
+            let matcher = request.matcher.as_deref();
+            let real_call = transcript.as_deref().and_then(|transcript| {
+                find_transcript_tool_call(transcript, matcher, request.tool_name.as_deref())
+            });
+
+            let (tool_name, tool_input, tool_response) = match real_call {
+                Some((name, input, response)) => (name, input, response),
+                None => {
+                    let tool_name = match request.tool_name {
+                        Some(ref tool_name) => tool_name.clone(),
+                        None => SAMPLE_TOOLS
+                            .iter()
+                            .find(|tool| matcher_matches(matcher, tool).unwrap_or(false))
+                            .unwrap_or(&SAMPLE_TOOLS[0])
+                            .to_string(),
+                    };
+                    let (input, response) = sample_tool_call(&tool_name, &request.project_path);
+                    (tool_name, input, response)
+                }
+            };
+            matched = matcher_matches(matcher, &tool_name)?;
+
+            payload["tool_name"] = json!(tool_name);
+            payload["tool_input"] = request.tool_input.clone().unwrap_or(tool_input);
+            if request.event == "PostToolUse" {
+                payload["tool_response"] = tool_response;
+            }
+        }
+        "UserPromptSubmit" => {
+            payload["prompt"] = json!(request
+                .prompt
+                .clone()
+                .unwrap_or_else(|| "Add tests for the parser".to_string()));
+        }
+        "Notification" => {
+            payload["message"] = json!("Claude needs your permission to use Bash");
+        }
+        "Stop" | "SubagentStop" => {
+            payload["stop_hook_active"] = json!(false);
+        }
+        "PreCompact" => {
+            payload["trigger"] = json!("manual");
+            payload["custom_instructions"] = json!("");
+        }
+        "SessionStart" => {
+            payload["source"] = json!("startup");
+        }
+        "SessionEnd" => {
+            payload["reason"] = json!("other");
+        }
+        other => return Err(format!("Unknown hook event: {}", other)),
+    }
+
+    Ok((payload, matched))
+}
+
+/// Interprets a hook's exit code and output the way Claude Code does
+pub(crate) fn decode_decision(
+    event: &str,
+    exit_code: Option<i32>,
+    stdout: &str,
+    stderr: &str,
+) -> HookDecision {
+    let mut decision = HookDecision {
+        continue_session: true,
+        ..Default::default()
+    };
+
+    match exit_code {
+        Some(0) => decision.outcome = "success".to_string(),
+        // Exit code 2 blocks; stderr is fed back to Claude
+        Some(2) => {
+            decision.outcome = "blocking_error".to_string();
+            decision.decision = Some(
+                if event == "PreToolUse" {
+                    "deny"
+                } else {
+                    "block"
+                }
+                .to_string(),
+            );
+            decision.reason = Some(stderr.trim().to_string()).filter(|r| !r.is_empty());
+            return decision;
+        }
+        _ => {
+            decision.outcome = "non_blocking_error".to_string();
+            decision.reason = Some(stderr.trim().to_string()).filter(|r| !r.is_empty());
+            return decision;
+        }
+    }
+
+    let output = match serde_json::from_str::<Value>(stdout.trim()) {
+        Ok(output @ Value::Object(_)) => output,
+        // Plain stdout is added as context for these events
+        _ => {
+            if matches!(event, "UserPromptSubmit" | "SessionStart") && !stdout.trim().is_empty() {
+                decision.additional_context = Some(stdout.trim().to_string());
+            }
+            return decision;
+        }
+    };
+
+    let text = |value: &Value| value.as_str().map(str::to_string);
+    let specific = &output["hookSpecificOutput"];
+    decision.decision = text(&specific["permissionDecision"]).or_else(|| text(&output["decision"]));
+    decision.reason =
+        text(&specific["permissionDecisionReason"]).or_else(|| text(&output["reason"]));
+    decision.additional_context = text(&specific["additionalContext"]);
+    if output["continue"] == json!(false) {
+        decision.continue_session = false;
+        decision.stop_reason = text(&output["stopReason"]);
+    }
+    decision.json_output = Some(output);
+    decision
+}
+
+/// Runs a hook command against a sample (or recorded) event payload, as
+/// Claude Code would: through the shell, in the project directory, with the
+/// payload on stdin and a timeout
+#[tauri::command]
+pub async fn test_hook(
+    context: tauri::State<'_, CoreContext>,
+    request: HookTestRequest,
+) -> Result<HookTestResult, String> {
+    log::info!(
+        "Testing {} hook (matcher: {:?}) in {}",
+        request.event,
+        request.matcher,
+        request.project_path
+    );
+
+    if request.command.trim().is_empty() {
+        return Err("Hook command cannot be empty".to_string());
+    }
+    if is_tool_event(&request.event) {
+        matcher_matches(request.matcher.as_deref(), "")?;
+    }
+
+    let (payload, matched) = build_payload(&context, &request)?;
+    if !matched {
+        return Ok(HookTestResult {
+            matched,
+            payload,
+            exit_code: None,
+            stdout: String::new(),
+            stderr: String::new(),
+            duration_ms: 0,
+            decision: HookDecision::default(),
+        });
+    }
+
+    let mut cmd = if cfg!(target_os = "windows") {
+        let mut cmd = tokio::process::Command::new("cmd");
+        cmd.args(["/C", &request.command]);
+        cmd
+    } else {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.args(["-c", &request.command]);
+        cmd
+    };
+    cmd.current_dir(&request.project_path)
+        .env("CLAUDE_PROJECT_DIR", &request.project_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let started = Instant::now();
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to run hook: {}", e))?;
+    let stdin = child.stdin.take();
+    let input = payload.to_string();
+    // Write the input while the hook runs, so one that never reads it can't block
+    // past the timeout; stdin is closed once written
+    let write_input = async move {
+        if let Some(mut stdin) = stdin {
+            // A hook may exit without reading its input
+            let _ = stdin.write_all(input.as_bytes()).await;
+        }
+    };
+
+    let timeout = Duration::from_secs(request.timeout_secs.unwrap_or(DEFAULT_HOOK_TIMEOUT_SECS));
+    let output = tokio::time::timeout(timeout, async {
+        let ((), output) = tokio::join!(write_input, child.wait_with_output());
+        output
+    })
+    .await;
+    let duration_ms = started.elapsed().as_millis() as u64;
+
+    let (exit_code, stdout, stderr, decision) = match output {
+        Ok(Ok(output)) => {
+            let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            let exit_code = output.status.code();
+            let decision = decode_decision(&request.event, exit_code, &stdout, &stderr);
+            (exit_code, stdout, stderr, decision)
+        }
+        Ok(Err(e)) => return Err(format!("Failed to wait for hook: {}", e)),
+        // The child is killed when dropped
+        Err(_) => (
+            None,
+            String::new(),
+            String::new(),
+            HookDecision {
+                outcome: "timeout".to_string(),
+                reason: Some(format!("Hook timed out after {}s", timeout.as_secs())),
+                continue_session: true,
+                ..Default::default()
+            },
+        ),
+    };
+
+    log::info!(
+        "Hook test finished in {}ms with outcome {}",
+        duration_ms,
+        decision.outcome
+    );
+    Ok(HookTestResult {
+        matched,
+        payload,
+        exit_code,
+        stdout,
+        stderr,
+        duration_ms,
+        decision,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matcher_matches() {
+        assert!(matcher_matches(None, "Bash").unwrap());
+        assert!(matcher_matches(Some("*"), "Bash").unwrap());
+        assert!(matcher_matches(Some("Edit|Write"), "Write").unwrap());
+        assert!(!matcher_matches(Some("Edit"), "MultiEdit").unwrap());
+        assert!(matcher_matches(Some("mcp__.*"), "mcp__github__get_issue").unwrap());
+        assert!(matcher_matches(Some("Bash("), "Bash").is_err());
+    }
+
+    #[test]
+    fn test_decode_decision() {
+        let blocked = decode_decision("PreToolUse", Some(2), "", "rm is not allowed\n");
+        assert_eq!(blocked.outcome, "blocking_error");
+        assert_eq!(blocked.decision.as_deref(), Some("deny"));
+        assert_eq!(blocked.reason.as_deref(), Some("rm is not allowed"));
+
+        let json_output = r#"{"hookSpecificOutput":{"hookEventName":"PreToolUse","permissionDecision":"ask","permissionDecisionReason":"Touches prod"}}"#;
+        let asked = decode_decision("PreToolUse", Some(0), json_output, "");
+        assert_eq!(asked.decision.as_deref(), Some("ask"));
+        assert_eq!(asked.reason.as_deref(), Some("Touches prod"));
+
+        let context = decode_decision("UserPromptSubmit", Some(0), "Branch: main\n", "");
+        assert_eq!(context.additional_context.as_deref(), Some("Branch: main"));
+
+        let stopped = decode_decision(
+            "Stop",
+            Some(0),
+            r#"{"continue":false,"stopReason":"Tests failing"}"#,
+            "",
+        );
+        assert!(!stopped.continue_session);
+        assert_eq!(stopped.stop_reason.as_deref(), Some("Tests failing"));
+
+        let failed = decode_decision("PostToolUse", Some(1), "", "oops");
+        assert_eq!(failed.outcome, "non_blocking_error");
+        assert!(failed.decision.is_none());
+    }
+}
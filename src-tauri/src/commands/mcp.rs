@@ -6,7 +6,10 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use std::process::Command;
-use tauri::AppHandle;
+use tauri::{AppHandle, Manager};
+
+use super::capabilities::ClaudeFeature;
+use crate::context::CoreContext;
 
 /// Helper function to create a std::process::Command with proper environment variables
 /// This ensures commands like Claude can find Node.js and other dependencies
@@ -14,12 +17,6 @@ fn create_command_with_env(program: &str) -> Command {
     crate::claude_binary::create_command_with_env(program)
 }
 
-/// Finds the full path to the claude binary
-/// This is necessary because macOS apps have a limited PATH environment
-fn find_claude_binary(app_handle: &AppHandle) -> Result<String> {
-    crate::claude_binary::find_claude_binary(app_handle).map_err(|e| anyhow::anyhow!(e))
-}
-
 /// Represents an MCP server configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MCPServer {
@@ -95,11 +92,17 @@ pub struct ImportServerResult {
     pub error: Option<String>,
 }
 
-/// Executes a claude mcp command
-fn execute_claude_mcp_command(app_handle: &AppHandle, args: Vec<&str>) -> Result<String> {
+/// Executes a claude mcp command with the context's claude binary
+fn execute_claude_mcp_command(context: &CoreContext, args: Vec<&str>) -> Result<String> {
     info!("Executing claude mcp command with args: {:?}", args);
 
-    let claude_path = find_claude_binary(app_handle)?;
+    let claude_path = context.claude_binary().map_err(|e| anyhow::anyhow!(e))?;
+    if let Some(subcommand) = args.first() {
+        context
+            .claude_capabilities(&claude_path)
+            .check_subcommand(&format!("mcp {}", subcommand))
+            .map_err(|e| anyhow::anyhow!(e))?;
+    }
     let mut cmd = create_command_with_env(&claude_path);
     cmd.arg("mcp");
     for arg in args {
@@ -188,7 +191,7 @@ pub async fn mcp_add(
         }
     }
 
-    match execute_claude_mcp_command(&app, cmd_args) {
+    match execute_claude_mcp_command(&app.state::<CoreContext>(), cmd_args) {
         Ok(output) => {
             info!("Successfully added MCP server: {}", name);
             Ok(AddServerResult {
@@ -211,9 +214,14 @@ pub async fn mcp_add(
 /// Lists all configured MCP servers
 #[tauri::command]
 pub async fn mcp_list(app: AppHandle) -> Result<Vec<MCPServer>, String> {
+    mcp_list_in(&app.state::<CoreContext>())
+}
+
+/// Lists MCP servers by parsing `claude mcp list`
+pub fn mcp_list_in(context: &CoreContext) -> Result<Vec<MCPServer>, String> {
     info!("Listing MCP servers");
 
-    match execute_claude_mcp_command(&app, vec!["list"]) {
+    match execute_claude_mcp_command(context, vec!["list"]) {
         Ok(output) => {
             info!("Raw output from 'claude mcp list': {:?}", output);
             let trimmed = output.trim();
@@ -333,9 +341,14 @@ pub async fn mcp_list(app: AppHandle) -> Result<Vec<MCPServer>, String> {
 /// Gets details for a specific MCP server
 #[tauri::command]
 pub async fn mcp_get(app: AppHandle, name: String) -> Result<MCPServer, String> {
+    mcp_get_in(&app.state::<CoreContext>(), &name)
+}
+
+/// Gets a server's details by parsing `claude mcp get`
+pub fn mcp_get_in(context: &CoreContext, name: &str) -> Result<MCPServer, String> {
     info!("Getting MCP server details for: {}", name);
 
-    match execute_claude_mcp_command(&app, vec!["get", &name]) {
+    match execute_claude_mcp_command(context, vec!["get", name]) {
         Ok(output) => {
             // Parse the structured text output
             let mut scope = "local".to_string();
@@ -377,7 +390,7 @@ pub async fn mcp_get(app: AppHandle, name: String) -> Result<MCPServer, String>
             }
 
             Ok(MCPServer {
-                name,
+                name: name.to_string(),
                 transport,
                 command,
                 args,
@@ -404,7 +417,7 @@ pub async fn mcp_get(app: AppHandle, name: String) -> Result<MCPServer, String>
 pub async fn mcp_remove(app: AppHandle, name: String) -> Result<String, String> {
     info!("Removing MCP server: {}", name);
 
-    match execute_claude_mcp_command(&app, vec!["remove", &name]) {
+    match execute_claude_mcp_command(&app.state::<CoreContext>(), vec!["remove", &name]) {
         Ok(output) => {
             info!("Successfully removed MCP server: {}", name);
             Ok(output.trim().to_string())
@@ -437,7 +450,7 @@ pub async fn mcp_add_json(
     cmd_args.push(scope_flag);
     cmd_args.push(&scope);
 
-    match execute_claude_mcp_command(&app, cmd_args) {
+    match execute_claude_mcp_command(&app.state::<CoreContext>(), cmd_args) {
         Ok(output) => {
             info!("Successfully added MCP server from JSON: {}", name);
             Ok(AddServerResult {
@@ -616,13 +629,17 @@ pub async fn mcp_serve(app: AppHandle) -> Result<String, String> {
     info!("Starting Claude Code as MCP server");
 
     // Start the server in a separate process
-    let claude_path = match find_claude_binary(&app) {
+    let context = app.state::<CoreContext>();
+    let claude_path = match context.claude_binary() {
         Ok(path) => path,
         Err(e) => {
             error!("Failed to find claude binary: {}", e);
-            return Err(e.to_string());
+            return Err(e);
         }
     };
+    context
+        .claude_capabilities(&claude_path)
+        .require(ClaudeFeature::McpServe)?;
 
     let mut cmd = create_command_with_env(&claude_path);
     cmd.arg("mcp").arg("serve");
@@ -642,10 +659,15 @@ pub async fn mcp_serve(app: AppHandle) -> Result<String, String> {
 /// Tests connection to an MCP server
 #[tauri::command]
 pub async fn mcp_test_connection(app: AppHandle, name: String) -> Result<String, String> {
+    mcp_test_connection_in(&app.state::<CoreContext>(), &name)
+}
+
+/// Checks a server is configured and reachable through `claude mcp get`
+pub fn mcp_test_connection_in(context: &CoreContext, name: &str) -> Result<String, String> {
     info!("Testing connection to MCP server: {}", name);
 
     // For now, we'll use the get command to test if the server exists
-    match execute_claude_mcp_command(&app, vec!["get", &name]) {
+    match execute_claude_mcp_command(context, vec!["get", name]) {
         Ok(_) => Ok(format!("Connection to {} successful", name)),
         Err(e) => Err(e.to_string()),
     }
@@ -656,7 +678,7 @@ pub async fn mcp_test_connection(app: AppHandle, name: String) -> Result<String,
 pub async fn mcp_reset_project_choices(app: AppHandle) -> Result<String, String> {
     info!("Resetting MCP project choices");
 
-    match execute_claude_mcp_command(&app, vec!["reset-project-choices"]) {
+    match execute_claude_mcp_command(&app.state::<CoreContext>(), vec!["reset-project-choices"]) {
         Ok(output) => {
             info!("Successfully reset MCP project choices");
             Ok(output.trim().to_string())
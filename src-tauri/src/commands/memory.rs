@@ -0,0 +1,387 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::settings::managed_settings_dir;
+use crate::context::CoreContext;
+
+/// Maximum depth of nested `@path` imports, as enforced by Claude Code
+const MAX_IMPORT_DEPTH: usize = 5;
+
+/// Where a memory file comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryKind {
+    /// Organization-wide policy file
+    Managed,
+    /// ~/.claude/CLAUDE.md
+    User,
+    /// CLAUDE.md or .claude/CLAUDE.md in the working directory or an ancestor
+    Project,
+    /// CLAUDE.local.md in the working directory or an ancestor
+    Local,
+}
+
+impl MemoryKind {
+    /// Description used in the header of each section, as the CLI labels them
+    fn description(self) -> &'static str {
+        match self {
+            MemoryKind::Managed => "managed policy instructions",
+            MemoryKind::User => "user's private global instructions for all projects",
+            MemoryKind::Project => "project instructions, checked into the codebase",
+            MemoryKind::Local => "user's private project instructions, not checked in",
+        }
+    }
+}
+
+/// A memory file included in the resolved memory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemorySource {
+    pub path: String,
+    /// Kind of the file, or of the file that imported it
+    pub kind: MemoryKind,
+    /// The file whose `@path` import pulled this one in
+    pub imported_from: Option<String>,
+    /// Import depth; 0 for files loaded directly
+    pub depth: usize,
+    /// File size in bytes
+    pub size: u64,
+    /// Byte range of the file's content in the resolved text
+    pub start: usize,
+    pub end: usize,
+}
+
+/// An import that was not followed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedImport {
+    /// The import as written, without the leading `@`
+    pub import: String,
+    pub imported_from: String,
+    /// "not_found", "cycle", "duplicate" or "max_depth"
+    pub reason: String,
+}
+
+/// The memory Claude Code loads for a working directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedMemory {
+    pub working_dir: String,
+    /// All memory files concatenated in load order
+    pub text: String,
+    /// Size of the text in bytes
+    pub size: usize,
+    /// Files in load order
+    pub sources: Vec<MemorySource>,
+    pub skipped_imports: Vec<SkippedImport>,
+}
+
+/// Extracts `@path` imports from markdown, ignoring code blocks and code spans
+fn parse_imports(content: &str) -> Vec<String> {
+    let mut imports = Vec::new();
+    let mut in_code_block = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        // Blank out inline code spans
+        let mut in_span = false;
+        let text: String = line
+            .chars()
+            .map(|c| {
+                if c == '`' {
+                    in_span = !in_span;
+                    ' '
+                } else if in_span {
+                    ' '
+                } else {
+                    c
+                }
+            })
+            .collect();
+
+        let mut rest = text.as_str();
+        let mut preceded_by_space = true;
+        while let Some(index) = rest.find('@') {
+            let before = &rest[..index];
+            let at_boundary = if before.is_empty() {
+                preceded_by_space
+            } else {
+                before.ends_with(char::is_whitespace)
+            };
+            rest = &rest[index + 1..];
+            preceded_by_space = false;
+            if !at_boundary {
+                continue;
+            }
+
+            // The path runs to the next unescaped whitespace
+            let mut path = String::new();
+            let mut chars = rest.char_indices().peekable();
+            let mut consumed = rest.len();
+            while let Some((i, c)) = chars.next() {
+                if c == '\\' && chars.peek().is_some_and(|(_, next)| *next == ' ') {
+                    path.push(' ');
+                    chars.next();
+                } else if c.is_whitespace() {
+                    consumed = i;
+                    break;
+                } else {
+                    path.push(c);
+                }
+            }
+            rest = &rest[consumed..];
+            // Punctuation ending a sentence isn't part of the path
+            let path = path.trim_end_matches(['.', ',', ';', ':', '!', '?', ')']);
+            if !path.is_empty() {
+                imports.push(path.to_string());
+            }
+        }
+    }
+    imports
+}
+
+/// Resolves an import relative to the file that contains it
+fn resolve_import(import: &str, from: &Path, home: Option<&Path>) -> PathBuf {
+    if let (Some(rest), Some(home)) = (import.strip_prefix("~/"), home) {
+        return home.join(rest);
+    }
+    let path = Path::new(import);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        from.parent().unwrap_or(Path::new("")).join(path)
+    }
+}
+
+struct Resolver<'a> {
+    home: Option<&'a Path>,
+    text: String,
+    sources: Vec<MemorySource>,
+    skipped_imports: Vec<SkippedImport>,
+    /// Canonical paths of every included file
+    included: HashSet<PathBuf>,
+    /// Canonical paths on the current import chain
+    chain: Vec<PathBuf>,
+}
+
+impl Resolver<'_> {
+    /// Adds a file and, after it, the files it imports
+    fn include(&mut self, path: &Path, kind: MemoryKind, imported_from: Option<&Path>) {
+        let Ok(canonical) = path.canonicalize() else {
+            return;
+        };
+        let Ok(content) = fs::read_to_string(path) else {
+            return;
+        };
+        self.included.insert(canonical.clone());
+
+        if !self.text.is_empty() {
+            self.text.push_str("\n\n");
+        }
+        self.text.push_str(&format!(
+            "Contents of {} ({}):\n\n",
+            path.display(),
+            kind.description()
+        ));
+        let start = self.text.len();
+        self.text.push_str(&content);
+
+        self.sources.push(MemorySource {
+            path: path.to_string_lossy().to_string(),
+            kind,
+            imported_from: imported_from.map(|p| p.to_string_lossy().to_string()),
+            depth: self.chain.len(),
+            size: content.len() as u64,
+            start,
+            end: self.text.len(),
+        });
+
+        self.chain.push(canonical);
+        for import in parse_imports(&content) {
+            let target = resolve_import(&import, path, self.home);
+            let reason = match target.canonicalize() {
+                Ok(canonical) if !canonical.is_file() => Some("not_found"),
+                Ok(canonical) if self.chain.contains(&canonical) => Some("cycle"),
+                Ok(canonical) if self.included.contains(&canonical) => Some("duplicate"),
+                Ok(_) if self.chain.len() > MAX_IMPORT_DEPTH => Some("max_depth"),
+                Ok(_) => None,
+                Err(_) => Some("not_found"),
+            };
+            match reason {
+                Some(reason) => self.skipped_imports.push(SkippedImport {
+                    import,
+                    imported_from: path.to_string_lossy().to_string(),
+                    reason: reason.to_string(),
+                }),
+                None => self.include(&target, kind, Some(path)),
+            }
+        }
+        self.chain.pop();
+    }
+}
+
+/// Resolves the memory files loaded for a working directory, in load order:
+/// the managed policy file, the user file, then for each directory from the
+/// filesystem root down to `working_dir` its CLAUDE.md, .claude/CLAUDE.md and
+/// CLAUDE.local.md. Each file is followed by the files it imports.
+fn resolve_memory(working_dir: &Path, home: Option<&Path>, managed: &Path) -> ResolvedMemory {
+    let mut resolver = Resolver {
+        home,
+        text: String::new(),
+        sources: Vec::new(),
+        skipped_imports: Vec::new(),
+        included: HashSet::new(),
+        chain: Vec::new(),
+    };
+
+    let mut candidates = vec![(managed.to_path_buf(), MemoryKind::Managed)];
+    if let Some(home) = home {
+        candidates.push((home.join(".claude").join("CLAUDE.md"), MemoryKind::User));
+    }
+    let mut ancestors: Vec<&Path> = working_dir.ancestors().collect();
+    ancestors.reverse();
+    for dir in ancestors {
+        candidates.push((dir.join("CLAUDE.md"), MemoryKind::Project));
+        candidates.push((dir.join(".claude").join("CLAUDE.md"), MemoryKind::Project));
+        candidates.push((dir.join("CLAUDE.local.md"), MemoryKind::Local));
+    }
+
+    for (path, kind) in candidates {
+        if !path.is_file() {
+            continue;
+        }
+        // A file can be reached both directly and via an import (e.g. the
+        // user file when the home directory is an ancestor)
+        if path
+            .canonicalize()
+            .is_ok_and(|canonical| resolver.included.contains(&canonical))
+        {
+            continue;
+        }
+        resolver.include(&path, kind, None);
+    }
+
+    ResolvedMemory {
+        working_dir: working_dir.to_string_lossy().to_string(),
+        size: resolver.text.len(),
+        text: resolver.text,
+        sources: resolver.sources,
+        skipped_imports: resolver.skipped_imports,
+    }
+}
+
+/// Resolves the CLAUDE.md memory Claude Code would load in a working directory,
+/// with `@path` imports expanded
+#[tauri::command]
+pub async fn resolve_claude_memory(
+    context: tauri::State<'_, CoreContext>,
+    working_dir: String,
+) -> Result<ResolvedMemory, String> {
+    log::info!("Resolving CLAUDE.md memory for: {}", working_dir);
+
+    let path = PathBuf::from(&working_dir);
+    if !path.is_dir() {
+        return Err(format!("Directory does not exist: {}", working_dir));
+    }
+
+    let memory = resolve_memory(
+        &path,
+        context.home_dir(),
+        &managed_settings_dir().join("CLAUDE.md"),
+    );
+    log::info!(
+        "Resolved {} memory files ({} bytes) for {}",
+        memory.sources.len(),
+        memory.size,
+        working_dir
+    );
+    Ok(memory)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_imports_skips_code() {
+        let content = "See @docs/style.md and @~/notes.md.\n\
+                       Email me at me@example.com\n\
+                       Use `@not-an-import` here\n\
+                       ```\n@also/not\n```\n\
+                       @my\\ file.md";
+        assert_eq!(
+            parse_imports(content),
+            vec!["docs/style.md", "~/notes.md", "my file.md"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_memory_order_and_imports() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let home = root.join("home");
+        let repo = root.join("repo");
+        let app = repo.join("app");
+        fs::create_dir_all(home.join(".claude")).unwrap();
+        fs::create_dir_all(app.join("docs")).unwrap();
+
+        fs::write(home.join(".claude/CLAUDE.md"), "user").unwrap();
+        fs::write(repo.join("CLAUDE.md"), "repo @app/docs/a.md").unwrap();
+        fs::write(app.join("CLAUDE.md"), "app @docs/a.md @missing.md").unwrap();
+        fs::write(app.join("CLAUDE.local.md"), "local").unwrap();
+        fs::write(app.join("docs/a.md"), "a @b.md").unwrap();
+        fs::write(app.join("docs/b.md"), "b @a.md").unwrap();
+
+        let memory = resolve_memory(&app, Some(&home), &root.join("managed.md"));
+        let paths: Vec<String> = memory
+            .sources
+            .iter()
+            .map(|s| {
+                Path::new(&s.path)
+                    .strip_prefix(&root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "home/.claude/CLAUDE.md",
+                "repo/CLAUDE.md",
+                "repo/app/docs/a.md",
+                "repo/app/docs/b.md",
+                "repo/app/CLAUDE.md",
+                "repo/app/CLAUDE.local.md",
+            ]
+        );
+        assert_eq!(memory.sources[3].depth, 2);
+        assert_eq!(memory.sources[5].kind, MemoryKind::Local);
+        assert_eq!(
+            &memory.text[memory.sources[2].start..memory.sources[2].end],
+            "a @b.md"
+        );
+        assert_eq!(memory.size, memory.text.len());
+
+        let reasons: Vec<(&str, &str)> = memory
+            .skipped_imports
+            .iter()
+            .map(|s| (s.import.as_str(), s.reason.as_str()))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                ("a.md", "cycle"),
+                ("docs/a.md", "duplicate"),
+                ("missing.md", "not_found")
+            ]
+        );
+    }
+}
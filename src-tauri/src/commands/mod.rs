@@ -5,3 +5,24 @@ pub mod usage;
 pub mod storage;
 pub mod slash_commands;
 pub mod proxy;
+pub mod session_metadata;
+pub mod external_sessions;
+pub mod scheduler;
+pub mod budget;
+pub mod profiles;
+pub mod worktrees;
+pub mod attachments;
+pub mod todos;
+pub mod memory;
+pub mod files;
+pub mod settings;
+pub mod hooks;
+pub mod hook_activity;
+pub mod capabilities;
+pub mod stream_events;
+pub mod replay;
+pub mod api_server;
+pub mod session_changes;
+pub mod process_recovery;
+pub mod process_monitor;
+pub mod notifications;
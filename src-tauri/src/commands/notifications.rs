@@ -0,0 +1,520 @@
+use chrono::{SecondsFormat, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+use super::claude::RunOutcome;
+use super::proxy::{
+    get_claudio_settings, save_claudio_settings, NotificationSettings, RunEvent, WebhookConfig,
+};
+
+/// Attempts per webhook delivery, including the first
+const WEBHOOK_ATTEMPTS: u32 = 4;
+/// Wait before the first retry, doubled for each further one
+const WEBHOOK_RETRY_DELAY: Duration = Duration::from_secs(2);
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Result text longer than this is cut from desktop notifications
+const NOTIFICATION_BODY_CHARS: usize = 200;
+
+/// A run event, as sent to webhooks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunNotification {
+    pub status: RunEvent,
+    pub session_id: Option<String>,
+    pub project_path: String,
+    pub cost_usd: Option<f64>,
+    pub duration_ms: Option<u64>,
+    /// The run's final result text, or why it stopped or is waiting
+    pub result: Option<String>,
+    pub timestamp: String,
+}
+
+impl RunNotification {
+    /// Describes a finished run
+    pub fn from_outcome(outcome: &RunOutcome, project_path: &str, elapsed: Duration) -> Self {
+        let result = outcome.result.as_ref();
+        let is_error = result.is_some_and(|result| result["is_error"].as_bool() == Some(true));
+        let status = if outcome.budget_exceeded.is_some() {
+            RunEvent::BudgetExceeded
+        } else if outcome.cancelled {
+            RunEvent::Cancelled
+        } else if outcome.success && !is_error {
+            RunEvent::Completed
+        } else {
+            RunEvent::Failed
+        };
+
+        let text = match &outcome.budget_exceeded {
+            Some(exceeded) => Some(exceeded.reason.clone()),
+            None => result.and_then(|result| result["result"].as_str().map(str::to_string)),
+        };
+        Self {
+            status,
+            session_id: outcome.session_id.clone(),
+            project_path: project_path.to_string(),
+            cost_usd: result.and_then(|result| {
+                result["total_cost_usd"]
+                    .as_f64()
+                    .or_else(|| result["cost_usd"].as_f64())
+            }),
+            duration_ms: result
+                .and_then(|result| result["duration_ms"].as_u64())
+                .or(Some(elapsed.as_millis() as u64)),
+            result: text,
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        }
+    }
+
+    /// The event name sent in `X-Claudio-Event`, as it's serialized in the payload
+    fn event(&self) -> String {
+        serde_json::to_value(self.status)
+            .ok()
+            .and_then(|event| event.as_str().map(str::to_string))
+            .unwrap_or_default()
+    }
+
+    /// Describes a run whose tool call was refused for lack of permission
+    pub fn permission_required(
+        session_id: Option<String>,
+        project_path: &str,
+        message: String,
+    ) -> Self {
+        Self {
+            status: RunEvent::PermissionRequired,
+            session_id,
+            project_path: project_path.to_string(),
+            cost_usd: None,
+            duration_ms: None,
+            result: Some(message),
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        }
+    }
+
+    fn title(&self) -> String {
+        let project = self
+            .project_path
+            .rsplit(['/', '\\'])
+            .find(|part| !part.is_empty())
+            .unwrap_or("Claude");
+        let what = match self.status {
+            RunEvent::Completed => "Run completed",
+            RunEvent::Failed => "Run failed",
+            RunEvent::Cancelled => "Run cancelled",
+            RunEvent::BudgetExceeded => "Run stopped at its budget",
+            RunEvent::PermissionRequired => "Run is waiting for permission",
+            RunEvent::Test => "Test event",
+        };
+        format!("{}: {}", project, what)
+    }
+
+    fn body(&self) -> String {
+        let text = self.result.as_deref().unwrap_or_default().trim();
+        let mut body: String = text.chars().take(NOTIFICATION_BODY_CHARS).collect();
+        if text.chars().count() > NOTIFICATION_BODY_CHARS {
+            body.push('…');
+        }
+        if let Some(cost) = self.cost_usd {
+            if !body.is_empty() {
+                body.push('\n');
+            }
+            body.push_str(&format!("${:.2}", cost));
+        }
+        body
+    }
+}
+
+/// The message of a tool call the CLI refused for lack of permission, if `msg`
+/// reports one. Headless runs can't prompt, so this is where they'd wait.
+pub fn permission_request(msg: &Value) -> Option<String> {
+    if msg["type"] != "user" {
+        return None;
+    }
+    msg["message"]["content"]
+        .as_array()?
+        .iter()
+        .filter(|block| block["type"] == "tool_result" && block["is_error"] == true)
+        .filter_map(|block| match &block["content"] {
+            Value::String(text) => Some(text.clone()),
+            Value::Array(parts) => parts
+                .iter()
+                .find_map(|part| part["text"].as_str().map(str::to_string)),
+            _ => None,
+        })
+        .find(|text| text.contains("requested permissions"))
+}
+
+/// Shows the desktop notification and calls the webhooks configured for the
+/// event, in the background
+pub fn notify_run_event(app: &AppHandle, notification: RunNotification) {
+    let app = app.clone();
+    tokio::spawn(async move {
+        let settings = match get_claudio_settings().await {
+            Ok(settings) => settings,
+            Err(e) => {
+                log::warn!("Not sending run notifications: {}", e);
+                return;
+            }
+        };
+
+        let desktop = &settings.notifications;
+        if desktop.enabled && desktop.events.contains(&notification.status) {
+            if let Err(e) = app
+                .notification()
+                .builder()
+                .title(notification.title())
+                .body(notification.body())
+                .show()
+            {
+                log::warn!("Failed to show notification: {}", e);
+            }
+        }
+
+        let client = webhook_client();
+        for webhook in settings.webhooks {
+            if !webhook.enabled || !webhook.events.contains(&notification.status) {
+                continue;
+            }
+            let client = client.clone();
+            let notification = notification.clone();
+            tokio::spawn(async move {
+                if let Err(e) =
+                    deliver_webhook(&client, &webhook, &notification, WEBHOOK_RETRY_DELAY).await
+                {
+                    log::error!("Webhook '{}' failed: {}", webhook.name, e);
+                }
+            });
+        }
+    });
+}
+
+fn webhook_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+}
+
+/// Signs `{timestamp}.{body}` with HMAC-SHA256, hex encoded
+pub fn sign_payload(secret: &str, timestamp: &str, body: &str) -> Result<String, String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .map_err(|e| format!("Invalid webhook secret: {}", e))?;
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    Ok(mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+/// Builds the POST of a payload to a webhook, signed if it has a secret
+fn signed_request(
+    client: &reqwest::Client,
+    webhook: &WebhookConfig,
+    event: &str,
+    body: &str,
+) -> Result<reqwest::RequestBuilder, String> {
+    let timestamp = Utc::now().timestamp().to_string();
+    let mut request = client
+        .post(&webhook.url)
+        .header("Content-Type", "application/json")
+        .header("X-Claudio-Event", event)
+        .header("X-Claudio-Timestamp", &timestamp)
+        .body(body.to_string());
+    if let Some(secret) = webhook
+        .secret
+        .as_deref()
+        .filter(|secret| !secret.is_empty())
+    {
+        let signature = sign_payload(secret, &timestamp, body)?;
+        request = request.header("X-Claudio-Signature", format!("sha256={}", signature));
+    }
+    Ok(request)
+}
+
+/// POSTs a run event to a webhook, retrying network errors, 429s and 5xx
+/// responses with exponential backoff. Returns the number of attempts made.
+///
+/// Requests carry `X-Claudio-Event`, `X-Claudio-Timestamp` and, with a secret,
+/// `X-Claudio-Signature: sha256=<hmac of "{timestamp}.{body}">`.
+pub async fn deliver_webhook(
+    client: &reqwest::Client,
+    webhook: &WebhookConfig,
+    notification: &RunNotification,
+    retry_delay: Duration,
+) -> Result<u32, String> {
+    let body = serde_json::to_string(notification)
+        .map_err(|e| format!("Failed to serialize webhook payload: {}", e))?;
+    let event = notification.event();
+
+    let mut delay = retry_delay;
+    for attempt in 1..=WEBHOOK_ATTEMPTS {
+        let request = signed_request(client, webhook, &event, &body)?;
+        let error = match request.send().await {
+            Ok(response) if response.status().is_success() => {
+                log::info!(
+                    "Delivered {} event to webhook '{}' (attempt {})",
+                    event,
+                    webhook.name,
+                    attempt
+                );
+                return Ok(attempt);
+            }
+            Ok(response) => {
+                let status = response.status();
+                if !status.is_server_error() && status != reqwest::StatusCode::TOO_MANY_REQUESTS {
+                    return Err(format!("{} responded {}", webhook.url, status));
+                }
+                format!("{} responded {}", webhook.url, status)
+            }
+            Err(e) => format!("Failed to reach {}: {}", webhook.url, e),
+        };
+
+        if attempt == WEBHOOK_ATTEMPTS {
+            return Err(format!("{} (after {} attempts)", error, attempt));
+        }
+        log::warn!(
+            "Webhook '{}' attempt {} failed, retrying in {:?}: {}",
+            webhook.name,
+            attempt,
+            delay,
+            error
+        );
+        tokio::time::sleep(delay).await;
+        delay *= 2;
+    }
+    unreachable!("the last attempt returns")
+}
+
+/// Gets which run events show desktop notifications
+#[tauri::command]
+pub async fn get_notification_settings() -> Result<NotificationSettings, String> {
+    Ok(get_claudio_settings().await?.notifications)
+}
+
+/// Saves which run events show desktop notifications
+#[tauri::command]
+pub async fn save_notification_settings(settings: NotificationSettings) -> Result<(), String> {
+    let mut claudio_settings = get_claudio_settings().await?;
+    claudio_settings.notifications = settings;
+    save_claudio_settings(claudio_settings).await
+}
+
+/// Lists the configured webhooks
+#[tauri::command]
+pub async fn list_webhooks() -> Result<Vec<WebhookConfig>, String> {
+    Ok(get_claudio_settings().await?.webhooks)
+}
+
+/// Creates or replaces a webhook by name, generating a signing secret if it has none
+#[tauri::command]
+pub async fn save_webhook(webhook: WebhookConfig) -> Result<WebhookConfig, String> {
+    let name = webhook.name.trim();
+    if name.is_empty() {
+        return Err("Webhook name cannot be empty".to_string());
+    }
+    let url = reqwest::Url::parse(webhook.url.trim())
+        .map_err(|e| format!("Invalid webhook URL '{}': {}", webhook.url, e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("Webhook URL must be http or https: {}", url));
+    }
+
+    log::info!("Saving webhook: {}", name);
+
+    let secret = webhook
+        .secret
+        .filter(|secret| !secret.trim().is_empty())
+        .unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string());
+    let webhook = WebhookConfig {
+        name: name.to_string(),
+        url: url.to_string(),
+        secret: Some(secret),
+        ..webhook
+    };
+    let mut settings = get_claudio_settings().await?;
+    match settings
+        .webhooks
+        .iter_mut()
+        .find(|w| w.name == webhook.name)
+    {
+        Some(existing) => *existing = webhook.clone(),
+        None => settings.webhooks.push(webhook.clone()),
+    }
+    save_claudio_settings(settings).await?;
+    Ok(webhook)
+}
+
+/// Deletes a webhook
+#[tauri::command]
+pub async fn delete_webhook(name: String) -> Result<(), String> {
+    log::info!("Deleting webhook: {}", name);
+
+    let mut settings = get_claudio_settings().await?;
+    settings.webhooks.retain(|webhook| webhook.name != name);
+    save_claudio_settings(settings).await
+}
+
+/// Sends a sample `completed` event to a webhook, without retries. Returns the
+/// error if it isn't accepted.
+#[tauri::command]
+pub async fn test_webhook(name: String) -> Result<(), String> {
+    let webhook = get_claudio_settings()
+        .await?
+        .webhooks
+        .into_iter()
+        .find(|webhook| webhook.name == name)
+        .ok_or_else(|| format!("Webhook '{}' not found", name))?;
+
+    let notification = RunNotification {
+        status: RunEvent::Test,
+        session_id: None,
+        project_path: String::new(),
+        cost_usd: Some(0.0),
+        duration_ms: Some(0),
+        result: Some("Test event from Claudio".to_string()),
+        timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+    };
+    let body = serde_json::to_string(&notification)
+        .map_err(|e| format!("Failed to serialize webhook payload: {}", e))?;
+    let response = signed_request(&webhook_client(), &webhook, &notification.event(), &body)?
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach {}: {}", webhook.url, e))?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("{} responded {}", webhook.url, response.status()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::State;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::routing::post;
+    use axum::Router;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    type Received = Arc<Mutex<Vec<(HeaderMap, String)>>>;
+
+    /// Records each request and fails the first one with a 503
+    async fn flaky_hook(
+        State(received): State<Received>,
+        headers: HeaderMap,
+        body: String,
+    ) -> StatusCode {
+        let mut received = received.lock().unwrap();
+        received.push((headers, body));
+        if received.len() == 1 {
+            StatusCode::SERVICE_UNAVAILABLE
+        } else {
+            StatusCode::NO_CONTENT
+        }
+    }
+
+    #[test]
+    fn test_notification_from_outcome() {
+        let outcome = RunOutcome {
+            session_id: Some("session-1".to_string()),
+            success: true,
+            exit_code: Some(0),
+            result: Some(json!({
+                "type": "result",
+                "is_error": false,
+                "result": "Added the login form",
+                "total_cost_usd": 0.42,
+                "duration_ms": 61000
+            })),
+            budget_exceeded: None,
+            cancelled: false,
+        };
+        let notification = RunNotification::from_outcome(&outcome, "/work/app", Duration::ZERO);
+        assert_eq!(notification.status, RunEvent::Completed);
+        assert_eq!(notification.duration_ms, Some(61000));
+        assert_eq!(notification.title(), "app: Run completed");
+        assert_eq!(notification.body(), "Added the login form\n$0.42");
+
+        // A run killed by a signal only counts as cancelled if it was cancelled
+        let killed = RunOutcome {
+            success: false,
+            exit_code: None,
+            result: None,
+            ..outcome
+        };
+        let notification =
+            RunNotification::from_outcome(&killed, "/work/app", Duration::from_secs(3));
+        assert_eq!(notification.status, RunEvent::Failed);
+        assert_eq!(notification.duration_ms, Some(3000));
+        let cancelled = RunOutcome {
+            cancelled: true,
+            ..killed
+        };
+        let notification = RunNotification::from_outcome(&cancelled, "/work/app", Duration::ZERO);
+        assert_eq!(notification.status, RunEvent::Cancelled);
+
+        let refused = json!({
+            "type": "user",
+            "message": {"content": [{
+                "type": "tool_result",
+                "is_error": true,
+                "content": "Claude requested permissions to use Bash, but you haven't granted it yet."
+            }]}
+        });
+        assert!(permission_request(&refused).unwrap().contains("Bash"));
+    }
+
+    #[tokio::test]
+    async fn test_deliver_webhook_signs_and_retries() {
+        let received = Received::default();
+        let app = Router::new()
+            .route("/hook", post(flaky_hook))
+            .with_state(received.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let webhook = WebhookConfig {
+            name: "relay".to_string(),
+            url,
+            secret: Some("s3cret".to_string()),
+            events: vec![RunEvent::Completed],
+            enabled: true,
+        };
+        let notification = RunNotification::permission_required(
+            Some("session-1".to_string()),
+            "/work/app",
+            "Claude requested permissions to use Bash".to_string(),
+        );
+        let attempts = deliver_webhook(
+            &webhook_client(),
+            &webhook,
+            &notification,
+            Duration::from_millis(10),
+        )
+        .await
+        .unwrap();
+        assert_eq!(attempts, 2);
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 2);
+        let (headers, body) = &received[1];
+        let payload: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(payload["status"], "permission_required");
+        assert_eq!(payload["session_id"], "session-1");
+        assert_eq!(headers["x-claudio-event"], "permission_required");
+        let timestamp = headers["x-claudio-timestamp"].to_str().unwrap();
+        let expected = sign_payload("s3cret", timestamp, body).unwrap();
+        assert_eq!(
+            headers["x-claudio-signature"].to_str().unwrap(),
+            format!("sha256={}", expected)
+        );
+    }
+}
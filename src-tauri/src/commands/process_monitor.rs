@@ -0,0 +1,326 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::proxy::{
+    get_claudio_settings, get_termination_timeouts, save_claudio_settings, MonitoringSettings,
+    ThresholdAction,
+};
+use crate::process::{
+    clock_ticks_per_second, sample_process_tree, ProcessInfo, ProcessRegistryState, ProcessType,
+    TreeUsage,
+};
+
+/// Resource usage of a running process and everything it started
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessStats {
+    pub run_id: i64,
+    pub pid: u32,
+    pub session_id: Option<String>,
+    /// CPU use since the previous sample, where 100 is one core fully busy
+    pub cpu_percent: f64,
+    pub rss_bytes: u64,
+    pub open_fds: u64,
+    pub child_processes: u32,
+    pub sampled_at: DateTime<Utc>,
+}
+
+/// Emitted once per run when it has crossed a threshold for
+/// `sustained_samples` samples in a row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdExceeded {
+    pub stats: ProcessStats,
+    /// The thresholds crossed, e.g. "RSS 2048 MB > 1024 MB"
+    pub reasons: Vec<String>,
+    pub action: ThresholdAction,
+}
+
+/// The latest sample of a run
+struct Sample {
+    stats: ProcessStats,
+    cpu_ticks: u64,
+    taken_at: Instant,
+    /// Samples in a row that crossed a threshold
+    breaches: u32,
+    /// Whether the run has been reported, so it's reported only once
+    alerted: bool,
+}
+
+/// Latest resource samples of the registered processes
+#[derive(Default)]
+pub struct ProcessMonitorState {
+    samples: Mutex<HashMap<i64, Sample>>,
+}
+
+impl ProcessMonitorState {
+    fn latest(&self, run_id: i64) -> Option<ProcessStats> {
+        let samples = self.samples.lock().ok()?;
+        samples.get(&run_id).map(|sample| sample.stats.clone())
+    }
+}
+
+fn session_id(info: &ProcessInfo) -> Option<String> {
+    match &info.process_type {
+        ProcessType::ClaudeSession { session_id } => Some(session_id.clone()),
+        ProcessType::AgentRun { .. } => None,
+    }
+}
+
+/// Turns a tree sample into stats, with CPU use measured against the previous sample
+fn to_stats(
+    info: &ProcessInfo,
+    usage: &TreeUsage,
+    previous: Option<(u64, Instant)>,
+    now: Instant,
+) -> ProcessStats {
+    let cpu_percent = match previous {
+        Some((cpu_ticks, taken_at)) if now > taken_at => {
+            // Descendants that exited take their ticks with them; don't go negative
+            let ticks = usage.cpu_ticks.saturating_sub(cpu_ticks) as f64;
+            let elapsed = now.duration_since(taken_at).as_secs_f64();
+            ticks / clock_ticks_per_second() as f64 / elapsed * 100.0
+        }
+        _ => 0.0,
+    };
+    ProcessStats {
+        run_id: info.run_id,
+        pid: info.pid,
+        session_id: session_id(info),
+        cpu_percent,
+        rss_bytes: usage.rss_bytes,
+        open_fds: usage.open_fds,
+        child_processes: usage.child_processes,
+        sampled_at: Utc::now(),
+    }
+}
+
+/// The thresholds a sample crosses
+fn exceeded_thresholds(stats: &ProcessStats, settings: &MonitoringSettings) -> Vec<String> {
+    let mut reasons = Vec::new();
+    if let Some(max) = settings.max_cpu_percent {
+        if stats.cpu_percent > max {
+            reasons.push(format!("CPU {:.0}% > {:.0}%", stats.cpu_percent, max));
+        }
+    }
+    if let Some(max) = settings.max_rss_mb {
+        let rss_mb = stats.rss_bytes / (1024 * 1024);
+        if rss_mb > max {
+            reasons.push(format!("RSS {} MB > {} MB", rss_mb, max));
+        }
+    }
+    if let Some(max) = settings.max_open_fds {
+        if stats.open_fds > max {
+            reasons.push(format!("{} open files > {}", stats.open_fds, max));
+        }
+    }
+    if let Some(max) = settings.max_child_processes {
+        if stats.child_processes > max {
+            reasons.push(format!(
+                "{} child processes > {}",
+                stats.child_processes, max
+            ));
+        }
+    }
+    reasons
+}
+
+/// Samples every registered process, emits `process-stats` and acts on thresholds
+async fn sample_once(app: &AppHandle, settings: &MonitoringSettings) -> Result<(), String> {
+    let registry = app.state::<ProcessRegistryState>().0.clone();
+    let processes = registry.get_running_processes()?;
+
+    let usages = tokio::task::spawn_blocking(move || {
+        processes
+            .into_iter()
+            .filter_map(|info| sample_process_tree(info.pid).map(|usage| (info, usage)))
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| format!("Failed to sample processes: {}", e))?;
+
+    let now = Instant::now();
+    let mut all_stats = Vec::new();
+    let mut exceeded = Vec::new();
+    {
+        let state = app.state::<ProcessMonitorState>();
+        let mut samples = state.samples.lock().map_err(|e| e.to_string())?;
+        // Forget runs that have ended
+        samples.retain(|run_id, _| usages.iter().any(|(info, _)| info.run_id == *run_id));
+
+        for (info, usage) in &usages {
+            let previous = samples.get(&info.run_id);
+            let stats = to_stats(
+                info,
+                usage,
+                previous.map(|sample| (sample.cpu_ticks, sample.taken_at)),
+                now,
+            );
+            let mut alerted = previous.is_some_and(|sample| sample.alerted);
+            let reasons = exceeded_thresholds(&stats, settings);
+            let breaches = if reasons.is_empty() {
+                0
+            } else {
+                previous.map_or(0, |sample| sample.breaches) + 1
+            };
+            if !alerted && breaches >= settings.sustained_samples.max(1) {
+                alerted = true;
+                exceeded.push(ThresholdExceeded {
+                    stats: stats.clone(),
+                    reasons,
+                    action: settings.on_threshold,
+                });
+            }
+            all_stats.push(stats.clone());
+            samples.insert(
+                info.run_id,
+                Sample {
+                    stats,
+                    cpu_ticks: usage.cpu_ticks,
+                    taken_at: now,
+                    breaches,
+                    alerted,
+                },
+            );
+        }
+    }
+
+    if !all_stats.is_empty() {
+        let _ = app.emit("process-stats", &all_stats);
+    }
+
+    for event in exceeded {
+        let run_id = event.stats.run_id;
+        log::warn!(
+            "Run {} (PID: {}) crossed resource thresholds: {}",
+            run_id,
+            event.stats.pid,
+            event.reasons.join(", ")
+        );
+        if let Some(ref session_id) = event.stats.session_id {
+            let _ = app.emit(
+                &format!("process-threshold-exceeded:{}", session_id),
+                &event,
+            );
+        }
+        let _ = app.emit("process-threshold-exceeded", &event);
+
+        if event.action == ThresholdAction::Cancel {
+            let registry = registry.clone();
+            tokio::spawn(async move {
+                let timeouts = get_termination_timeouts().await;
+                if let Err(e) = registry.kill_process(run_id, timeouts).await {
+                    log::error!("Failed to stop run {} over its thresholds: {}", run_id, e);
+                }
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Samples the registered processes for the lifetime of the app
+pub async fn run_process_monitor(app: AppHandle) {
+    log::info!("Process monitor started");
+    loop {
+        let settings = get_claudio_settings()
+            .await
+            .map(|settings| settings.monitoring)
+            .unwrap_or_default();
+        if settings.enabled {
+            if let Err(e) = sample_once(&app, &settings).await {
+                log::error!("Process monitor sample failed: {}", e);
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(settings.interval_secs.max(1))).await;
+    }
+}
+
+/// Gets the resource usage of a registered process: the monitor's latest sample,
+/// or a fresh one (without CPU use) if it hasn't been sampled yet. Returns `None`
+/// if the run isn't registered or can't be sampled on this platform.
+#[tauri::command]
+pub async fn get_process_stats(
+    registry: State<'_, ProcessRegistryState>,
+    monitor: State<'_, ProcessMonitorState>,
+    run_id: i64,
+) -> Result<Option<ProcessStats>, String> {
+    if let Some(stats) = monitor.latest(run_id) {
+        return Ok(Some(stats));
+    }
+    let Some(info) = registry.0.get_process(run_id)? else {
+        return Ok(None);
+    };
+    let pid = info.pid;
+    let usage = tokio::task::spawn_blocking(move || sample_process_tree(pid))
+        .await
+        .map_err(|e| format!("Failed to sample process {}: {}", pid, e))?;
+    Ok(usage.map(|usage| to_stats(&info, &usage, None, Instant::now())))
+}
+
+/// Gets the sampling interval and resource thresholds
+#[tauri::command]
+pub async fn get_monitoring_settings() -> Result<MonitoringSettings, String> {
+    Ok(get_claudio_settings().await?.monitoring)
+}
+
+/// Saves the sampling interval and resource thresholds, applied from the next sample
+#[tauri::command]
+pub async fn save_monitoring_settings(settings: MonitoringSettings) -> Result<(), String> {
+    if settings.interval_secs == 0 {
+        return Err("Sampling interval must be at least one second".to_string());
+    }
+    let mut claudio_settings = get_claudio_settings().await?;
+    claudio_settings.monitoring = settings;
+    save_claudio_settings(claudio_settings).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cpu_and_thresholds() {
+        let info = ProcessInfo {
+            run_id: 1000000,
+            process_type: ProcessType::ClaudeSession {
+                session_id: "session-1".to_string(),
+            },
+            pid: 4242,
+            started_at: Utc::now(),
+            project_path: "/work/app".to_string(),
+            task: "Run the tests".to_string(),
+            model: "sonnet".to_string(),
+        };
+        let usage = TreeUsage {
+            cpu_ticks: 3 * clock_ticks_per_second(),
+            rss_bytes: 2048 * 1024 * 1024,
+            open_fds: 40,
+            child_processes: 12,
+        };
+        let now = Instant::now();
+        let stats = to_stats(&info, &usage, Some((0, now - Duration::from_secs(2))), now);
+        assert!((stats.cpu_percent - 150.0).abs() < 1.0);
+        assert_eq!(stats.session_id.as_deref(), Some("session-1"));
+        assert_eq!(to_stats(&info, &usage, None, now).cpu_percent, 0.0);
+
+        let settings = MonitoringSettings {
+            max_cpu_percent: Some(200.0),
+            max_rss_mb: Some(1024),
+            max_child_processes: Some(8),
+            ..MonitoringSettings::default()
+        };
+        assert_eq!(
+            exceeded_thresholds(&stats, &settings),
+            ["RSS 2048 MB > 1024 MB", "12 child processes > 8"]
+        );
+        assert!(exceeded_thresholds(&stats, &MonitoringSettings::default()).is_empty());
+
+        // Saved settings missing newer fields still load
+        let partial: MonitoringSettings = serde_json::from_str(r#"{"max_rss_mb": 512}"#).unwrap();
+        assert_eq!(partial.max_rss_mb, Some(512));
+        assert!(!partial.enabled);
+        assert_eq!(partial.sustained_samples, 3);
+    }
+}
@@ -0,0 +1,153 @@
+use std::path::Path;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, State};
+
+use super::external_sessions::read_new_lines;
+use super::proxy::get_termination_timeouts;
+use crate::context::CoreContext;
+use crate::process::{
+    terminate_process, PersistedProcess, ProcessInfo, ProcessRegistryState, ProcessType,
+    TerminationReport, TerminationStage,
+};
+
+/// How often a reattached session's transcript is checked for new lines
+const TAIL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Lists runs started by a previous launch of Claudio that are still running
+#[tauri::command]
+pub async fn list_orphaned_processes(
+    registry: State<'_, ProcessRegistryState>,
+) -> Result<Vec<PersistedProcess>, String> {
+    registry.0.get_orphaned_processes()
+}
+
+/// Stops an orphaned run and its process group. Returns `None` if there is no
+/// such orphan.
+#[tauri::command]
+pub async fn kill_orphaned_process(
+    registry: State<'_, ProcessRegistryState>,
+    run_id: i64,
+) -> Result<Option<TerminationReport>, String> {
+    let Some(orphan) = registry.0.take_orphan(run_id)? else {
+        return Ok(None);
+    };
+    let pid = orphan.info.pid;
+
+    // The PID was checked at launch, but the run may have exited since
+    if !orphan
+        .identity
+        .as_ref()
+        .is_some_and(|identity| identity.matches(pid))
+    {
+        log::info!("Orphaned run {} (PID: {}) has already exited", run_id, pid);
+        return Ok(Some(TerminationReport {
+            pid,
+            process_group: false,
+            stage: TerminationStage::NotRunning,
+            elapsed_ms: 0,
+        }));
+    }
+
+    log::info!("Killing orphaned run {} (PID: {})", run_id, pid);
+    let timeouts = get_termination_timeouts().await;
+    let report = tokio::task::spawn_blocking(move || terminate_process(pid, &timeouts))
+        .await
+        .map_err(|e| format!("Failed to stop orphaned run {}: {}", run_id, e))??;
+    Ok(Some(report))
+}
+
+/// Reattaches to an orphaned Claude session: it is registered again, so it shows
+/// among the running sessions and can be cancelled, and new lines of its
+/// transcript are streamed as `claude-output:{session_id}` until it exits, when
+/// `claude-complete:{session_id}` is emitted
+#[tauri::command]
+pub async fn reattach_orphaned_process(
+    app: AppHandle,
+    registry: State<'_, ProcessRegistryState>,
+    context: State<'_, CoreContext>,
+    run_id: i64,
+) -> Result<ProcessInfo, String> {
+    let orphans = registry.0.get_orphaned_processes()?;
+    let orphan = orphans
+        .into_iter()
+        .find(|orphan| orphan.info.run_id == run_id)
+        .ok_or_else(|| format!("No orphaned run {}", run_id))?;
+
+    let ProcessType::ClaudeSession { session_id } = &orphan.info.process_type else {
+        return Err(format!(
+            "Run {} has no session transcript to follow; it can only be killed",
+            run_id
+        ));
+    };
+    let session_id = session_id.clone();
+    let transcript = context
+        .session_transcript(&session_id)
+        .ok_or_else(|| format!("Transcript for session {} not found", session_id))?;
+
+    let Some(identity) = orphan.identity.clone() else {
+        return Err(format!("Run {} can't be verified as still running", run_id));
+    };
+    if !identity.matches(orphan.info.pid) {
+        registry.0.take_orphan(run_id)?;
+        return Err(format!("Run {} has already exited", run_id));
+    }
+
+    let info = registry
+        .0
+        .adopt_orphan(run_id)?
+        .ok_or_else(|| format!("No orphaned run {}", run_id))?;
+    log::info!(
+        "Reattached to session {} (PID: {}), following {:?}",
+        session_id,
+        info.pid,
+        transcript
+    );
+
+    let registry = registry.0.clone();
+    let pid = info.pid;
+    tokio::spawn(async move {
+        // The history is available through load_session_history; stream from here on
+        let mut offset = std::fs::metadata(&transcript)
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+        loop {
+            // Read once more after the process exits, for its last lines
+            let running =
+                identity.matches(pid) && matches!(registry.get_process(run_id), Ok(Some(_)));
+            offset = stream_new_lines(&app, &transcript, &session_id, offset);
+            if !running {
+                break;
+            }
+            tokio::time::sleep(TAIL_INTERVAL).await;
+        }
+
+        log::info!("Reattached session {} ended", session_id);
+        let _ = registry.unregister_process(run_id);
+        // The exit status of a process spawned by a previous launch can't be read
+        let _ = app.emit(&format!("claude-complete:{}", session_id), true);
+    });
+
+    Ok(info)
+}
+
+/// Emits the lines appended to a transcript since `offset`, returning the new offset
+fn stream_new_lines(app: &AppHandle, transcript: &Path, session_id: &str, offset: u64) -> u64 {
+    // The transcript was rewritten (e.g. a checkpoint restore); skip ahead
+    if let Ok(metadata) = std::fs::metadata(transcript) {
+        if metadata.len() < offset {
+            return metadata.len();
+        }
+    }
+    match read_new_lines(transcript, offset) {
+        Ok((lines, new_offset)) => {
+            for line in lines {
+                let _ = app.emit(&format!("claude-output:{}", session_id), &line);
+            }
+            new_offset
+        }
+        Err(e) => {
+            log::warn!("Failed to tail session file {:?}: {}", transcript, e);
+            offset
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use tokio::process::Command;
+
+use super::proxy::{
+    get_claudio_settings, save_claudio_settings, ClaudioSettings, ExecutionProfile,
+};
+
+/// Picks the profile for a run: the one requested by the run, else the
+/// project's profile, else the default profile
+fn select_profile<'a>(
+    settings: &'a ClaudioSettings,
+    requested: Option<&str>,
+    project_path: &str,
+) -> Result<Option<&'a ExecutionProfile>, String> {
+    let name = requested
+        .filter(|name| !name.is_empty())
+        .or_else(|| {
+            settings
+                .project_profiles
+                .get(project_path)
+                .map(|s| s.as_str())
+        })
+        .or(settings.default_profile.as_deref());
+
+    match name {
+        Some(name) => settings
+            .profiles
+            .iter()
+            .find(|profile| profile.name == name)
+            .map(Some)
+            .ok_or_else(|| format!("Execution profile '{}' not found", name)),
+        None => Ok(None),
+    }
+}
+
+/// Resolves the execution profile for a run in a project
+pub async fn resolve_execution_profile(
+    requested: Option<&str>,
+    project_path: &str,
+) -> Result<Option<ExecutionProfile>, String> {
+    let settings = get_claudio_settings().await?;
+    Ok(select_profile(&settings, requested, project_path)?.cloned())
+}
+
+/// Runs a profile's API key helper and returns the key it prints
+fn run_api_key_helper(helper: &str) -> Result<String, String> {
+    let output = if cfg!(target_os = "windows") {
+        std::process::Command::new("cmd")
+            .args(["/C", helper])
+            .output()
+    } else {
+        std::process::Command::new("sh")
+            .args(["-c", helper])
+            .output()
+    }
+    .map_err(|e| format!("Failed to run API key helper: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "API key helper failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let key = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if key.is_empty() {
+        return Err("API key helper printed no key".to_string());
+    }
+    Ok(key)
+}
+
+/// Sets a profile's environment overrides and API key on a command
+pub async fn apply_profile_env(
+    cmd: &mut Command,
+    profile: &ExecutionProfile,
+) -> Result<(), String> {
+    log::info!("Applying execution profile: {}", profile.name);
+
+    for (key, value) in &profile.env {
+        // Values may hold credentials, so only the key is logged
+        log::debug!("Setting env var from profile: {}", key);
+        cmd.env(key, value);
+    }
+
+    if let Some(helper) = profile
+        .api_key_helper
+        .as_deref()
+        .filter(|h| !h.trim().is_empty())
+    {
+        let helper = helper.to_string();
+        let key = tokio::task::spawn_blocking(move || run_api_key_helper(&helper))
+            .await
+            .map_err(|e| format!("API key helper task failed: {}", e))??;
+        cmd.env("ANTHROPIC_API_KEY", key);
+    }
+
+    Ok(())
+}
+
+/// Lists the configured execution profiles
+#[tauri::command]
+pub async fn list_execution_profiles() -> Result<Vec<ExecutionProfile>, String> {
+    Ok(get_claudio_settings().await?.profiles)
+}
+
+/// Creates or replaces an execution profile by name
+#[tauri::command]
+pub async fn save_execution_profile(profile: ExecutionProfile) -> Result<(), String> {
+    let name = profile.name.trim();
+    if name.is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+    if let Some(key) = profile
+        .env
+        .keys()
+        .find(|key| key.is_empty() || key.contains('=') || key.contains('\0'))
+    {
+        return Err(format!("Invalid environment variable name: '{}'", key));
+    }
+
+    log::info!("Saving execution profile: {}", name);
+
+    let profile = ExecutionProfile {
+        name: name.to_string(),
+        ..profile
+    };
+    let mut settings = get_claudio_settings().await?;
+    match settings
+        .profiles
+        .iter_mut()
+        .find(|p| p.name == profile.name)
+    {
+        Some(existing) => *existing = profile,
+        None => settings.profiles.push(profile),
+    }
+    save_claudio_settings(settings).await
+}
+
+/// Deletes an execution profile and any references to it
+#[tauri::command]
+pub async fn delete_execution_profile(name: String) -> Result<(), String> {
+    log::info!("Deleting execution profile: {}", name);
+
+    let mut settings = get_claudio_settings().await?;
+    settings.profiles.retain(|profile| profile.name != name);
+    settings
+        .project_profiles
+        .retain(|_, profile| *profile != name);
+    if settings.default_profile.as_deref() == Some(name.as_str()) {
+        settings.default_profile = None;
+    }
+    save_claudio_settings(settings).await
+}
+
+/// Sets the profile used when neither the run nor its project picks one
+#[tauri::command]
+pub async fn set_default_execution_profile(name: Option<String>) -> Result<(), String> {
+    let mut settings = get_claudio_settings().await?;
+    if let Some(ref name) = name {
+        if !settings
+            .profiles
+            .iter()
+            .any(|profile| &profile.name == name)
+        {
+            return Err(format!("Execution profile '{}' not found", name));
+        }
+    }
+    settings.default_profile = name;
+    save_claudio_settings(settings).await
+}
+
+/// Gets the profile assigned to a project
+#[tauri::command]
+pub async fn get_project_execution_profile(project_path: String) -> Result<Option<String>, String> {
+    Ok(get_claudio_settings()
+        .await?
+        .project_profiles
+        .get(&project_path)
+        .cloned())
+}
+
+/// Assigns a profile to a project, or clears the assignment
+#[tauri::command]
+pub async fn set_project_execution_profile(
+    project_path: String,
+    name: Option<String>,
+) -> Result<(), String> {
+    let mut settings = get_claudio_settings().await?;
+    match name {
+        Some(name) => {
+            if !settings.profiles.iter().any(|profile| profile.name == name) {
+                return Err(format!("Execution profile '{}' not found", name));
+            }
+            settings.project_profiles.insert(project_path, name);
+        }
+        None => {
+            settings.project_profiles.remove(&project_path);
+        }
+    }
+    save_claudio_settings(settings).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str) -> ExecutionProfile {
+        ExecutionProfile {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_select_profile_precedence() {
+        let mut settings = ClaudioSettings {
+            profiles: vec![profile("direct"), profile("gateway"), profile("bedrock")],
+            default_profile: Some("direct".to_string()),
+            ..Default::default()
+        };
+        settings
+            .project_profiles
+            .insert("/work/repo".to_string(), "gateway".to_string());
+
+        let pick = |requested, project| {
+            select_profile(&settings, requested, project)
+                .unwrap()
+                .map(|p| p.name.clone())
+        };
+        assert_eq!(
+            pick(Some("bedrock"), "/work/repo").as_deref(),
+            Some("bedrock")
+        );
+        assert_eq!(pick(None, "/work/repo").as_deref(), Some("gateway"));
+        assert_eq!(pick(None, "/home/me/side").as_deref(), Some("direct"));
+
+        assert!(select_profile(&settings, Some("missing"), "/work/repo").is_err());
+
+        settings.default_profile = None;
+        assert!(select_profile(&settings, None, "/home/me/side")
+            .unwrap()
+            .is_none());
+    }
+}
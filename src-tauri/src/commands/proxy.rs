@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
+use std::time::Duration;
 use crate::commands::claude::get_claude_dir;
+use crate::process::TerminationTimeouts;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ProxySettings {
@@ -17,6 +20,119 @@ pub struct ThemeSettings {
     pub custom_colors: Option<String>, // JSON string of custom colors
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SchedulerSettings {
+    /// Maximum number of scheduled jobs running at the same time
+    pub max_concurrent_jobs: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CancellationSettings {
+    /// Seconds a cancelled run gets to exit after SIGINT before SIGTERM
+    pub interrupt_timeout_secs: u64,
+    /// Seconds it gets after SIGTERM before SIGKILL
+    pub terminate_timeout_secs: u64,
+}
+
+/// What happens when a run crosses a resource threshold
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ThresholdAction {
+    Warn,
+    Cancel,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct MonitoringSettings {
+    /// Whether running processes are sampled at all
+    pub enabled: bool,
+    /// Seconds between samples
+    pub interval_secs: u64,
+    /// CPU use of the whole process tree, where 100 is one core
+    pub max_cpu_percent: Option<f64>,
+    pub max_rss_mb: Option<u64>,
+    pub max_open_fds: Option<u64>,
+    pub max_child_processes: Option<u32>,
+    /// Consecutive samples a threshold must be crossed in before the run is
+    /// reported, so a short spike doesn't cancel it
+    pub sustained_samples: u32,
+    pub on_threshold: ThresholdAction,
+}
+
+/// Run events that desktop notifications and webhooks can be sent for
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RunEvent {
+    Completed,
+    Failed,
+    Cancelled,
+    BudgetExceeded,
+    PermissionRequired,
+    /// Sent by `test_webhook`, never by a run
+    Test,
+}
+
+fn default_run_events() -> Vec<RunEvent> {
+    vec![
+        RunEvent::Completed,
+        RunEvent::Failed,
+        RunEvent::BudgetExceeded,
+        RunEvent::PermissionRequired,
+    ]
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NotificationSettings {
+    /// Whether desktop notifications are shown
+    pub enabled: bool,
+    #[serde(default = "default_run_events")]
+    pub events: Vec<RunEvent>,
+}
+
+/// A URL run events are POSTed to as JSON
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WebhookConfig {
+    pub name: String,
+    pub url: String,
+    /// Key of the HMAC-SHA256 signature sent with each request, generated when
+    /// the webhook is saved without one
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default = "default_run_events")]
+    pub events: Vec<RunEvent>,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiServerSettings {
+    /// Whether the local control API is served; off unless the user turns it on
+    pub enabled: bool,
+    /// Port on 127.0.0.1 to listen on
+    pub port: u16,
+    /// Bearer token clients must send, generated when the API is first enabled
+    pub token: Option<String>,
+}
+
+/// A named set of overrides applied to Claude runs, e.g. to switch between a
+/// corporate gateway and the direct API
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ExecutionProfile {
+    pub name: String,
+    /// Environment variables set for the run (e.g. ANTHROPIC_BASE_URL)
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Shell command whose output is used as ANTHROPIC_API_KEY
+    #[serde(default)]
+    pub api_key_helper: Option<String>,
+    /// Model used when a run doesn't specify one
+    #[serde(default)]
+    pub default_model: Option<String>,
+    /// Extra arguments passed to the claude CLI
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ClaudioSettings {
     #[serde(default)]
@@ -25,6 +141,26 @@ pub struct ClaudioSettings {
     pub claude_binary_path: Option<String>,
     #[serde(default)]
     pub theme: ThemeSettings,
+    #[serde(default)]
+    pub scheduler: SchedulerSettings,
+    #[serde(default)]
+    pub api_server: ApiServerSettings,
+    #[serde(default)]
+    pub cancellation: CancellationSettings,
+    #[serde(default)]
+    pub monitoring: MonitoringSettings,
+    #[serde(default)]
+    pub notifications: NotificationSettings,
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+    #[serde(default)]
+    pub profiles: Vec<ExecutionProfile>,
+    /// Profile used for runs that don't pick one and whose project has none
+    #[serde(default)]
+    pub default_profile: Option<String>,
+    /// Profile name per project path
+    #[serde(default)]
+    pub project_profiles: HashMap<String, String>,
     // Future Claudio-specific settings can be added here
     // pub analytics: AnalyticsSettings,
 }
@@ -50,12 +186,81 @@ impl Default for ThemeSettings {
     }
 }
 
+impl Default for SchedulerSettings {
+    fn default() -> Self {
+        Self {
+            max_concurrent_jobs: 2,
+        }
+    }
+}
+
+impl Default for CancellationSettings {
+    fn default() -> Self {
+        Self {
+            interrupt_timeout_secs: 5,
+            terminate_timeout_secs: 5,
+        }
+    }
+}
+
+impl Default for MonitoringSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 5,
+            max_cpu_percent: None,
+            max_rss_mb: None,
+            max_open_fds: None,
+            max_child_processes: None,
+            sustained_samples: 3,
+            on_threshold: ThresholdAction::Warn,
+        }
+    }
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            events: default_run_events(),
+        }
+    }
+}
+
+impl From<&CancellationSettings> for TerminationTimeouts {
+    fn from(settings: &CancellationSettings) -> Self {
+        Self {
+            interrupt: Duration::from_secs(settings.interrupt_timeout_secs),
+            terminate: Duration::from_secs(settings.terminate_timeout_secs),
+        }
+    }
+}
+
+impl Default for ApiServerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 7878,
+            token: None,
+        }
+    }
+}
+
 impl Default for ClaudioSettings {
     fn default() -> Self {
         Self {
             proxy: ProxySettings::default(),
             claude_binary_path: None,
             theme: ThemeSettings::default(),
+            scheduler: SchedulerSettings::default(),
+            api_server: ApiServerSettings::default(),
+            cancellation: CancellationSettings::default(),
+            monitoring: MonitoringSettings::default(),
+            notifications: NotificationSettings::default(),
+            webhooks: Vec::new(),
+            profiles: Vec::new(),
+            default_profile: None,
+            project_profiles: HashMap::new(),
         }
     }
 }
@@ -86,6 +291,14 @@ pub async fn get_claudio_settings() -> Result<ClaudioSettings, String> {
     Ok(settings)
 }
 
+/// Gets how long cancelled runs get to exit after each signal
+pub async fn get_termination_timeouts() -> TerminationTimeouts {
+    get_claudio_settings()
+        .await
+        .map(|settings| TerminationTimeouts::from(&settings.cancellation))
+        .unwrap_or_default()
+}
+
 /// Save proxy settings to the consolidated Claudio settings file
 #[tauri::command]
 pub async fn save_proxy_settings(settings: ProxySettings) -> Result<(), String> {
@@ -180,6 +393,12 @@ pub async fn get_setting(key: String) -> Result<Option<String>, String> {
     match key.as_str() {
         "theme_preference" => Ok(settings.theme.theme_mode),
         "theme_custom_colors" => Ok(settings.theme.custom_colors),
+        "cancel_interrupt_timeout_secs" => {
+            Ok(Some(settings.cancellation.interrupt_timeout_secs.to_string()))
+        }
+        "cancel_terminate_timeout_secs" => {
+            Ok(Some(settings.cancellation.terminate_timeout_secs.to_string()))
+        }
         _ => Ok(None)
     }
 }
@@ -196,6 +415,12 @@ pub async fn save_setting(key: String, value: String) -> Result<(), String> {
         "theme_custom_colors" => {
             settings.theme.custom_colors = Some(value);
         }
+        "cancel_interrupt_timeout_secs" => {
+            settings.cancellation.interrupt_timeout_secs = parse_timeout_secs(&value)?;
+        }
+        "cancel_terminate_timeout_secs" => {
+            settings.cancellation.terminate_timeout_secs = parse_timeout_secs(&value)?;
+        }
         _ => {
             return Err(format!("Unknown setting key: {}", key));
         }
@@ -205,6 +430,13 @@ pub async fn save_setting(key: String, value: String) -> Result<(), String> {
     Ok(())
 }
 
+fn parse_timeout_secs(value: &str) -> Result<u64, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid timeout in seconds: {}", value))
+}
+
 /// Apply proxy settings as environment variables
 pub fn apply_proxy_settings(settings: &ProxySettings) {
     log::info!("Applying proxy settings: enabled={}", settings.enabled);
@@ -0,0 +1,639 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::claude::{load_session_history_in, start_headless_run, RunOptions};
+use super::usage::{calculate_cost, UsageData};
+use super::worktrees::{create_worktree, diff_since, run_git, WorktreeFileChange};
+use crate::context::CoreContext;
+
+/// Tools whose input names a file they change
+const EDIT_TOOLS: &[&str] = &["Edit", "MultiEdit", "Write", "NotebookEdit"];
+
+/// How to replay a recorded session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayRequest {
+    pub session_id: String,
+    pub project_id: String,
+    /// Model to replay with; the profile's default model, or the CLI's, if unset
+    pub model: Option<String>,
+    /// Execution profile to replay with
+    pub profile: Option<String>,
+    /// "worktree" (a new worktree from HEAD) or "copy" (a copy of the project
+    /// directory, including uncommitted changes). Defaults to a worktree for
+    /// git repositories.
+    pub isolation: Option<String>,
+    /// Also replay with the session's original model, so both sides have diffs
+    /// made from the same starting point
+    #[serde(default)]
+    pub baseline: bool,
+}
+
+/// Cost and effort of a session, computed the same way for recorded and replayed sessions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionMetrics {
+    pub cost_usd: f64,
+    /// Assistant messages
+    pub turns: u32,
+    pub tool_calls: u32,
+    /// Time from each prompt to the last assistant message before the next
+    /// one, summed, so idle time between prompts isn't counted
+    pub duration_ms: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Models that answered, most used first
+    pub models: Vec<String>,
+}
+
+/// The recorded session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedSession {
+    pub session_id: String,
+    pub metrics: SessionMetrics,
+    /// Files changed by edit tools, relative to the project
+    pub files_touched: Vec<String>,
+}
+
+/// One replay of the recorded prompts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayRun {
+    /// "candidate" or "baseline"
+    pub label: String,
+    pub model: Option<String>,
+    pub profile: Option<String>,
+    /// Sessions of the replay; resuming may start a new session per prompt
+    pub session_ids: Vec<String>,
+    /// Directory the replay ran in; kept so its changes can be inspected. Empty
+    /// if the replay couldn't be set up.
+    pub scratch_path: String,
+    /// Session worktree record, when replayed in a worktree
+    pub worktree_id: Option<i64>,
+    pub prompts_replayed: usize,
+    /// Whether every prompt was replayed successfully
+    pub completed: bool,
+    pub error: Option<String>,
+    pub metrics: SessionMetrics,
+    /// Changes in the scratch directory at the end of the replay
+    pub files: Vec<WorktreeFileChange>,
+    pub patch: String,
+}
+
+impl ReplayRun {
+    /// A replay that couldn't be set up or inspected
+    fn failed(label: &str, model: Option<String>, profile: Option<String>, error: String) -> Self {
+        Self {
+            label: label.to_string(),
+            model,
+            profile,
+            session_ids: Vec::new(),
+            scratch_path: String::new(),
+            worktree_id: None,
+            prompts_replayed: 0,
+            completed: false,
+            error: Some(error),
+            metrics: SessionMetrics::default(),
+            files: Vec::new(),
+            patch: String::new(),
+        }
+    }
+}
+
+/// A recorded session side by side with its replays
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayComparison {
+    pub project_path: String,
+    /// The user prompts that were replayed, in order
+    pub prompts: Vec<String>,
+    pub original: RecordedSession,
+    pub runs: Vec<ReplayRun>,
+}
+
+/// Progress of a replay, sent as `session-replay-progress:{session_id}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ReplayProgress {
+    label: String,
+    /// Prompts replayed so far
+    replayed: usize,
+    total: usize,
+    session_id: Option<String>,
+}
+
+/// The text of a prompt typed by the user, if the entry is one
+fn user_prompt(entry: &Value) -> Option<String> {
+    if entry["type"] != "user"
+        || entry["isMeta"] == Value::Bool(true)
+        || entry["isSidechain"] == Value::Bool(true)
+    {
+        return None;
+    }
+    let text = match &entry["message"]["content"] {
+        Value::String(text) => text.clone(),
+        Value::Array(blocks) => {
+            if blocks.iter().any(|block| block["type"] == "tool_result") {
+                return None;
+            }
+            blocks
+                .iter()
+                .filter_map(|block| block["text"].as_str())
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        _ => return None,
+    };
+    let text = text.trim();
+    // Slash command wrappers, their output and interruption notices aren't prompts
+    let generated = [
+        "<command-",
+        "<local-command-",
+        "Caveat:",
+        "[Request interrupted",
+    ];
+    if text.is_empty() || generated.iter().any(|prefix| text.starts_with(prefix)) {
+        return None;
+    }
+    Some(text.to_string())
+}
+
+fn timestamp_ms(entry: &Value) -> Option<i64> {
+    entry["timestamp"]
+        .as_str()
+        .and_then(|ts| chrono::DateTime::parse_from_rfc3339(ts).ok())
+        .map(|ts| ts.timestamp_millis())
+}
+
+/// Computes a session's metrics from its transcript entries. Entries may come
+/// from several transcripts of the same conversation; repeated entries and
+/// messages are counted once.
+fn session_metrics(entries: &[Value]) -> SessionMetrics {
+    let mut seen_entries = HashSet::new();
+    let mut entries: Vec<&Value> = entries
+        .iter()
+        .filter(|entry| match entry["uuid"].as_str() {
+            Some(uuid) => seen_entries.insert(uuid.to_string()),
+            None => true,
+        })
+        .collect();
+    entries.sort_by_key(|entry| timestamp_ms(entry).unwrap_or(0));
+
+    let mut metrics = SessionMetrics::default();
+    let mut seen_messages = HashSet::new();
+    let mut model_counts: HashMap<String, u32> = HashMap::new();
+    // Start of the current prompt's segment and the last time seen in it
+    let mut segment: Option<(i64, i64)> = None;
+
+    for entry in entries {
+        if let Some(ts) = timestamp_ms(entry) {
+            if user_prompt(entry).is_some() {
+                if let Some((start, last)) = segment {
+                    metrics.duration_ms += (last - start).max(0) as u64;
+                }
+                segment = Some((ts, ts));
+            } else if entry["type"] == "assistant" {
+                if let Some((_, ref mut last)) = segment {
+                    *last = ts;
+                }
+            }
+        }
+
+        if entry["type"] != "assistant" {
+            continue;
+        }
+        let message = &entry["message"];
+        metrics.tool_calls += message["content"]
+            .as_array()
+            .map(|blocks| blocks.iter().filter(|b| b["type"] == "tool_use").count() as u32)
+            .unwrap_or(0);
+        // The CLI writes one entry per content block with the same message ID and usage
+        let id = message["id"].as_str().unwrap_or_default().to_string();
+        if !id.is_empty() && !seen_messages.insert(id) {
+            continue;
+        }
+        metrics.turns += 1;
+        let model = message["model"].as_str().unwrap_or_default();
+        if !model.is_empty() && model != "<synthetic>" {
+            *model_counts.entry(model.to_string()).or_default() += 1;
+        }
+        if let Ok(usage) = serde_json::from_value::<UsageData>(message["usage"].clone()) {
+            metrics.input_tokens += usage.input_tokens.unwrap_or(0);
+            metrics.output_tokens += usage.output_tokens.unwrap_or(0);
+            metrics.cost_usd += calculate_cost(model, &usage);
+        }
+    }
+    if let Some((start, last)) = segment {
+        metrics.duration_ms += (last - start).max(0) as u64;
+    }
+
+    let mut models: Vec<(String, u32)> = model_counts.into_iter().collect();
+    models.sort_by_key(|(model, count)| (std::cmp::Reverse(*count), model.clone()));
+    metrics.models = models.into_iter().map(|(model, _)| model).collect();
+    metrics
+}
+
+/// Files changed by edit tools in a transcript, relative to the project
+fn files_touched(entries: &[Value], project_path: &Path) -> Vec<String> {
+    let mut files: Vec<String> = Vec::new();
+    for entry in entries.iter().filter(|entry| entry["type"] == "assistant") {
+        for block in entry["message"]["content"].as_array().into_iter().flatten() {
+            let name = block["name"].as_str().unwrap_or_default();
+            if block["type"] != "tool_use" || !EDIT_TOOLS.contains(&name) {
+                continue;
+            }
+            let input = &block["input"];
+            let Some(path) = input["file_path"]
+                .as_str()
+                .or_else(|| input["notebook_path"].as_str())
+            else {
+                continue;
+            };
+            let path = Path::new(path);
+            let relative = path.strip_prefix(project_path).unwrap_or(path);
+            let relative = relative.to_string_lossy().to_string();
+            if !files.contains(&relative) {
+                files.push(relative);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+fn read_transcript(path: &Path) -> Vec<Value> {
+    let Ok(file) = fs::File::open(path) else {
+        return Vec::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+/// Copies a project, skipping .git and gitignored files
+fn copy_project(from: &Path, to: &Path) -> Result<(), String> {
+    let walker = ignore::WalkBuilder::new(from)
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+    for entry in walker.flatten() {
+        let Ok(relative) = entry.path().strip_prefix(from) else {
+            continue;
+        };
+        let target = to.join(relative);
+        match entry.file_type() {
+            Some(file_type) if file_type.is_dir() => fs::create_dir_all(&target)
+                .map_err(|e| format!("Failed to create {:?}: {}", target, e))?,
+            Some(file_type) if file_type.is_file() => {
+                fs::copy(entry.path(), &target)
+                    .map_err(|e| format!("Failed to copy {:?}: {}", entry.path(), e))?;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// A directory to replay in, with the commit its changes are diffed against
+struct Scratch {
+    path: PathBuf,
+    base_commit: String,
+    worktree_id: Option<i64>,
+}
+
+fn prepare_scratch(
+    app: &AppHandle,
+    project_path: &str,
+    isolation: &str,
+    name: &str,
+) -> Result<Scratch, String> {
+    match isolation {
+        "worktree" => {
+            let worktree =
+                create_worktree(app, project_path, Some(format!("claudio/replay-{}", name)))?;
+            Ok(Scratch {
+                path: PathBuf::from(&worktree.worktree_path),
+                base_commit: worktree.base_commit,
+                worktree_id: Some(worktree.id),
+            })
+        }
+        "copy" => {
+            let path = app
+                .path()
+                .app_data_dir()
+                .map_err(|e| format!("Failed to get app data dir: {}", e))?
+                .join("replays")
+                .join(name);
+            fs::create_dir_all(&path)
+                .map_err(|e| format!("Failed to create replay directory: {}", e))?;
+            copy_project(Path::new(project_path), &path)?;
+
+            // A fresh repository makes the replay's changes diffable
+            run_git(&path, &["init", "-q"])?;
+            run_git(&path, &["add", "--all"])?;
+            run_git(
+                &path,
+                &[
+                    "-c",
+                    "user.name=Claudio",
+                    "-c",
+                    "user.email=claudio@localhost",
+                    "commit",
+                    "-q",
+                    "--allow-empty",
+                    "-m",
+                    "Replay starting point",
+                ],
+            )?;
+            let base_commit = run_git(&path, &["rev-parse", "HEAD"])?.trim().to_string();
+            Ok(Scratch {
+                path,
+                base_commit,
+                worktree_id: None,
+            })
+        }
+        other => Err(format!("Unknown isolation: {}", other)),
+    }
+}
+
+/// What is replayed, shared by the replay runs
+struct ReplayPlan<'a> {
+    app: &'a AppHandle,
+    original_session_id: &'a str,
+    project_path: &'a str,
+    isolation: &'a str,
+    prompts: &'a [String],
+}
+
+/// Replays prompts in order, resuming the replay's session for each one
+async fn replay_prompts(
+    plan: &ReplayPlan<'_>,
+    label: &str,
+    model: Option<String>,
+    profile: Option<String>,
+) -> Result<ReplayRun, String> {
+    let ReplayPlan {
+        app,
+        original_session_id,
+        project_path,
+        isolation,
+        prompts,
+    } = *plan;
+    let name = format!(
+        "{}-{}-{}",
+        original_session_id.chars().take(8).collect::<String>(),
+        label,
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    );
+    let scratch = prepare_scratch(app, project_path, isolation, &name)?;
+    let scratch_path = scratch.path.to_string_lossy().to_string();
+    log::info!(
+        "Replaying {} as {} in {}",
+        original_session_id,
+        label,
+        scratch_path
+    );
+
+    let mut session_ids: Vec<String> = Vec::new();
+    let mut prompts_replayed = 0;
+    let mut error = None;
+    for (index, prompt) in prompts.iter().enumerate() {
+        let extra_args = match session_ids.last() {
+            Some(session_id) => vec!["--resume".to_string(), session_id.clone()],
+            None => Vec::new(),
+        };
+        let options = RunOptions {
+            background: true,
+            profile: profile.clone(),
+            ..Default::default()
+        };
+        let handle = match start_headless_run(
+            app.clone(),
+            scratch_path.clone(),
+            prompt.clone(),
+            model.clone().unwrap_or_default(),
+            extra_args,
+            options,
+        )
+        .await
+        {
+            Ok(handle) => handle,
+            Err(e) => {
+                error = Some(e);
+                break;
+            }
+        };
+        let Ok(outcome) = handle.outcome.await else {
+            error = Some("The replay run ended without an outcome".to_string());
+            break;
+        };
+
+        if let Some(session_id) = outcome.session_id {
+            if session_ids.last() != Some(&session_id) {
+                session_ids.push(session_id);
+            }
+        }
+        prompts_replayed = index + 1;
+        let _ = app.emit(
+            &format!("session-replay-progress:{}", original_session_id),
+            ReplayProgress {
+                label: label.to_string(),
+                replayed: prompts_replayed,
+                total: prompts.len(),
+                session_id: session_ids.last().cloned(),
+            },
+        );
+
+        if !outcome.success {
+            let detail = outcome
+                .result
+                .as_ref()
+                .and_then(|result| result["result"].as_str())
+                .map(|result| format!(": {}", result))
+                .unwrap_or_default();
+            error = Some(format!("Prompt {} failed{}", index + 1, detail));
+            break;
+        }
+    }
+
+    let context = app.state::<CoreContext>();
+    let entries: Vec<Value> = session_ids
+        .iter()
+        .filter_map(|session_id| context.session_transcript(session_id))
+        .flat_map(|path| read_transcript(&path))
+        .collect();
+    let (files, patch) = diff_since(&scratch.path, &scratch.base_commit)?;
+
+    Ok(ReplayRun {
+        label: label.to_string(),
+        model,
+        profile,
+        session_ids,
+        scratch_path,
+        worktree_id: scratch.worktree_id,
+        prompts_replayed,
+        completed: error.is_none(),
+        error,
+        metrics: session_metrics(&entries),
+        files,
+        patch,
+    })
+}
+
+/// Replays the user prompts of a recorded session, in order, into a new
+/// headless session with another model or profile, and compares the result
+/// with the recording.
+///
+/// Each replay runs in a scratch worktree or copy of the project, which is kept
+/// so its changes can be inspected, merged or discarded. With `baseline`, the
+/// prompts are also replayed with the original model so both sides start from
+/// the same files. Progress is emitted as `session-replay-progress:{session_id}`.
+#[tauri::command]
+pub async fn replay_session(
+    app: AppHandle,
+    request: ReplayRequest,
+) -> Result<ReplayComparison, String> {
+    log::info!(
+        "Replaying session {} with model {:?} and profile {:?}",
+        request.session_id,
+        request.model,
+        request.profile
+    );
+
+    if request.model.is_none() && request.profile.is_none() {
+        return Err("Choose a model or an execution profile to replay with".to_string());
+    }
+
+    let history = load_session_history_in(
+        &app.state::<CoreContext>(),
+        &request.session_id,
+        &request.project_id,
+    )?;
+    let prompts: Vec<String> = history.iter().filter_map(user_prompt).collect();
+    if prompts.is_empty() {
+        return Err("The session has no user prompts to replay".to_string());
+    }
+
+    let project_path = history
+        .iter()
+        .find_map(|entry| entry["cwd"].as_str())
+        .map(str::to_string)
+        .ok_or_else(|| "Could not determine the session's project directory".to_string())?;
+    if !Path::new(&project_path).is_dir() {
+        return Err(format!(
+            "Project directory does not exist: {}",
+            project_path
+        ));
+    }
+    let isolation = match request.isolation.as_deref() {
+        Some(isolation) => isolation.to_string(),
+        None if run_git(Path::new(&project_path), &["rev-parse", "HEAD"]).is_ok() => {
+            "worktree".to_string()
+        }
+        None => "copy".to_string(),
+    };
+
+    let original = RecordedSession {
+        session_id: request.session_id.clone(),
+        metrics: session_metrics(&history),
+        files_touched: files_touched(&history, Path::new(&project_path)),
+    };
+
+    let plan = ReplayPlan {
+        app: &app,
+        original_session_id: &request.session_id,
+        project_path: &project_path,
+        isolation: &isolation,
+        prompts: &prompts,
+    };
+    // A run that fails to set up is reported with its error, next to the other
+    let replay = |label: &'static str, model: Option<String>, profile: Option<String>| {
+        let plan = &plan;
+        async move {
+            replay_prompts(plan, label, model.clone(), profile.clone())
+                .await
+                .unwrap_or_else(|e| ReplayRun::failed(label, model, profile, e))
+        }
+    };
+    let candidate = replay("candidate", request.model.clone(), request.profile.clone());
+    let runs = if request.baseline {
+        let baseline = replay("baseline", original.metrics.models.first().cloned(), None);
+        let (candidate, baseline) = tokio::join!(candidate, baseline);
+        vec![candidate, baseline]
+    } else {
+        vec![candidate.await]
+    };
+
+    Ok(ReplayComparison {
+        project_path,
+        prompts,
+        original,
+        runs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn transcript() -> Vec<Value> {
+        vec![
+            json!({"type": "user", "uuid": "u1", "timestamp": "2025-01-01T10:00:00Z",
+                   "message": {"role": "user", "content": "Add a README"}}),
+            json!({"type": "assistant", "uuid": "a1", "timestamp": "2025-01-01T10:00:05Z",
+                   "message": {"id": "msg_1", "model": "claude-sonnet-4-20250514",
+                               "content": [{"type": "tool_use", "name": "Write",
+                                            "input": {"file_path": "/repo/README.md"}}],
+                               "usage": {"input_tokens": 100, "output_tokens": 20}}}),
+            json!({"type": "user", "uuid": "u2", "timestamp": "2025-01-01T10:00:06Z",
+                   "message": {"role": "user", "content": [
+                       {"type": "tool_result", "tool_use_id": "t1", "content": "ok"}]}}),
+            json!({"type": "assistant", "uuid": "a2", "timestamp": "2025-01-01T10:00:10Z",
+                   "message": {"id": "msg_2", "model": "claude-sonnet-4-20250514",
+                               "content": [{"type": "text", "text": "Done"}],
+                               "usage": {"input_tokens": 150, "output_tokens": 5}}}),
+            // Idle for an hour before the next prompt
+            json!({"type": "user", "uuid": "u3", "timestamp": "2025-01-01T11:00:10Z",
+                   "message": {"role": "user", "content": "<command-name>/clear</command-name>"}}),
+            json!({"type": "user", "uuid": "u4", "timestamp": "2025-01-01T11:00:10Z",
+                   "message": {"role": "user", "content": [{"type": "text", "text": "Now add tests"}]}}),
+            json!({"type": "assistant", "uuid": "a3", "timestamp": "2025-01-01T11:00:12Z",
+                   "message": {"id": "msg_3", "model": "claude-sonnet-4-20250514",
+                               "content": [{"type": "tool_use", "name": "Edit",
+                                            "input": {"file_path": "/repo/src/lib.rs"}}],
+                               "usage": {"input_tokens": 200, "output_tokens": 30}}}),
+        ]
+    }
+
+    #[test]
+    fn test_user_prompts() {
+        let prompts: Vec<String> = transcript().iter().filter_map(user_prompt).collect();
+        assert_eq!(prompts, vec!["Add a README", "Now add tests"]);
+    }
+
+    #[test]
+    fn test_session_metrics_and_files() {
+        let mut entries = transcript();
+        // A resumed session repeats the earlier entries
+        entries.extend(transcript().into_iter().take(2));
+
+        let metrics = session_metrics(&entries);
+        assert_eq!(metrics.turns, 3);
+        assert_eq!(metrics.tool_calls, 2);
+        assert_eq!(metrics.input_tokens, 450);
+        assert_eq!(metrics.output_tokens, 55);
+        // 10s for the first prompt, 2s for the second; the idle hour isn't counted
+        assert_eq!(metrics.duration_ms, 12_000);
+        assert_eq!(metrics.models, vec!["claude-sonnet-4-20250514"]);
+        assert!(metrics.cost_usd > 0.0);
+
+        assert_eq!(
+            files_touched(&entries, Path::new("/repo")),
+            vec!["README.md", "src/lib.rs"]
+        );
+    }
+}
@@ -0,0 +1,1315 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone, Timelike, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Notify;
+
+use super::agents::{get_agent_in, AgentDb};
+use super::budget::RunBudget;
+use super::claude::{start_headless_run, RunOptions, RunOutcome};
+use super::proxy::{get_claudio_settings, get_termination_timeouts};
+use super::worktrees::{bind_session, create_worktree};
+use crate::context::CoreContext;
+use crate::process::terminate_process;
+
+/// How often the scheduler checks for due jobs when nothing wakes it up
+const TICK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// A parsed five-field cron expression: minute, hour, day of month, month, day of week
+///
+/// Each field accepts `*`, single values, ranges (`1-5`), lists (`1,15`) and
+/// steps (`*/15`, `0-30/10`). The aliases `@hourly`, `@daily`, `@weekly`,
+/// `@monthly` and `@yearly` are also accepted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// Cron semantics: when both day fields are restricted, either may match
+    days_of_month_restricted: bool,
+    days_of_week_restricted: bool,
+}
+
+fn parse_cron_value(value: &str, min: u32, max: u32) -> Result<u32, String> {
+    let parsed: u32 = value
+        .parse()
+        .map_err(|_| format!("Invalid cron value '{}'", value))?;
+    if parsed < min || parsed > max {
+        return Err(format!(
+            "Cron value {} out of range {}-{}",
+            parsed, min, max
+        ));
+    }
+    Ok(parsed)
+}
+
+fn parse_cron_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut mask = 0u64;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| format!("Invalid cron step '{}'", step))?;
+                if step == 0 {
+                    return Err("Cron step cannot be zero".to_string());
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                parse_cron_value(start, min, max)?,
+                parse_cron_value(end, min, max)?,
+            )
+        } else {
+            let value = parse_cron_value(range, min, max)?;
+            // `5/15` means every 15 starting at 5
+            if step > 1 {
+                (value, max)
+            } else {
+                (value, value)
+            }
+        };
+
+        if start > end {
+            return Err(format!("Invalid cron range '{}'", range));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+
+    Ok(mask)
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expression = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "Expected 5 cron fields (minute hour day month weekday), got {}",
+                fields.len()
+            ));
+        }
+
+        let mut days_of_week = parse_cron_field(fields[4], 0, 7)?;
+        // Both 0 and 7 are Sunday
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week | 1) & !(1 << 7);
+        }
+
+        Ok(Self {
+            minutes: parse_cron_field(fields[0], 0, 59)?,
+            hours: parse_cron_field(fields[1], 0, 23)?,
+            days_of_month: parse_cron_field(fields[2], 1, 31)?,
+            months: parse_cron_field(fields[3], 1, 12)?,
+            days_of_week,
+            days_of_month_restricted: !fields[2].starts_with('*'),
+            days_of_week_restricted: !fields[4].starts_with('*'),
+        })
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        let dom = self.days_of_month & (1 << date.day()) != 0;
+        let dow = self.days_of_week & (1 << date.weekday().num_days_from_sunday()) != 0;
+        if self.days_of_month_restricted && self.days_of_week_restricted {
+            dom || dow
+        } else {
+            dom && dow
+        }
+    }
+
+    /// Returns the first matching minute strictly after `after`
+    ///
+    /// Times that don't exist in the time zone (DST gaps) are skipped.
+    /// Returns None if nothing matches within the next four years.
+    pub fn next_after<Tz: TimeZone>(&self, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let tz = after.timezone();
+        let mut time =
+            after.naive_local().with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = time + Duration::days(4 * 366);
+
+        while time <= limit {
+            if self.months & (1 << time.month()) == 0 || !self.matches_day(time.date()) {
+                time = (time.date() + Duration::days(1)).and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if self.hours & (1 << time.hour()) == 0 {
+                time = time.date().and_hms_opt(time.hour(), 0, 0)? + Duration::hours(1);
+                continue;
+            }
+            if self.minutes & (1 << time.minute()) != 0 {
+                if let Some(candidate) = tz.from_local_datetime(&time).earliest() {
+                    if candidate > *after {
+                        return Some(candidate);
+                    }
+                }
+            }
+            time += Duration::minutes(1);
+        }
+
+        None
+    }
+}
+
+/// Computes the next run time of a cron expression in local time, as RFC 3339 UTC
+fn next_scheduled_run(expression: &str) -> Result<Option<String>, String> {
+    let schedule = CronSchedule::parse(expression)?;
+    Ok(schedule
+        .next_after(&Local::now())
+        .map(|next| next.with_timezone(&Utc).to_rfc3339()))
+}
+
+/// A queued or scheduled headless Claude run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: i64,
+    pub name: String,
+    pub project_path: String,
+    /// "prompt" or "agent"
+    pub kind: String,
+    /// The prompt; for agent jobs this overrides the agent's default task
+    pub prompt: Option<String>,
+    /// Name of the agent in .claude/agents, for agent jobs
+    pub agent_name: Option<String>,
+    pub model: Option<String>,
+    /// Cron expression for recurring jobs
+    pub schedule: Option<String>,
+    /// Job that must complete successfully before this one is queued
+    pub after_job_id: Option<i64>,
+    /// Limits after which each run is stopped
+    pub budget: Option<RunBudget>,
+    /// Run each execution in a fresh git worktree on its own branch
+    pub use_worktree: bool,
+    pub enabled: bool,
+    /// waiting, queued, running, completed, failed or cancelled
+    pub status: String,
+    pub next_run_at: Option<String>,
+    pub last_run_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// A single execution of a job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRun {
+    pub id: i64,
+    pub job_id: i64,
+    /// Claude's session ID, once the run has started streaming
+    pub session_id: Option<String>,
+    /// running, completed, failed or cancelled
+    pub status: String,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub exit_code: Option<i32>,
+    /// The final result text reported by Claude
+    pub result: Option<String>,
+    pub cost_usd: Option<f64>,
+    pub error: Option<String>,
+    /// The git worktree the run was isolated in, if any
+    pub worktree_id: Option<i64>,
+}
+
+/// A job whose Claude process is currently running or being started
+struct RunningJob {
+    run_id: i64,
+    /// Missing until the process has started
+    pid: Option<u32>,
+    cancelled: bool,
+}
+
+/// Shared state of the background scheduler
+#[derive(Default)]
+pub struct SchedulerState {
+    wake: Arc<Notify>,
+    running: Arc<Mutex<HashMap<i64, RunningJob>>>,
+}
+
+const JOB_COLUMNS: &str = "SELECT id, name, project_path, kind, prompt, agent_name, model, schedule, after_job_id, enabled, status, next_run_at, last_run_at, created_at, updated_at, budget, use_worktree FROM jobs";
+
+const JOB_RUN_COLUMNS: &str = "SELECT id, job_id, session_id, status, started_at, finished_at, exit_code, result, cost_usd, error, worktree_id FROM job_runs";
+
+fn row_to_job(row: &rusqlite::Row) -> SqliteResult<Job> {
+    Ok(Job {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        project_path: row.get(2)?,
+        kind: row.get(3)?,
+        prompt: row.get(4)?,
+        agent_name: row.get(5)?,
+        model: row.get(6)?,
+        schedule: row.get(7)?,
+        after_job_id: row.get(8)?,
+        enabled: row.get::<_, i64>(9)? != 0,
+        status: row.get(10)?,
+        next_run_at: row.get(11)?,
+        last_run_at: row.get(12)?,
+        created_at: row.get(13)?,
+        updated_at: row.get(14)?,
+        budget: row
+            .get::<_, Option<String>>(15)?
+            .and_then(|budget| serde_json::from_str(&budget).ok()),
+        use_worktree: row.get::<_, i64>(16)? != 0,
+    })
+}
+
+fn row_to_job_run(row: &rusqlite::Row) -> SqliteResult<JobRun> {
+    Ok(JobRun {
+        id: row.get(0)?,
+        job_id: row.get(1)?,
+        session_id: row.get(2)?,
+        status: row.get(3)?,
+        started_at: row.get(4)?,
+        finished_at: row.get(5)?,
+        exit_code: row.get(6)?,
+        result: row.get(7)?,
+        cost_usd: row.get(8)?,
+        error: row.get(9)?,
+        worktree_id: row.get(10)?,
+    })
+}
+
+fn load_job(conn: &Connection, job_id: i64) -> Result<Job, String> {
+    conn.query_row(
+        &format!("{} WHERE id = ?1", JOB_COLUMNS),
+        params![job_id],
+        row_to_job,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Job {} not found", job_id))
+}
+
+fn query_jobs(conn: &Connection, filter: &str) -> Result<Vec<Job>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{} {}", JOB_COLUMNS, filter))
+        .map_err(|e| e.to_string())?;
+    let jobs = stmt
+        .query_map([], row_to_job)
+        .map_err(|e| e.to_string())?
+        .collect::<SqliteResult<Vec<_>>>()
+        .map_err(|e| e.to_string())?;
+    Ok(jobs)
+}
+
+fn set_job_status(conn: &Connection, job_id: i64, status: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE jobs SET status = ?1, updated_at = ?2 WHERE id = ?3",
+        params![status, Utc::now().to_rfc3339(), job_id],
+    )
+    .map_err(|e| format!("Failed to update job status: {}", e))?;
+    Ok(())
+}
+
+/// Marks a job running and records a new run for it
+///
+/// Checking and updating the status in one statement makes sure a job is only
+/// started once when ticks and `run_job_now` race. Returns `None` if the job
+/// isn't queued (or, without `queued_only`, is already running).
+fn claim_job(
+    conn: &Connection,
+    job_id: i64,
+    queued_only: bool,
+    now: &str,
+) -> Result<Option<i64>, String> {
+    let claimed = conn
+        .execute(
+            "UPDATE jobs SET status = 'running', last_run_at = ?1, updated_at = ?1
+             WHERE id = ?2
+               AND ((status = 'queued' AND enabled = 1) OR (?3 = 0 AND status != 'running'))",
+            params![now, job_id, queued_only as i64],
+        )
+        .map_err(|e| format!("Failed to update job: {}", e))?;
+    if claimed == 0 {
+        return Ok(None);
+    }
+
+    conn.execute(
+        "INSERT INTO job_runs (job_id, status, started_at) VALUES (?1, 'running', ?2)",
+        params![job_id, now],
+    )
+    .map_err(|e| format!("Failed to record job run: {}", e))?;
+    Ok(Some(conn.last_insert_rowid()))
+}
+
+/// Moves a recurring job past its pending occurrence, to wait for the one after
+fn skip_occurrence(
+    conn: &Connection,
+    job: &Job,
+    schedule: &str,
+    now: DateTime<Utc>,
+) -> Result<(), String> {
+    let schedule = CronSchedule::parse(schedule)?;
+    // A queued occurrence is already due, so the next one is the first after now
+    let pending = job
+        .next_run_at
+        .as_deref()
+        .filter(|_| job.status == "waiting")
+        .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+        .map_or(now, |at| at.with_timezone(&Utc).max(now));
+    let next_run_at = schedule
+        .next_after(&pending.with_timezone(&Local))
+        .map(|next| next.with_timezone(&Utc).to_rfc3339());
+
+    conn.execute(
+        "UPDATE jobs SET status = 'waiting', next_run_at = ?1, updated_at = ?2 WHERE id = ?3",
+        params![next_run_at, now.to_rfc3339(), job.id],
+    )
+    .map_err(|e| format!("Failed to update job: {}", e))?;
+    Ok(())
+}
+
+/// Queues waiting jobs whose scheduled time has passed
+fn promote_due_jobs(conn: &Connection, now: DateTime<Utc>) -> Result<Vec<i64>, String> {
+    let waiting = query_jobs(
+        conn,
+        "WHERE status = 'waiting' AND enabled = 1 AND next_run_at IS NOT NULL",
+    )?;
+
+    let mut promoted = Vec::new();
+    for job in waiting {
+        let due = job
+            .next_run_at
+            .as_deref()
+            .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+            .is_some_and(|at| at <= now);
+        if due {
+            set_job_status(conn, job.id, "queued")?;
+            promoted.push(job.id);
+        }
+    }
+    Ok(promoted)
+}
+
+/// Updates the jobs that run after `job_id` once it has finished
+///
+/// Dependents are queued when the job succeeded. When a one-off job failed
+/// or was cancelled, its waiting dependents (and theirs) are marked failed,
+/// since they would otherwise wait forever.
+fn settle_dependents(conn: &Connection, job_id: i64, succeeded: bool) -> Result<Vec<i64>, String> {
+    let mut changed = Vec::new();
+
+    if succeeded {
+        for job in query_jobs(
+            conn,
+            &format!(
+                "WHERE after_job_id = {} AND status = 'waiting' AND enabled = 1",
+                job_id
+            ),
+        )? {
+            set_job_status(conn, job.id, "queued")?;
+            changed.push(job.id);
+        }
+        return Ok(changed);
+    }
+
+    if load_job(conn, job_id)?.schedule.is_some() {
+        return Ok(changed);
+    }
+
+    let mut pending = vec![job_id];
+    while let Some(parent) = pending.pop() {
+        for job in query_jobs(
+            conn,
+            &format!(
+                "WHERE after_job_id = {} AND status = 'waiting' AND schedule IS NULL",
+                parent
+            ),
+        )? {
+            set_job_status(conn, job.id, "failed")?;
+            changed.push(job.id);
+            pending.push(job.id);
+        }
+    }
+    Ok(changed)
+}
+
+/// Emits `job-status-changed` for each job
+fn emit_jobs(app: &AppHandle, job_ids: &[i64]) {
+    let db = app.state::<AgentDb>();
+    let Ok(conn) = db.0.lock() else {
+        return;
+    };
+    for job_id in job_ids {
+        if let Ok(job) = load_job(&conn, *job_id) {
+            let _ = app.emit("job-status-changed", &job);
+        }
+    }
+}
+
+/// Resolves the prompt, model and extra CLI arguments for a job
+fn resolve_job_run(
+    context: &CoreContext,
+    job: &Job,
+) -> Result<(String, String, Vec<String>), String> {
+    if job.kind != "agent" {
+        let prompt = job
+            .prompt
+            .clone()
+            .ok_or_else(|| "Prompt job has no prompt".to_string())?;
+        // An empty model falls back to the execution profile's or the CLI's default
+        let model = job.model.clone().unwrap_or_default();
+        return Ok((prompt, model, Vec::new()));
+    }
+
+    let agent_name = job
+        .agent_name
+        .clone()
+        .ok_or_else(|| "Agent job has no agent name".to_string())?;
+    let agent = get_agent_in(context, Some(&job.project_path), &agent_name)?;
+
+    let prompt = job
+        .prompt
+        .clone()
+        .or(agent.default_task.clone())
+        .ok_or_else(|| {
+            format!(
+                "Agent '{}' has no default task; a prompt is required",
+                agent.name
+            )
+        })?;
+    let model = job.model.clone().unwrap_or_else(|| {
+        if agent.model == "inherit" {
+            String::new()
+        } else {
+            agent.model.clone()
+        }
+    });
+
+    let mut args = vec!["--append-system-prompt".to_string(), agent.system_prompt];
+    if let Some(tools) = agent.tools.filter(|t| !t.trim().is_empty()) {
+        args.push("--allowedTools".to_string());
+        args.push(tools);
+    }
+
+    Ok((prompt, model, args))
+}
+
+/// Returns the directory a run executes in, creating a worktree for it if the
+/// job asks for isolation
+fn prepare_run_dir(app: &AppHandle, job: &Job, run_id: i64) -> Result<String, String> {
+    if !job.use_worktree {
+        return Ok(job.project_path.clone());
+    }
+
+    let worktree = create_worktree(app, &job.project_path, None)?;
+    let db = app.state::<AgentDb>();
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE job_runs SET worktree_id = ?1 WHERE id = ?2",
+        params![worktree.id, run_id],
+    )
+    .map_err(|e| format!("Failed to record run worktree: {}", e))?;
+    Ok(worktree.worktree_path)
+}
+
+/// Claims a job, starts its Claude process and records a new run
+///
+/// Returns false if the job was started elsewhere first; see `claim_job`.
+async fn start_job(app: &AppHandle, job: Job, queued_only: bool) -> Result<bool, String> {
+    let now = Utc::now().to_rfc3339();
+    let run_id = {
+        let db = app.state::<AgentDb>();
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        match claim_job(&conn, job.id, queued_only, &now)? {
+            Some(run_id) => run_id,
+            None => return Ok(false),
+        }
+    };
+    // Tracked before the process starts, so a cancel in the meantime isn't lost
+    if let Ok(mut running) = app.state::<SchedulerState>().running.lock() {
+        running.insert(
+            job.id,
+            RunningJob {
+                run_id,
+                pid: None,
+                cancelled: false,
+            },
+        );
+    }
+    emit_jobs(app, &[job.id]);
+
+    log::info!("Starting job {} ({}) as run {}", job.id, job.name, run_id);
+
+    let handle = async {
+        let (prompt, model, args) = resolve_job_run(&app.state::<CoreContext>(), &job)?;
+        let run_path = prepare_run_dir(app, &job, run_id)?;
+        start_headless_run(
+            app.clone(),
+            run_path,
+            prompt,
+            model,
+            args,
+            RunOptions {
+                background: true,
+                budget: job.budget.clone().unwrap_or_default(),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+    .await;
+
+    let handle = match handle {
+        Ok(handle) => handle,
+        Err(e) => {
+            log::error!("Failed to start job {}: {}", job.id, e);
+            let cancelled = app
+                .state::<SchedulerState>()
+                .running
+                .lock()
+                .ok()
+                .and_then(|mut running| running.remove(&job.id))
+                .is_some_and(|running| running.cancelled);
+            let status = if cancelled { "cancelled" } else { "failed" };
+            finish_run(app, job.id, run_id, status, None, Some(e.clone()));
+            return Err(e);
+        }
+    };
+
+    let cancelled = app
+        .state::<SchedulerState>()
+        .running
+        .lock()
+        .ok()
+        .and_then(|mut running| {
+            running.get_mut(&job.id).map(|running| {
+                running.pid = Some(handle.pid);
+                running.cancelled
+            })
+        })
+        .unwrap_or(false);
+    if cancelled {
+        log::info!("Job {} was cancelled while it was starting", job.id);
+        if let Err(e) = stop_run(job.id, run_id, handle.pid).await {
+            log::error!("Failed to stop cancelled job {}: {}", job.id, e);
+        }
+    }
+
+    let app = app.clone();
+    tokio::spawn(async move {
+        let outcome = handle.outcome.await.ok();
+        let cancelled = app
+            .state::<SchedulerState>()
+            .running
+            .lock()
+            .ok()
+            .and_then(|mut running| running.remove(&job.id))
+            .is_some_and(|running| running.cancelled)
+            || outcome.as_ref().is_some_and(|outcome| outcome.cancelled);
+
+        let succeeded = outcome.as_ref().is_some_and(|outcome| {
+            outcome.success
+                && !outcome
+                    .result
+                    .as_ref()
+                    .and_then(|result| result["is_error"].as_bool())
+                    .unwrap_or(false)
+        });
+        let status = if cancelled {
+            "cancelled"
+        } else if succeeded {
+            "completed"
+        } else {
+            "failed"
+        };
+        let error = match (&outcome, status) {
+            (None, _) => Some("Claude process ended without reporting an outcome".to_string()),
+            (Some(outcome), "failed") if outcome.budget_exceeded.is_some() => outcome
+                .budget_exceeded
+                .as_ref()
+                .map(|exceeded| format!("Stopped: {}", exceeded.reason)),
+            (Some(outcome), "failed") => Some(match outcome.exit_code {
+                Some(code) => format!("Claude exited with code {}", code),
+                None => "Claude run reported an error".to_string(),
+            }),
+            _ => None,
+        };
+
+        finish_run(&app, job.id, run_id, status, outcome.as_ref(), error);
+    });
+
+    Ok(true)
+}
+
+/// Stops a run's Claude process along with the processes it started
+async fn stop_run(job_id: i64, run_id: i64, pid: u32) -> Result<(), String> {
+    log::info!("Stopping run {} (PID {}) of job {}", run_id, pid, job_id);
+    // The run's registry entry is removed once the process has exited
+    let timeouts = get_termination_timeouts().await;
+    let report = tokio::task::spawn_blocking(move || terminate_process(pid, &timeouts))
+        .await
+        .map_err(|e| format!("Failed to cancel job: {}", e))??;
+    log::info!(
+        "Run {} of job {} stopped at {:?}",
+        run_id,
+        job_id,
+        report.stage
+    );
+    Ok(())
+}
+
+/// Records the end of a run, reschedules or settles the job and its dependents,
+/// and wakes the scheduler so the next queued job can start
+fn finish_run(
+    app: &AppHandle,
+    job_id: i64,
+    run_id: i64,
+    status: &str,
+    outcome: Option<&RunOutcome>,
+    error: Option<String>,
+) {
+    log::info!("Job {} run {} finished: {}", job_id, run_id, status);
+
+    let result = outcome.and_then(|o| o.result.as_ref());
+    let result_text = result
+        .and_then(|r| r["result"].as_str())
+        .map(|s| s.to_string());
+    let cost_usd = result.and_then(|r| r["total_cost_usd"].as_f64());
+    let now = Utc::now().to_rfc3339();
+
+    let changed = {
+        let db = app.state::<AgentDb>();
+        let Ok(conn) = db.0.lock() else {
+            return;
+        };
+
+        let _ = conn.execute(
+            "UPDATE job_runs SET status = ?1, finished_at = ?2, exit_code = ?3, session_id = ?4,
+                result = ?5, cost_usd = ?6, error = ?7
+             WHERE id = ?8",
+            params![
+                status,
+                now,
+                outcome.and_then(|o| o.exit_code),
+                outcome.and_then(|o| o.session_id.clone()),
+                result_text,
+                cost_usd,
+                error,
+                run_id,
+            ],
+        );
+
+        let worktree_id: Option<i64> = conn
+            .query_row(
+                "SELECT worktree_id FROM job_runs WHERE id = ?1",
+                params![run_id],
+                |row| row.get(0),
+            )
+            .unwrap_or(None);
+        if let (Some(worktree_id), Some(session_id)) =
+            (worktree_id, outcome.and_then(|o| o.session_id.as_deref()))
+        {
+            if let Err(e) = bind_session(&conn, worktree_id, session_id) {
+                log::error!("Failed to bind session to worktree {}: {}", worktree_id, e);
+            }
+        }
+
+        let Ok(job) = load_job(&conn, job_id) else {
+            return;
+        };
+
+        // Recurring jobs go back to waiting for their next occurrence
+        let (job_status, next_run_at) = match job.schedule.as_deref() {
+            Some(schedule) if job.enabled => {
+                ("waiting", next_scheduled_run(schedule).ok().flatten())
+            }
+            _ => (status, None),
+        };
+        let _ = conn.execute(
+            "UPDATE jobs SET status = ?1, next_run_at = ?2, updated_at = ?3 WHERE id = ?4",
+            params![job_status, next_run_at, now, job_id],
+        );
+
+        let mut changed = vec![job_id];
+        match settle_dependents(&conn, job_id, status == "completed") {
+            Ok(dependents) => changed.extend(dependents),
+            Err(e) => log::error!("Failed to update dependents of job {}: {}", job_id, e),
+        }
+        changed
+    };
+
+    emit_jobs(app, &changed);
+    app.state::<SchedulerState>().wake.notify_one();
+}
+
+/// Promotes due jobs and starts queued ones up to the concurrency limit
+async fn tick(app: &AppHandle) -> Result<(), String> {
+    let max_concurrent = get_claudio_settings()
+        .await
+        .unwrap_or_default()
+        .scheduler
+        .max_concurrent_jobs
+        .max(1);
+
+    let (promoted, queued) = {
+        let db = app.state::<AgentDb>();
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        let promoted = promote_due_jobs(&conn, Utc::now())?;
+        let queued = query_jobs(&conn, "WHERE status = 'queued' AND enabled = 1 ORDER BY id")?;
+        (promoted, queued)
+    };
+    emit_jobs(app, &promoted);
+
+    let running = app
+        .state::<SchedulerState>()
+        .running
+        .lock()
+        .map_err(|e| e.to_string())?
+        .len();
+
+    for job in queued
+        .into_iter()
+        .take(max_concurrent.saturating_sub(running))
+    {
+        let job_id = job.id;
+        if let Err(e) = start_job(app, job, true).await {
+            log::warn!("Job {} could not be started: {}", job_id, e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Marks runs left over from a previous app session as failed
+fn recover_interrupted_jobs(conn: &Connection) -> Result<(), String> {
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "UPDATE job_runs SET status = 'failed', finished_at = ?1, error = 'Interrupted by application exit'
+         WHERE status = 'running'",
+        params![now],
+    )
+    .map_err(|e| e.to_string())?;
+
+    for job in query_jobs(conn, "WHERE status = 'running'")? {
+        match job.schedule.as_deref() {
+            Some(schedule) => {
+                conn.execute(
+                    "UPDATE jobs SET status = 'waiting', next_run_at = ?1, updated_at = ?2 WHERE id = ?3",
+                    params![next_scheduled_run(schedule)?, now, job.id],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+            None => set_job_status(conn, job.id, "failed")?,
+        }
+    }
+    Ok(())
+}
+
+/// Runs the scheduler loop for the lifetime of the app
+pub async fn run_scheduler(app: AppHandle) {
+    {
+        let db = app.state::<AgentDb>();
+        if let Ok(conn) = db.0.lock() {
+            if let Err(e) = recover_interrupted_jobs(&conn) {
+                log::error!("Failed to recover interrupted jobs: {}", e);
+            }
+        };
+    }
+
+    log::info!("Job scheduler started");
+    let wake = app.state::<SchedulerState>().wake.clone();
+    loop {
+        if let Err(e) = tick(&app).await {
+            log::error!("Scheduler tick failed: {}", e);
+        }
+        let _ = tokio::time::timeout(TICK_INTERVAL, wake.notified()).await;
+    }
+}
+
+/// Creates a job
+///
+/// Without `schedule`, `run_at` or `after_job_id` the job is queued right away.
+/// `schedule` makes the job recurring; `run_at` (RFC 3339) runs it once at that
+/// time; `after_job_id` queues it once the given job completes successfully.
+/// `budget` limits the cost, tokens, time and turns of each run, and
+/// `use_worktree` runs each execution in a fresh git worktree.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn create_job(
+    app: AppHandle,
+    name: String,
+    project_path: String,
+    kind: String,
+    prompt: Option<String>,
+    agent_name: Option<String>,
+    model: Option<String>,
+    schedule: Option<String>,
+    run_at: Option<String>,
+    after_job_id: Option<i64>,
+    budget: Option<RunBudget>,
+    use_worktree: Option<bool>,
+) -> Result<Job, String> {
+    log::info!("Creating {} job '{}' for {}", kind, name, project_path);
+
+    let prompt = prompt.filter(|p| !p.trim().is_empty());
+    let schedule = schedule.filter(|s| !s.trim().is_empty());
+    match kind.as_str() {
+        "prompt" if prompt.is_none() => return Err("A prompt job needs a prompt".to_string()),
+        "agent" if agent_name.is_none() => {
+            return Err("An agent job needs an agent name".to_string())
+        }
+        "prompt" | "agent" => {}
+        _ => return Err(format!("Unknown job kind: {}", kind)),
+    }
+    if [schedule.is_some(), run_at.is_some(), after_job_id.is_some()]
+        .iter()
+        .filter(|set| **set)
+        .count()
+        > 1
+    {
+        return Err("Only one of schedule, run_at and after_job_id can be set".to_string());
+    }
+
+    let next_run_at = match (&schedule, &run_at) {
+        (Some(schedule), _) => Some(
+            next_scheduled_run(schedule)?
+                .ok_or_else(|| format!("Schedule '{}' never matches", schedule))?,
+        ),
+        (_, Some(run_at)) => Some(
+            DateTime::parse_from_rfc3339(run_at)
+                .map_err(|e| format!("Invalid run_at time: {}", e))?
+                .with_timezone(&Utc)
+                .to_rfc3339(),
+        ),
+        _ => None,
+    };
+
+    let budget = budget
+        .filter(|budget| !budget.is_unlimited())
+        .map(|budget| serde_json::to_string(&budget))
+        .transpose()
+        .map_err(|e| format!("Failed to serialize budget: {}", e))?;
+
+    let job = {
+        let db = app.state::<AgentDb>();
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+
+        let status = match after_job_id {
+            Some(parent_id) if load_job(&conn, parent_id)?.status == "completed" => "queued",
+            Some(_) => "waiting",
+            None if next_run_at.is_some() => "waiting",
+            None => "queued",
+        };
+
+        let now = Utc::now().to_rfc3339();
+        conn.execute(
+            "INSERT INTO jobs (name, project_path, kind, prompt, agent_name, model, schedule,
+                after_job_id, budget, use_worktree, enabled, status, next_run_at, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, 1, ?11, ?12, ?13, ?13)",
+            params![
+                name,
+                project_path,
+                kind,
+                prompt,
+                agent_name,
+                model,
+                schedule,
+                after_job_id,
+                budget,
+                use_worktree.unwrap_or(false) as i64,
+                status,
+                next_run_at,
+                now,
+            ],
+        )
+        .map_err(|e| format!("Failed to create job: {}", e))?;
+
+        load_job(&conn, conn.last_insert_rowid())?
+    };
+
+    let _ = app.emit("job-status-changed", &job);
+    app.state::<SchedulerState>().wake.notify_one();
+    Ok(job)
+}
+
+/// Lists all jobs, newest first
+#[tauri::command]
+pub async fn list_jobs(db: tauri::State<'_, AgentDb>) -> Result<Vec<Job>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    query_jobs(&conn, "ORDER BY created_at DESC, id DESC")
+}
+
+/// Lists runs, newest first, optionally only those of one job
+#[tauri::command]
+pub async fn list_job_runs(
+    db: tauri::State<'_, AgentDb>,
+    job_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<Vec<JobRun>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE (?1 IS NULL OR job_id = ?1) ORDER BY id DESC LIMIT ?2",
+            JOB_RUN_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let runs = stmt
+        .query_map(params![job_id, limit.unwrap_or(100)], row_to_job_run)
+        .map_err(|e| e.to_string())?
+        .collect::<SqliteResult<Vec<_>>>()
+        .map_err(|e| e.to_string())?;
+    Ok(runs)
+}
+
+/// Deletes a job and its run history
+///
+/// Running jobs and jobs that other jobs are waiting on can't be deleted.
+#[tauri::command]
+pub async fn delete_job(db: tauri::State<'_, AgentDb>, job_id: i64) -> Result<(), String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+
+    if load_job(&conn, job_id)?.status == "running" {
+        return Err("Cancel the job before deleting it".to_string());
+    }
+    let dependents: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM jobs WHERE after_job_id = ?1",
+            params![job_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if dependents > 0 {
+        return Err(format!(
+            "Job {} has {} dependent job(s); delete them first",
+            job_id, dependents
+        ));
+    }
+
+    conn.execute("DELETE FROM job_runs WHERE job_id = ?1", params![job_id])
+        .map_err(|e| format!("Failed to delete job runs: {}", e))?;
+    conn.execute("DELETE FROM jobs WHERE id = ?1", params![job_id])
+        .map_err(|e| format!("Failed to delete job: {}", e))?;
+    Ok(())
+}
+
+/// Enables or disables a job
+///
+/// Disabled jobs are not started by the scheduler. Re-enabling a recurring job
+/// schedules its next occurrence.
+#[tauri::command]
+pub async fn set_job_enabled(app: AppHandle, job_id: i64, enabled: bool) -> Result<Job, String> {
+    let job = {
+        let db = app.state::<AgentDb>();
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        let job = load_job(&conn, job_id)?;
+        let now = Utc::now().to_rfc3339();
+
+        conn.execute(
+            "UPDATE jobs SET enabled = ?1, updated_at = ?2 WHERE id = ?3",
+            params![enabled as i64, now, job_id],
+        )
+        .map_err(|e| format!("Failed to update job: {}", e))?;
+
+        if let Some(schedule) = job.schedule.as_deref().filter(|_| enabled) {
+            if job.status != "running" {
+                conn.execute(
+                    "UPDATE jobs SET status = 'waiting', next_run_at = ?1 WHERE id = ?2",
+                    params![next_scheduled_run(schedule)?, job_id],
+                )
+                .map_err(|e| format!("Failed to update job: {}", e))?;
+            }
+        }
+
+        load_job(&conn, job_id)?
+    };
+
+    let _ = app.emit("job-status-changed", &job);
+    app.state::<SchedulerState>().wake.notify_one();
+    Ok(job)
+}
+
+/// Starts a job immediately, regardless of its schedule and the concurrency limit
+#[tauri::command]
+pub async fn run_job_now(app: AppHandle, job_id: i64) -> Result<Job, String> {
+    let job = {
+        let db = app.state::<AgentDb>();
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        load_job(&conn, job_id)?
+    };
+    if !start_job(&app, job, false).await? {
+        return Err(format!("Job {} is already running", job_id));
+    }
+
+    let db = app.state::<AgentDb>();
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_job(&conn, job_id)
+}
+
+/// Cancels a job
+///
+/// A running job's Claude process is stopped along with the processes it
+/// started; recurring jobs then wait for their next occurrence. A recurring job
+/// that isn't running skips its pending occurrence (disable it to stop it from
+/// recurring); any other job that hasn't started is marked cancelled.
+#[tauri::command]
+pub async fn cancel_job(app: AppHandle, job_id: i64) -> Result<Job, String> {
+    log::info!("Cancelling job {}", job_id);
+
+    let running = {
+        let state = app.state::<SchedulerState>();
+        let mut running = state.running.lock().map_err(|e| e.to_string())?;
+        running.get_mut(&job_id).map(|job| {
+            job.cancelled = true;
+            (job.run_id, job.pid)
+        })
+    };
+
+    match running {
+        Some((run_id, Some(pid))) => stop_run(job_id, run_id, pid).await?,
+        // start_job stops the process as soon as it has started
+        Some((run_id, None)) => {
+            log::info!(
+                "Run {} of job {} will be stopped once started",
+                run_id,
+                job_id
+            )
+        }
+        None => {
+            let db = app.state::<AgentDb>();
+            let conn = db.0.lock().map_err(|e| e.to_string())?;
+            let job = load_job(&conn, job_id)?;
+            match job.schedule.as_deref() {
+                Some(schedule) => skip_occurrence(&conn, &job, schedule, Utc::now())?,
+                None => {
+                    set_job_status(&conn, job_id, "cancelled")?;
+                    settle_dependents(&conn, job_id, false)?;
+                }
+            }
+        }
+    }
+
+    let job = {
+        let db = app.state::<AgentDb>();
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        load_job(&conn, job_id)?
+    };
+    let _ = app.emit("job-status-changed", &job);
+    Ok(job)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_cron_next_after() {
+        let every_15 = CronSchedule::parse("*/15 * * * *").unwrap();
+        assert_eq!(
+            every_15.next_after(&utc("2024-03-04T10:07:30Z")),
+            Some(utc("2024-03-04T10:15:00Z"))
+        );
+        // Strictly after: a matching minute is not returned again
+        assert_eq!(
+            every_15.next_after(&utc("2024-03-04T10:15:00Z")),
+            Some(utc("2024-03-04T10:30:00Z"))
+        );
+
+        // Mondays at 09:00; 2024-03-04 is a Monday
+        let mondays = CronSchedule::parse("0 9 * * 1").unwrap();
+        assert_eq!(
+            mondays.next_after(&utc("2024-03-04T09:00:00Z")),
+            Some(utc("2024-03-11T09:00:00Z"))
+        );
+
+        let weekly = CronSchedule::parse("@weekly").unwrap();
+        assert_eq!(
+            weekly.next_after(&utc("2024-03-04T12:00:00Z")),
+            Some(utc("2024-03-10T00:00:00Z"))
+        );
+
+        // When both day fields are restricted, either one matches
+        let either = CronSchedule::parse("30 2 15 * 5").unwrap();
+        assert_eq!(
+            either.next_after(&utc("2024-03-04T00:00:00Z")),
+            Some(utc("2024-03-08T02:30:00Z"))
+        );
+        assert_eq!(
+            either.next_after(&utc("2024-03-08T03:00:00Z")),
+            Some(utc("2024-03-15T02:30:00Z"))
+        );
+
+        let leap_day = CronSchedule::parse("0 0 29 2 *").unwrap();
+        assert_eq!(
+            leap_day.next_after(&utc("2024-03-01T00:00:00Z")),
+            Some(utc("2028-02-29T00:00:00Z"))
+        );
+    }
+
+    fn test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::commands::agents::init_schema(&conn).unwrap();
+        conn
+    }
+
+    fn insert_job(
+        conn: &Connection,
+        status: &str,
+        schedule: Option<&str>,
+        after_job_id: Option<i64>,
+    ) -> i64 {
+        conn.execute(
+            "INSERT INTO jobs (name, project_path, prompt, schedule, after_job_id, status, created_at, updated_at)
+             VALUES ('job', '/tmp/project', 'Do it', ?1, ?2, ?3, '', '')",
+            params![schedule, after_job_id, status],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn job_status(conn: &Connection, job_id: i64) -> String {
+        load_job(conn, job_id).unwrap().status
+    }
+
+    #[test]
+    fn test_claim_job() {
+        let conn = test_db();
+        let now = "2024-03-04T10:00:00+00:00";
+
+        let queued = insert_job(&conn, "queued", None, None);
+        assert!(claim_job(&conn, queued, true, now).unwrap().is_some());
+        assert_eq!(job_status(&conn, queued), "running");
+        // A running job is never claimed twice
+        assert!(claim_job(&conn, queued, true, now).unwrap().is_none());
+        assert!(claim_job(&conn, queued, false, now).unwrap().is_none());
+
+        // Only run_job_now starts jobs that aren't queued
+        let waiting = insert_job(&conn, "waiting", Some("@daily"), None);
+        assert!(claim_job(&conn, waiting, true, now).unwrap().is_none());
+        assert!(claim_job(&conn, waiting, false, now).unwrap().is_some());
+
+        let runs: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM job_runs WHERE status = 'running'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(runs, 2);
+    }
+
+    #[test]
+    fn test_settle_dependents() {
+        let conn = test_db();
+
+        let parent = insert_job(&conn, "completed", None, None);
+        let child = insert_job(&conn, "waiting", None, Some(parent));
+        let grandchild = insert_job(&conn, "waiting", None, Some(child));
+        assert_eq!(settle_dependents(&conn, parent, true).unwrap(), vec![child]);
+        assert_eq!(job_status(&conn, child), "queued");
+        assert_eq!(job_status(&conn, grandchild), "waiting");
+
+        // A failed one-off job fails everything waiting on it
+        let parent = insert_job(&conn, "failed", None, None);
+        let child = insert_job(&conn, "waiting", None, Some(parent));
+        let grandchild = insert_job(&conn, "waiting", None, Some(child));
+        assert_eq!(
+            settle_dependents(&conn, parent, false).unwrap(),
+            vec![child, grandchild]
+        );
+        assert_eq!(job_status(&conn, grandchild), "failed");
+
+        // A failed recurring job can still succeed next time
+        let parent = insert_job(&conn, "waiting", Some("@hourly"), None);
+        let child = insert_job(&conn, "waiting", None, Some(parent));
+        assert!(settle_dependents(&conn, parent, false).unwrap().is_empty());
+        assert_eq!(job_status(&conn, child), "waiting");
+    }
+
+    #[test]
+    fn test_recover_interrupted_jobs() {
+        let conn = test_db();
+        let now = "2024-03-04T10:00:00+00:00";
+
+        let one_off = insert_job(&conn, "queued", None, None);
+        let recurring = insert_job(&conn, "queued", Some("*/15 * * * *"), None);
+        let run_id = claim_job(&conn, one_off, true, now).unwrap().unwrap();
+        claim_job(&conn, recurring, true, now).unwrap();
+
+        recover_interrupted_jobs(&conn).unwrap();
+
+        assert_eq!(job_status(&conn, one_off), "failed");
+        let recurring = load_job(&conn, recurring).unwrap();
+        assert_eq!(recurring.status, "waiting");
+        assert!(recurring.next_run_at.is_some());
+        let run = conn
+            .query_row(
+                &format!("{} WHERE id = ?1", JOB_RUN_COLUMNS),
+                params![run_id],
+                row_to_job_run,
+            )
+            .unwrap();
+        assert_eq!(run.status, "failed");
+        assert!(run.finished_at.is_some());
+    }
+
+    #[test]
+    fn test_skip_occurrence() {
+        let conn = test_db();
+        let now = utc("2024-03-04T10:07:30Z");
+
+        let job_id = insert_job(&conn, "waiting", Some("*/15 * * * *"), None);
+        conn.execute(
+            "UPDATE jobs SET next_run_at = '2024-03-04T10:15:00+00:00' WHERE id = ?1",
+            params![job_id],
+        )
+        .unwrap();
+        let job = load_job(&conn, job_id).unwrap();
+        skip_occurrence(&conn, &job, "*/15 * * * *", now).unwrap();
+        let job = load_job(&conn, job_id).unwrap();
+        assert_eq!(job.status, "waiting");
+        assert_eq!(
+            job.next_run_at.as_deref().map(utc),
+            Some(utc("2024-03-04T10:30:00Z"))
+        );
+
+        // A queued occurrence is due already
+        set_job_status(&conn, job_id, "queued").unwrap();
+        let job = load_job(&conn, job_id).unwrap();
+        skip_occurrence(&conn, &job, "*/15 * * * *", now).unwrap();
+        assert_eq!(
+            load_job(&conn, job_id)
+                .unwrap()
+                .next_run_at
+                .as_deref()
+                .map(utc),
+            Some(utc("2024-03-04T10:15:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_cron_parse_errors() {
+        assert!(CronSchedule::parse("* * * *").is_err());
+        assert!(CronSchedule::parse("60 * * * *").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+        assert!(CronSchedule::parse("5-1 * * * *").is_err());
+        assert!(CronSchedule::parse("0 0 * * 7").is_ok());
+        assert_eq!(
+            CronSchedule::parse("0 0 * * 7").unwrap(),
+            CronSchedule::parse("0 0 * * 0").unwrap()
+        );
+    }
+}
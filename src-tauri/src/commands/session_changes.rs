@@ -0,0 +1,567 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use similar::{ChangeTag, TextDiff};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tauri::State;
+
+use crate::checkpoint::storage::CheckpointStorage;
+use crate::checkpoint::{Checkpoint, CheckpointPaths, FileSnapshot};
+use crate::context::CoreContext;
+
+/// Where the content a file's diff starts from was found
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeSource {
+    /// Replayed from the tool calls and the original contents in their results
+    Transcript,
+    /// Replayed on top of a checkpoint snapshot of the file
+    Checkpoint,
+    /// The file's content is unknown, so the diff only shows the edited fragments
+    Fragments,
+}
+
+/// A file a session wrote to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionFileChange {
+    /// Path relative to the project, or absolute for files outside it
+    pub path: String,
+    /// "added" or "modified"
+    pub status: String,
+    /// Number of successful Edit, Write and MultiEdit calls on the file
+    pub operations: usize,
+    /// Index of the transcript line with the first and last call
+    pub first_message: usize,
+    pub last_message: usize,
+    pub additions: usize,
+    pub deletions: usize,
+    pub source: ChangeSource,
+    /// Unified diff of the file
+    pub diff: String,
+}
+
+/// Everything a session changed, computed from its transcript
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionChanges {
+    pub session_id: String,
+    pub project_path: Option<String>,
+    pub files: Vec<SessionFileChange>,
+    /// The diffs of all files, in order
+    pub patch: String,
+}
+
+/// What is known about a file's content; `None` inside means it doesn't exist
+#[derive(Debug, Clone, PartialEq)]
+enum Content {
+    Known(Option<String>),
+    Unknown,
+}
+
+/// A file's state while replaying the transcript
+struct FileReplay {
+    current: Content,
+    /// State before the first change in the requested range
+    before: Option<Content>,
+    source: ChangeSource,
+    /// Edits that couldn't be applied because the content was unknown
+    fragments: Vec<(String, String)>,
+    operations: usize,
+    first_message: usize,
+    last_message: usize,
+}
+
+/// A file operation read from a tool call and its result
+#[derive(Debug)]
+struct FileOperation {
+    file_path: String,
+    /// Content written by Write
+    write: Option<String>,
+    /// (old, new, replace_all) of Edit and MultiEdit
+    edits: Vec<(String, String, bool)>,
+    /// The file's content before the call, from the tool result
+    original: Option<Content>,
+}
+
+fn edit_from(value: &Value) -> Option<(String, String, bool)> {
+    Some((
+        value.get("old_string")?.as_str()?.to_string(),
+        value.get("new_string")?.as_str()?.to_string(),
+        value
+            .get("replace_all")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+    ))
+}
+
+/// Reads a file operation from a tool call, using its result for the original content
+fn file_operation(name: &str, input: &Value, result: Option<&Value>) -> Option<FileOperation> {
+    let file_path = input.get("file_path")?.as_str()?.to_string();
+    let mut operation = FileOperation {
+        file_path,
+        write: None,
+        edits: Vec::new(),
+        original: None,
+    };
+
+    match name {
+        "Write" => operation.write = Some(input.get("content")?.as_str()?.to_string()),
+        "Edit" => operation.edits.push(edit_from(input)?),
+        "MultiEdit" => {
+            operation.edits = input
+                .get("edits")?
+                .as_array()?
+                .iter()
+                .filter_map(edit_from)
+                .collect()
+        }
+        _ => return None,
+    }
+
+    if let Some(result) = result {
+        let original = result
+            .get("originalFile")
+            .or_else(|| result.get("originalFileContents"));
+        operation.original = match original {
+            Some(Value::String(content)) => Some(Content::Known(Some(content.clone()))),
+            _ if result.get("type").and_then(|t| t.as_str()) == Some("create") => {
+                Some(Content::Known(None))
+            }
+            _ => None,
+        };
+    }
+    Some(operation)
+}
+
+/// Applies edits to known content; `None` if an old string isn't found
+fn apply_edits(content: &str, edits: &[(String, String, bool)]) -> Option<String> {
+    let mut content = content.to_string();
+    for (old, new, replace_all) in edits {
+        if old.is_empty() || !content.contains(old.as_str()) {
+            return None;
+        }
+        content = if *replace_all {
+            content.replace(old.as_str(), new)
+        } else {
+            content.replacen(old.as_str(), new, 1)
+        };
+    }
+    Some(content)
+}
+
+/// The session's checkpoints, loading file snapshots only when they're needed
+struct CheckpointSnapshots {
+    storage: CheckpointStorage,
+    project_id: String,
+    session_id: String,
+    /// Checkpoints ordered by message index
+    checkpoints: Vec<Checkpoint>,
+    loaded: HashMap<String, Vec<FileSnapshot>>,
+}
+
+impl CheckpointSnapshots {
+    fn new(claude_dir: &Path, project_id: &str, session_id: &str) -> Self {
+        let claude_dir = claude_dir.to_path_buf();
+        let paths = CheckpointPaths::new(&claude_dir, project_id, session_id);
+        let mut checkpoints: Vec<Checkpoint> = fs::read_dir(&paths.checkpoints_dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let id = entry.file_name().to_string_lossy().to_string();
+                let metadata = fs::read_to_string(paths.checkpoint_metadata_file(&id)).ok()?;
+                serde_json::from_str(&metadata).ok()
+            })
+            .collect();
+        checkpoints.sort_by_key(|checkpoint| checkpoint.message_index);
+
+        Self {
+            storage: CheckpointStorage::new(claude_dir),
+            project_id: project_id.to_string(),
+            session_id: session_id.to_string(),
+            checkpoints,
+            loaded: HashMap::new(),
+        }
+    }
+
+    /// The file's content in the latest checkpoint taken before a message
+    fn content_before(&mut self, relative_path: &Path, message_index: usize) -> Option<Content> {
+        let candidates: Vec<String> = self
+            .checkpoints
+            .iter()
+            .rev()
+            .filter(|checkpoint| checkpoint.message_index < message_index)
+            .map(|checkpoint| checkpoint.id.clone())
+            .collect();
+
+        for checkpoint_id in candidates {
+            if !self.loaded.contains_key(&checkpoint_id) {
+                let snapshots = self
+                    .storage
+                    .load_checkpoint(&self.project_id, &self.session_id, &checkpoint_id)
+                    .map(|(_, snapshots, _)| snapshots)
+                    .unwrap_or_default();
+                self.loaded.insert(checkpoint_id.clone(), snapshots);
+            }
+            let snapshot = self.loaded[&checkpoint_id]
+                .iter()
+                .find(|snapshot| snapshot.file_path == relative_path);
+            if let Some(snapshot) = snapshot {
+                return Some(Content::Known(
+                    (!snapshot.is_deleted).then(|| snapshot.content.clone()),
+                ));
+            }
+        }
+        None
+    }
+}
+
+/// Path of a file relative to the project, if it's inside it
+fn relative_to<'a>(file_path: &'a str, project_path: Option<&str>) -> &'a str {
+    project_path
+        .and_then(|project| Path::new(file_path).strip_prefix(project).ok())
+        .and_then(|relative| relative.to_str())
+        .unwrap_or(file_path)
+}
+
+/// Unified diff between two versions of a file, with its added and removed line counts
+fn unified_diff(path: &str, before: Option<&str>, after: Option<&str>) -> (String, usize, usize) {
+    let old = before.unwrap_or("");
+    let new = after.unwrap_or("");
+    let diff = TextDiff::from_lines(old, new);
+
+    let (mut additions, mut deletions) = (0, 0);
+    for change in diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Insert => additions += 1,
+            ChangeTag::Delete => deletions += 1,
+            ChangeTag::Equal => {}
+        }
+    }
+    if additions == 0 && deletions == 0 {
+        return (String::new(), 0, 0);
+    }
+
+    let old_header = match before {
+        Some(_) => format!("a/{}", path),
+        None => "/dev/null".to_string(),
+    };
+    let new_header = match after {
+        Some(_) => format!("b/{}", path),
+        None => "/dev/null".to_string(),
+    };
+    let patch = diff
+        .unified_diff()
+        .context_radius(3)
+        .header(&old_header, &new_header)
+        .to_string();
+    (patch, additions, deletions)
+}
+
+/// Replays the file operations of a transcript into per-file changes between
+/// two message indexes (inclusive)
+fn replay_changes(
+    lines: &[&str],
+    project_path: Option<&str>,
+    from_message: usize,
+    to_message: usize,
+    mut checkpoints: Option<&mut CheckpointSnapshots>,
+) -> Vec<SessionFileChange> {
+    // Tool calls by ID, waiting for their result
+    let mut pending: HashMap<String, (usize, String, Value)> = HashMap::new();
+    let mut files: HashMap<String, FileReplay> = HashMap::new();
+    let mut order: Vec<String> = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        let Ok(entry) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        let Some(content) = entry
+            .get("message")
+            .and_then(|m| m.get("content"))
+            .and_then(|c| c.as_array())
+        else {
+            continue;
+        };
+
+        for item in content {
+            match item.get("type").and_then(|t| t.as_str()) {
+                Some("tool_use") if index <= to_message => {
+                    let name = item.get("name").and_then(|n| n.as_str()).unwrap_or("");
+                    if let (Some(id), "Edit" | "Write" | "MultiEdit") =
+                        (item.get("id").and_then(|i| i.as_str()), name)
+                    {
+                        let input = item.get("input").cloned().unwrap_or(Value::Null);
+                        pending.insert(id.to_string(), (index, name.to_string(), input));
+                    }
+                }
+                Some("tool_result") => {
+                    let Some((call_index, name, input)) = item
+                        .get("tool_use_id")
+                        .and_then(|id| id.as_str())
+                        .and_then(|id| pending.remove(id))
+                    else {
+                        continue;
+                    };
+                    // Failed calls didn't change anything
+                    if item.get("is_error").and_then(|e| e.as_bool()) == Some(true) {
+                        continue;
+                    }
+                    let Some(operation) = file_operation(&name, &input, entry.get("toolUseResult"))
+                    else {
+                        continue;
+                    };
+
+                    let file = files.entry(operation.file_path.clone()).or_insert_with(|| {
+                        order.push(operation.file_path.clone());
+                        FileReplay {
+                            current: Content::Unknown,
+                            before: None,
+                            source: ChangeSource::Transcript,
+                            fragments: Vec::new(),
+                            operations: 0,
+                            first_message: call_index,
+                            last_message: call_index,
+                        }
+                    });
+
+                    if file.current == Content::Unknown {
+                        if let Some(original) = operation.original.clone() {
+                            file.current = original;
+                        } else if let Some(ref mut checkpoints) = checkpoints {
+                            let relative =
+                                Path::new(relative_to(&operation.file_path, project_path));
+                            if let Some(content) = checkpoints.content_before(relative, call_index)
+                            {
+                                file.current = content;
+                                file.source = ChangeSource::Checkpoint;
+                            }
+                        }
+                    }
+
+                    let in_range = call_index >= from_message;
+                    if in_range {
+                        if file.before.is_none() {
+                            file.before = Some(file.current.clone());
+                            file.first_message = call_index;
+                        }
+                        file.operations += 1;
+                        file.last_message = call_index;
+                    }
+
+                    file.current = match (&operation.write, &file.current) {
+                        (Some(written), _) => Content::Known(Some(written.clone())),
+                        (None, Content::Known(Some(text))) => {
+                            match apply_edits(text, &operation.edits) {
+                                Some(edited) => Content::Known(Some(edited)),
+                                None => Content::Unknown,
+                            }
+                        }
+                        (None, _) => Content::Unknown,
+                    };
+                    if file.current == Content::Unknown && in_range {
+                        file.fragments
+                            .extend(operation.edits.into_iter().map(|(old, new, _)| (old, new)));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    order
+        .into_iter()
+        .filter_map(|file_path| {
+            let file = files.remove(&file_path)?;
+            let before = file.before?;
+            let path = relative_to(&file_path, project_path).to_string();
+
+            let (diff, additions, deletions, source, status) = match (&before, &file.current) {
+                (Content::Known(before), Content::Known(after)) => {
+                    let (diff, additions, deletions) =
+                        unified_diff(&path, before.as_deref(), after.as_deref());
+                    let status = if before.is_none() {
+                        "added"
+                    } else {
+                        "modified"
+                    };
+                    (diff, additions, deletions, file.source, status)
+                }
+                _ => {
+                    let mut patch = String::new();
+                    let (mut additions, mut deletions) = (0, 0);
+                    for (old, new) in &file.fragments {
+                        // Snippets rarely end in a newline; don't report that as a change
+                        let (old, new) = (format!("{}\n", old), format!("{}\n", new));
+                        let (diff, added, removed) = unified_diff(&path, Some(&old), Some(&new));
+                        patch.push_str(&diff);
+                        additions += added;
+                        deletions += removed;
+                    }
+                    (
+                        patch,
+                        additions,
+                        deletions,
+                        ChangeSource::Fragments,
+                        "modified",
+                    )
+                }
+            };
+
+            Some(SessionFileChange {
+                path,
+                status: status.to_string(),
+                operations: file.operations,
+                first_message: file.first_message,
+                last_message: file.last_message,
+                additions,
+                deletions,
+                source,
+                diff,
+            })
+        })
+        .collect()
+}
+
+/// Computes the files a session changed and their diffs, optionally only
+/// between two message indexes (transcript lines, inclusive)
+pub fn get_session_changes_in(
+    context: &CoreContext,
+    session_id: &str,
+    from_message: Option<usize>,
+    to_message: Option<usize>,
+) -> Result<SessionChanges, String> {
+    let transcript = context
+        .session_transcript(session_id)
+        .ok_or_else(|| format!("Session not found: {}", session_id))?;
+    let content = fs::read_to_string(&transcript)
+        .map_err(|e| format!("Failed to read session transcript: {}", e))?;
+    let lines: Vec<&str> = content.lines().collect();
+
+    let project_path = lines.iter().find_map(|line| {
+        serde_json::from_str::<Value>(line)
+            .ok()?
+            .get("cwd")?
+            .as_str()
+            .map(str::to_string)
+    });
+    let project_id = transcript
+        .parent()
+        .and_then(|dir| dir.file_name())
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let mut checkpoints = CheckpointSnapshots::new(&context.claude_dir, project_id, session_id);
+
+    let files = replay_changes(
+        &lines,
+        project_path.as_deref(),
+        from_message.unwrap_or(0),
+        to_message.unwrap_or(usize::MAX),
+        Some(&mut checkpoints),
+    );
+    let patch = files.iter().map(|file| file.diff.as_str()).collect();
+
+    Ok(SessionChanges {
+        session_id: session_id.to_string(),
+        project_path,
+        files,
+        patch,
+    })
+}
+
+/// Gets the files a session changed with a unified diff per file, computed
+/// from its Edit, Write and MultiEdit calls without running git
+#[tauri::command]
+pub async fn get_session_changes(
+    context: State<'_, CoreContext>,
+    session_id: String,
+    from_message: Option<usize>,
+    to_message: Option<usize>,
+) -> Result<SessionChanges, String> {
+    log::info!("Computing changes of session: {}", session_id);
+    get_session_changes_in(&context, &session_id, from_message, to_message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool_use(id: &str, name: &str, input: Value) -> String {
+        json!({
+            "type": "assistant",
+            "message": {"role": "assistant", "content": [
+                {"type": "tool_use", "id": id, "name": name, "input": input}
+            ]}
+        })
+        .to_string()
+    }
+
+    fn tool_result(id: &str, is_error: bool, result: Value) -> String {
+        json!({
+            "type": "user",
+            "message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": id, "is_error": is_error, "content": "ok"}
+            ]},
+            "toolUseResult": result
+        })
+        .to_string()
+    }
+
+    fn transcript() -> Vec<String> {
+        vec![
+            json!({"type": "user", "cwd": "/work/app", "message": {"role": "user", "content": "go"}})
+                .to_string(),
+            tool_use("t1", "Write", json!({"file_path": "/work/app/new.txt", "content": "one\ntwo\n"})),
+            tool_result("t1", false, json!({"type": "create"})),
+            tool_use("t2", "Edit", json!({"file_path": "/work/app/new.txt", "old_string": "two", "new_string": "three"})),
+            tool_result("t2", false, json!({"originalFile": "one\ntwo\n"})),
+            tool_use("t3", "Edit", json!({"file_path": "/work/app/src/lib.rs", "old_string": "a", "new_string": "b"})),
+            tool_result("t3", false, json!({"originalFile": "a\nkeep\n"})),
+            // A failed edit changes nothing
+            tool_use("t4", "Edit", json!({"file_path": "/work/app/src/lib.rs", "old_string": "keep", "new_string": "gone"})),
+            tool_result("t4", true, json!({})),
+            tool_use("t5", "Edit", json!({"file_path": "/elsewhere/notes.md", "old_string": "x", "new_string": "y"})),
+            tool_result("t5", false, json!({})),
+        ]
+    }
+
+    #[test]
+    fn test_replay_whole_session() {
+        let lines = transcript();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let files = replay_changes(&lines, Some("/work/app"), 0, usize::MAX, None);
+
+        assert_eq!(files.len(), 3);
+        let added = &files[0];
+        assert_eq!(added.path, "new.txt");
+        assert_eq!(added.status, "added");
+        assert_eq!(added.operations, 2);
+        assert!(added.diff.starts_with("--- /dev/null\n+++ b/new.txt\n"));
+        assert!(added.diff.contains("+three\n"));
+        assert_eq!((added.additions, added.deletions), (2, 0));
+
+        let edited = &files[1];
+        assert_eq!(edited.path, "src/lib.rs");
+        assert_eq!(edited.operations, 1);
+        assert_eq!(edited.source, ChangeSource::Transcript);
+        assert!(edited.diff.contains("-a\n+b\n keep\n"));
+
+        let outside = &files[2];
+        assert_eq!(outside.path, "/elsewhere/notes.md");
+        assert_eq!(outside.source, ChangeSource::Fragments);
+        assert!(outside.diff.contains("-x\n+y\n"));
+    }
+
+    #[test]
+    fn test_replay_message_range() {
+        let lines = transcript();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let files = replay_changes(&lines, Some("/work/app"), 3, 4, None);
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].status, "modified");
+        assert_eq!(files[0].first_message, 3);
+        assert!(files[0].diff.contains("-two\n+three\n"));
+    }
+}
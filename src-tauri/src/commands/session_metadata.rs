@@ -0,0 +1,245 @@
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::State;
+
+use super::agents::AgentDb;
+use super::claude::{load_session, Session};
+use crate::context::CoreContext;
+
+/// User-provided metadata for a session, stored in Claudio's database
+/// rather than in Claude Code's JSONL files
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SessionMetadata {
+    /// The session ID (UUID)
+    pub session_id: String,
+    /// The project ID this session belongs to
+    pub project_id: String,
+    /// Custom title shown instead of the first message
+    pub title: Option<String>,
+    /// Free-form tags used to group sessions across projects
+    pub tags: Vec<String>,
+    /// Whether the session is pinned to the top of the list
+    pub pinned: bool,
+    /// Free-form notes
+    pub notes: Option<String>,
+    /// RFC 3339 timestamp of the last metadata change
+    pub updated_at: String,
+}
+
+/// Normalizes tags: trims whitespace, drops empty entries and duplicates
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_string();
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+fn row_to_metadata(row: &rusqlite::Row) -> SqliteResult<SessionMetadata> {
+    let tags: String = row.get(3)?;
+    Ok(SessionMetadata {
+        session_id: row.get(0)?,
+        project_id: row.get(1)?,
+        title: row.get(2)?,
+        tags: serde_json::from_str(&tags).unwrap_or_default(),
+        pinned: row.get::<_, i64>(4)? != 0,
+        notes: row.get(5)?,
+        updated_at: row.get(6)?,
+    })
+}
+
+const SELECT_COLUMNS: &str =
+    "SELECT session_id, project_id, title, tags, pinned, notes, updated_at FROM session_metadata";
+
+/// Loads metadata for all sessions of a project, keyed by session ID
+pub fn load_project_metadata(
+    conn: &Connection,
+    project_id: &str,
+) -> Result<HashMap<String, SessionMetadata>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{} WHERE project_id = ?1", SELECT_COLUMNS))
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![project_id], row_to_metadata)
+        .map_err(|e| e.to_string())?
+        .collect::<SqliteResult<Vec<_>>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows
+        .into_iter()
+        .map(|metadata| (metadata.session_id.clone(), metadata))
+        .collect())
+}
+
+/// Loads metadata for a single session
+pub fn load_session_metadata(
+    conn: &Connection,
+    session_id: &str,
+) -> Result<Option<SessionMetadata>, String> {
+    conn.query_row(
+        &format!("{} WHERE session_id = ?1", SELECT_COLUMNS),
+        params![session_id],
+        row_to_metadata,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Gets the metadata for a session
+#[tauri::command]
+pub async fn get_session_metadata(
+    db: State<'_, AgentDb>,
+    session_id: String,
+) -> Result<Option<SessionMetadata>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_session_metadata(&conn, &session_id)
+}
+
+/// Sets the title, tags, pinned flag and notes of a session
+///
+/// The metadata is replaced as a whole; pass the current values for fields
+/// that should stay unchanged.
+#[tauri::command]
+pub async fn set_session_metadata(
+    db: State<'_, AgentDb>,
+    session_id: String,
+    project_id: String,
+    title: Option<String>,
+    tags: Option<Vec<String>>,
+    pinned: Option<bool>,
+    notes: Option<String>,
+) -> Result<SessionMetadata, String> {
+    log::info!("Setting metadata for session: {}", session_id);
+
+    let metadata = SessionMetadata {
+        session_id,
+        project_id,
+        title: title.filter(|t| !t.trim().is_empty()),
+        tags: normalize_tags(tags.unwrap_or_default()),
+        pinned: pinned.unwrap_or(false),
+        notes: notes.filter(|n| !n.trim().is_empty()),
+        updated_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    let tags_json = serde_json::to_string(&metadata.tags)
+        .map_err(|e| format!("Failed to serialize tags: {}", e))?;
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO session_metadata (session_id, project_id, title, tags, pinned, notes, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT(session_id) DO UPDATE SET
+            project_id = excluded.project_id,
+            title = excluded.title,
+            tags = excluded.tags,
+            pinned = excluded.pinned,
+            notes = excluded.notes,
+            updated_at = excluded.updated_at",
+        params![
+            metadata.session_id,
+            metadata.project_id,
+            metadata.title,
+            tags_json,
+            metadata.pinned as i64,
+            metadata.notes,
+            metadata.updated_at,
+        ],
+    )
+    .map_err(|e| format!("Failed to save session metadata: {}", e))?;
+
+    Ok(metadata)
+}
+
+/// Removes all metadata for a session
+#[tauri::command]
+pub async fn delete_session_metadata(
+    db: State<'_, AgentDb>,
+    session_id: String,
+) -> Result<(), String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM session_metadata WHERE session_id = ?1",
+        params![session_id],
+    )
+    .map_err(|e| format!("Failed to delete session metadata: {}", e))?;
+    Ok(())
+}
+
+/// Lists all tags in use, sorted alphabetically
+#[tauri::command]
+pub async fn list_session_tags(db: State<'_, AgentDb>) -> Result<Vec<String>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare("SELECT tags FROM session_metadata")
+        .map_err(|e| e.to_string())?;
+
+    let mut tags: Vec<String> = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .filter_map(|tags| tags.ok())
+        .flat_map(|tags| serde_json::from_str::<Vec<String>>(&tags).unwrap_or_default())
+        .collect();
+
+    tags.sort();
+    tags.dedup();
+    Ok(tags)
+}
+
+/// Lists sessions carrying a tag across all projects
+///
+/// Sessions whose JSONL file no longer exists are skipped. Pinned sessions
+/// come first, then the newest.
+#[tauri::command]
+pub async fn list_sessions_by_tag(
+    db: State<'_, AgentDb>,
+    context: State<'_, CoreContext>,
+    tag: String,
+) -> Result<Vec<Session>, String> {
+    log::info!("Listing sessions tagged: {}", tag);
+
+    let tagged: Vec<SessionMetadata> = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn.prepare(SELECT_COLUMNS).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], row_to_metadata)
+            .map_err(|e| e.to_string())?
+            .collect::<SqliteResult<Vec<_>>>()
+            .map_err(|e| e.to_string())?;
+        rows.into_iter()
+            .filter(|metadata| metadata.tags.iter().any(|t| t == &tag))
+            .collect()
+    };
+
+    let mut sessions = Vec::new();
+
+    for metadata in tagged {
+        match load_session(
+            &context.claude_dir,
+            &metadata.project_id,
+            &metadata.session_id,
+        ) {
+            Ok(mut session) => {
+                session.metadata = Some(metadata);
+                sessions.push(session);
+            }
+            Err(e) => {
+                log::warn!("Skipping tagged session {}: {}", metadata.session_id, e);
+            }
+        }
+    }
+
+    sessions.sort_by(|a, b| {
+        let a_pinned = a.metadata.as_ref().is_some_and(|m| m.pinned);
+        let b_pinned = b.metadata.as_ref().is_some_and(|m| m.pinned);
+        b_pinned
+            .cmp(&a_pinned)
+            .then_with(|| b.created_at.cmp(&a.created_at))
+    });
+
+    Ok(sessions)
+}
@@ -0,0 +1,647 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::context::CoreContext;
+
+/// Lists that Claude Code combines across scopes instead of replacing
+const MERGED_LISTS: &[&str] = &[
+    "permissions.allow",
+    "permissions.deny",
+    "permissions.ask",
+    "permissions.additionalDirectories",
+    "enabledMcpjsonServers",
+    "disabledMcpjsonServers",
+    "allowedTools",
+];
+
+/// Objects that are replaced as a whole rather than merged key by key
+const ATOMIC_OBJECTS: &[&str] = &["statusLine"];
+
+/// Directory holding the managed (enterprise) policy files on this platform
+pub(crate) fn managed_settings_dir() -> PathBuf {
+    if cfg!(target_os = "macos") {
+        PathBuf::from("/Library/Application Support/ClaudeCode")
+    } else if cfg!(target_os = "windows") {
+        PathBuf::from(r"C:\ProgramData\ClaudeCode")
+    } else {
+        PathBuf::from("/etc/claude-code")
+    }
+}
+
+/// Path of the settings file of a scope ("user", "project" or "local")
+pub(crate) fn scope_settings_path(
+    context: &CoreContext,
+    scope: &str,
+    project_path: Option<&str>,
+) -> Result<PathBuf, String> {
+    match scope {
+        "user" => Ok(context.claude_dir.join("settings.json")),
+        "project" => {
+            let path = project_path.ok_or("Project path required for project scope")?;
+            Ok(PathBuf::from(path).join(".claude").join("settings.json"))
+        }
+        "local" => {
+            let path = project_path.ok_or("Project path required for local scope")?;
+            Ok(PathBuf::from(path)
+                .join(".claude")
+                .join("settings.local.json"))
+        }
+        _ => Err("Invalid scope".to_string()),
+    }
+}
+
+/// A settings file that takes part in the effective settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsSource {
+    /// "global_config", "user", "project", "local" or "managed", lowest precedence first
+    pub scope: String,
+    pub path: String,
+    pub exists: bool,
+    /// Why the file was skipped, if it could not be read or parsed
+    pub error: Option<String>,
+}
+
+/// The scope of one item of a list merged across scopes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingItemOrigin {
+    pub value: Value,
+    pub scope: String,
+}
+
+/// Where the effective value of a setting comes from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingOrigin {
+    /// Dotted key, e.g. "model", "env.HTTP_PROXY" or "permissions.allow"
+    pub key: String,
+    /// Scope of the effective value; for merged lists, the highest scope contributing
+    pub scope: String,
+    /// Lower-precedence scopes whose value for the key was replaced
+    pub overrides: Vec<String>,
+    /// For lists merged across scopes (permission rules, hooks), the scope of each item
+    pub items: Vec<SettingItemOrigin>,
+}
+
+/// Settings in effect for a project, with the origin of each key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectiveSettings {
+    pub project_path: Option<String>,
+    pub settings: Value,
+    pub sources: Vec<SettingsSource>,
+    /// Origins by dotted key, sorted by key
+    pub origins: Vec<SettingOrigin>,
+}
+
+fn is_merged_list(key: &str) -> bool {
+    MERGED_LISTS.contains(&key) || key.starts_with("hooks.")
+}
+
+/// Whether an object is merged key by key into the lower scopes' value
+fn is_merged_object(key: &str) -> bool {
+    // Each MCP server definition is replaced as a whole
+    !ATOMIC_OBJECTS.contains(&key) && !key.starts_with("mcpServers.")
+}
+
+/// Merges a scope's settings over the settings of lower scopes, recording origins
+fn merge_scope(
+    merged: &mut Map<String, Value>,
+    incoming: &Map<String, Value>,
+    prefix: &str,
+    scope: &str,
+    origins: &mut BTreeMap<String, SettingOrigin>,
+) {
+    for (name, value) in incoming {
+        let key = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+
+        match (merged.get_mut(name), value) {
+            (Some(Value::Object(existing)), Value::Object(object)) if is_merged_object(&key) => {
+                merge_scope(existing, object, &key, scope, origins);
+                continue;
+            }
+            (None, Value::Object(object)) if is_merged_object(&key) => {
+                let mut nested = Map::new();
+                merge_scope(&mut nested, object, &key, scope, origins);
+                merged.insert(name.clone(), Value::Object(nested));
+                continue;
+            }
+            (Some(Value::Array(existing)), Value::Array(items)) if is_merged_list(&key) => {
+                let origin = origins.entry(key.clone()).or_insert_with(|| SettingOrigin {
+                    key: key.clone(),
+                    scope: scope.to_string(),
+                    overrides: Vec::new(),
+                    items: Vec::new(),
+                });
+                for item in items {
+                    if !existing.contains(item) {
+                        existing.push(item.clone());
+                        origin.items.push(SettingItemOrigin {
+                            value: item.clone(),
+                            scope: scope.to_string(),
+                        });
+                    }
+                }
+                origin.scope = scope.to_string();
+                continue;
+            }
+            _ => {}
+        }
+
+        // Replace the value, remembering the scopes it overrides
+        let mut overrides = Vec::new();
+        if merged.contains_key(name) {
+            let nested_prefix = format!("{}.", key);
+            let replaced: Vec<String> = origins
+                .range(key.clone()..)
+                .take_while(|(k, _)| k.starts_with(key.as_str()))
+                .filter(|(k, _)| **k == key || k.starts_with(&nested_prefix))
+                .map(|(k, _)| k.clone())
+                .collect();
+            for replaced_key in replaced {
+                if let Some(origin) = origins.remove(&replaced_key) {
+                    for scope in origin.overrides.into_iter().chain([origin.scope]) {
+                        if !overrides.contains(&scope) {
+                            overrides.push(scope);
+                        }
+                    }
+                }
+            }
+        }
+
+        let items = match value {
+            Value::Array(items) if is_merged_list(&key) => items
+                .iter()
+                .map(|item| SettingItemOrigin {
+                    value: item.clone(),
+                    scope: scope.to_string(),
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        origins.insert(
+            key.clone(),
+            SettingOrigin {
+                key,
+                scope: scope.to_string(),
+                overrides,
+                items,
+            },
+        );
+        merged.insert(name.clone(), value.clone());
+    }
+}
+
+/// Merges scope settings given lowest precedence first
+fn merge_settings(scopes: &[(String, Value)]) -> (Value, Vec<SettingOrigin>) {
+    let mut merged = Map::new();
+    let mut origins = BTreeMap::new();
+    for (scope, value) in scopes {
+        if let Value::Object(object) = value {
+            merge_scope(&mut merged, object, "", scope, &mut origins);
+        }
+    }
+    (Value::Object(merged), origins.into_values().collect())
+}
+
+/// Reads a JSON settings file, recording why it was skipped if it couldn't be used
+fn read_source(scope: &str, path: &Path) -> (SettingsSource, Option<Value>) {
+    let mut source = SettingsSource {
+        scope: scope.to_string(),
+        path: path.to_string_lossy().to_string(),
+        exists: path.exists(),
+        error: None,
+    };
+    if !source.exists {
+        return (source, None);
+    }
+
+    let value = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read: {}", e))
+        .and_then(|content| {
+            serde_json::from_str::<Value>(&content).map_err(|e| format!("Invalid JSON: {}", e))
+        });
+    match value {
+        Ok(value) if value.is_object() => (source, Some(value)),
+        Ok(_) => {
+            source.error = Some("Not a JSON object".to_string());
+            (source, None)
+        }
+        Err(e) => {
+            source.error = Some(e);
+            (source, None)
+        }
+    }
+}
+
+/// Settings from ~/.claude.json that apply to a project: the user's MCP
+/// servers and the project's entry (allowed tools and MCP choices)
+fn global_config_settings(config: &Value, project_path: Option<&str>) -> Value {
+    let mut settings = Map::new();
+    if let Some(servers) = config.get("mcpServers") {
+        settings.insert("mcpServers".to_string(), servers.clone());
+    }
+
+    let project = project_path.and_then(|path| config["projects"].get(path));
+    if let Some(Value::Object(project)) = project {
+        for key in [
+            "allowedTools",
+            "enabledMcpjsonServers",
+            "disabledMcpjsonServers",
+            "enableAllProjectMcpServers",
+        ] {
+            if let Some(value) = project.get(key) {
+                settings.insert(key.to_string(), value.clone());
+            }
+        }
+        if let Some(Value::Object(servers)) = project.get("mcpServers") {
+            let all = settings
+                .entry("mcpServers")
+                .or_insert_with(|| Value::Object(Map::new()));
+            if let Value::Object(all) = all {
+                all.extend(servers.clone());
+            }
+        }
+    }
+    Value::Object(settings)
+}
+
+/// Computes the settings in effect for a project by merging, from lowest to
+/// highest precedence, ~/.claude.json, the user, project and local settings
+/// files, and the managed policy file
+///
+/// Permission rules and hooks from all scopes are combined; other keys are
+/// taken from the highest scope that sets them.
+#[tauri::command]
+pub async fn get_effective_settings(
+    context: tauri::State<'_, CoreContext>,
+    project_path: Option<String>,
+) -> Result<EffectiveSettings, String> {
+    log::info!("Computing effective settings for: {:?}", project_path);
+
+    let project_path = project_path.filter(|p| !p.trim().is_empty());
+    let mut files = Vec::new();
+    if let Some(home) = context.home_dir() {
+        files.push(("global_config", home.join(".claude.json")));
+    }
+    files.push(("user", scope_settings_path(&context, "user", None)?));
+    if let Some(ref project_path) = project_path {
+        files.push((
+            "project",
+            scope_settings_path(&context, "project", Some(project_path))?,
+        ));
+        files.push((
+            "local",
+            scope_settings_path(&context, "local", Some(project_path))?,
+        ));
+    }
+    files.push((
+        "managed",
+        managed_settings_dir().join("managed-settings.json"),
+    ));
+
+    let mut sources = Vec::new();
+    let mut scopes = Vec::new();
+    for (scope, path) in files {
+        let (source, value) = read_source(scope, &path);
+        if let Some(ref error) = source.error {
+            log::warn!("Skipping {} settings {:?}: {}", scope, path, error);
+        }
+        sources.push(source);
+
+        if let Some(value) = value {
+            let value = if scope == "global_config" {
+                global_config_settings(&value, project_path.as_deref())
+            } else {
+                value
+            };
+            scopes.push((scope.to_string(), value));
+        }
+    }
+
+    let (settings, origins) = merge_settings(&scopes);
+    Ok(EffectiveSettings {
+        project_path,
+        settings,
+        sources,
+        origins,
+    })
+}
+
+/// A problem found when validating settings against the bundled schema
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsIssue {
+    /// Location of the problem, e.g. "hooks.PreToolUse[0].matcher"
+    pub path: String,
+    pub message: String,
+}
+
+/// Result of validating settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SettingsValidation {
+    /// Whether there are no errors; warnings don't make settings invalid
+    pub valid: bool,
+    /// Problems that break Claude Code, such as wrong types or invalid regexes
+    pub errors: Vec<SettingsIssue>,
+    /// Likely mistakes that Claude Code tolerates, such as unknown keys
+    pub warnings: Vec<SettingsIssue>,
+}
+
+/// The bundled schema of Claude Code's settings.json
+fn settings_schema() -> &'static Value {
+    static SCHEMA: OnceLock<Value> = OnceLock::new();
+    SCHEMA.get_or_init(|| {
+        serde_json::from_str(include_str!("settings_schema.json"))
+            .expect("bundled settings schema is valid JSON")
+    })
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Checks the syntax of a permission rule: `Tool` or `Tool(specifier)`
+fn check_permission_rule(rule: &str) -> Result<(), String> {
+    let (tool, specifier) = match rule.find('(') {
+        Some(index) => {
+            if !rule.ends_with(')') {
+                return Err(format!("Rule '{}' is missing a closing parenthesis", rule));
+            }
+            (&rule[..index], Some(&rule[index + 1..rule.len() - 1]))
+        }
+        None => (rule, None),
+    };
+    if tool.is_empty()
+        || !tool
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(format!("Rule '{}' has an invalid tool name", rule));
+    }
+    if specifier.is_some_and(|s| s.trim().is_empty()) {
+        return Err(format!(
+            "Rule '{}' has an empty specifier; use '{}' to match every use",
+            rule, tool
+        ));
+    }
+    Ok(())
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// Validates a value against a schema node (a subset of JSON Schema)
+fn validate_node(value: &Value, schema: &Value, path: &str, report: &mut SettingsValidation) {
+    let schema = match schema["$ref"].as_str() {
+        Some(name) => &settings_schema()["definitions"][name],
+        None => schema,
+    };
+    let mut error = |message: String| {
+        report.errors.push(SettingsIssue {
+            path: path.to_string(),
+            message,
+        })
+    };
+
+    if let Some(expected) = schema["type"].as_str() {
+        let found = type_name(value);
+        let matches = found == expected || (expected == "number" && found == "integer");
+        if !matches {
+            error(format!("Expected {}, found {}", expected, found));
+            return;
+        }
+    }
+    if let Some(allowed) = schema["enum"].as_array() {
+        if !allowed.contains(value) {
+            let allowed: Vec<String> = allowed.iter().map(|v| v.to_string()).collect();
+            error(format!("Must be one of {}", allowed.join(", ")));
+        }
+    }
+    if let (Some(minimum), Some(number)) = (schema["minimum"].as_f64(), value.as_f64()) {
+        if number < minimum {
+            error(format!("Must be at least {}", minimum));
+        }
+    }
+    if let (Some(min_length), Some(text)) = (schema["minLength"].as_u64(), value.as_str()) {
+        if (text.len() as u64) < min_length {
+            error("Must not be empty".to_string());
+        }
+    }
+    if let Some(text) = value.as_str() {
+        match schema["format"].as_str() {
+            // "*" and "" match every tool; anything else is a regex
+            Some("matcher") if !text.is_empty() && text != "*" => {
+                if let Err(e) = regex::Regex::new(text) {
+                    error(format!("Invalid matcher regex: {}", e));
+                }
+            }
+            Some("permission-rule") => {
+                if let Err(e) = check_permission_rule(text) {
+                    error(e);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    match value {
+        Value::Object(object) => {
+            if let Some(required) = schema["required"].as_array() {
+                for key in required.iter().filter_map(|k| k.as_str()) {
+                    if !object.contains_key(key) {
+                        report.errors.push(SettingsIssue {
+                            path: path.to_string(),
+                            message: format!("Missing required key '{}'", key),
+                        });
+                    }
+                }
+            }
+            for (key, child) in object {
+                let child_path = join_path(path, key);
+                match (
+                    schema["properties"].get(key),
+                    &schema["additionalProperties"],
+                ) {
+                    (Some(child_schema), _) => {
+                        validate_node(child, child_schema, &child_path, report)
+                    }
+                    (None, Value::Bool(false)) => report.warnings.push(SettingsIssue {
+                        path: child_path,
+                        message: format!("Unknown key '{}'", key),
+                    }),
+                    (None, child_schema @ Value::Object(_)) => {
+                        validate_node(child, child_schema, &child_path, report)
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate_node(item, item_schema, &format!("{}[{}]", path, index), report);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Validates Claude Code settings against the bundled schema
+pub fn validate_settings(settings: &Value) -> SettingsValidation {
+    let mut report = SettingsValidation::default();
+    validate_node(settings, settings_schema(), "", &mut report);
+    report.valid = report.errors.is_empty();
+    report
+}
+
+/// Validates settings before they are written
+///
+/// Fails on errors unless `force` is set; warnings are only logged.
+pub(crate) fn check_settings_before_write(settings: &Value, force: bool) -> Result<(), String> {
+    let report = validate_settings(settings);
+    for warning in &report.warnings {
+        log::warn!("Settings warning at {}: {}", warning.path, warning.message);
+    }
+    if report.valid || force {
+        return Ok(());
+    }
+
+    let errors: Vec<String> = report
+        .errors
+        .iter()
+        .map(|issue| format!("{}: {}", issue.path, issue.message))
+        .collect();
+    Err(format!(
+        "Settings not saved, {} error(s) found (save with force to write anyway): {}",
+        errors.len(),
+        errors.join("; ")
+    ))
+}
+
+/// Validates settings against the bundled Claude Code settings schema
+#[tauri::command]
+pub async fn validate_claude_settings(settings: Value) -> Result<SettingsValidation, String> {
+    Ok(validate_settings(&settings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_merge_settings_provenance() {
+        let scopes = vec![
+            (
+                "user".to_string(),
+                json!({
+                    "model": "sonnet",
+                    "env": { "A": "1", "B": "1" },
+                    "permissions": { "allow": ["Bash(ls:*)"], "defaultMode": "default" },
+                    "statusLine": { "type": "command", "command": "status.sh" }
+                }),
+            ),
+            (
+                "project".to_string(),
+                json!({
+                    "model": "opus",
+                    "env": { "B": "2" },
+                    "permissions": { "allow": ["Bash(npm test:*)", "Bash(ls:*)"] },
+                    "hooks": { "PreToolUse": [{ "matcher": "Bash", "hooks": [] }] }
+                }),
+            ),
+            (
+                "local".to_string(),
+                json!({
+                    "model": "haiku",
+                    "statusLine": { "type": "command", "command": "local.sh" }
+                }),
+            ),
+        ];
+
+        let (settings, origins) = merge_settings(&scopes);
+        let origin = |key: &str| origins.iter().find(|o| o.key == key).unwrap();
+
+        assert_eq!(settings["model"], "haiku");
+        assert_eq!(origin("model").scope, "local");
+        assert_eq!(origin("model").overrides, vec!["user", "project"]);
+
+        assert_eq!(settings["env"], json!({ "A": "1", "B": "2" }));
+        assert_eq!(origin("env.A").scope, "user");
+        assert_eq!(origin("env.B").overrides, vec!["user"]);
+
+        assert_eq!(
+            settings["permissions"]["allow"],
+            json!(["Bash(ls:*)", "Bash(npm test:*)"])
+        );
+        let allow = origin("permissions.allow");
+        assert_eq!(allow.scope, "project");
+        assert_eq!(allow.items[0].scope, "user");
+        assert_eq!(allow.items[1].scope, "project");
+        assert_eq!(origin("permissions.defaultMode").scope, "user");
+
+        assert_eq!(settings["statusLine"]["command"], "local.sh");
+        assert_eq!(origin("statusLine").overrides, vec!["user"]);
+        assert_eq!(origin("hooks.PreToolUse").items.len(), 1);
+    }
+
+    #[test]
+    fn test_validate_settings() {
+        let report = validate_settings(&json!({
+            "model": "opus",
+            "permissions": {
+                "allow": ["Bash(npm test:*)", "Read", "mcp__github__get_issue"],
+                "deny": ["Bash(rm -rf", "Write()"],
+                "defaultMode": "yolo"
+            },
+            "hooks": {
+                "PreToolUse": [
+                    { "matcher": "Edit|Write", "hooks": [{ "type": "command", "command": "fmt.sh" }] },
+                    { "matcher": "Bash(", "hooks": [{ "type": "command", "command": "log.sh", "timeout": "5" }] }
+                ],
+                "OnSave": []
+            },
+            "env": { "DEBUG": 1 },
+            "colour": "blue"
+        }));
+
+        assert!(!report.valid);
+        let errors: Vec<&str> = report.errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            errors,
+            vec![
+                "env.DEBUG",
+                "hooks.PreToolUse[1].hooks[0].timeout",
+                "hooks.PreToolUse[1].matcher",
+                "permissions.defaultMode",
+                "permissions.deny[0]",
+                "permissions.deny[1]",
+            ]
+        );
+        let warnings: Vec<&str> = report.warnings.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(warnings, vec!["colour", "hooks.OnSave"]);
+
+        assert!(check_settings_before_write(&json!({ "model": 3 }), false).is_err());
+        assert!(check_settings_before_write(&json!({ "model": 3 }), true).is_ok());
+        assert!(validate_settings(&json!({})).valid);
+    }
+}
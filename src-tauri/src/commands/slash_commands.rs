@@ -1,9 +1,11 @@
 use anyhow::{Context, Result};
-use dirs;
 use log::{debug, error, info};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
+use tauri::State;
+
+use crate::context::CoreContext;
 
 /// Represents a custom slash command
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -248,7 +250,16 @@ fn create_default_commands() -> Vec<SlashCommand> {
 /// Discover all custom slash commands
 #[tauri::command]
 pub async fn slash_commands_list(
+    context: State<'_, CoreContext>,
     project_path: Option<String>,
+) -> Result<Vec<SlashCommand>, String> {
+    slash_commands_list_in(&context, project_path.as_deref())
+}
+
+/// Lists the default, project and user slash commands
+pub fn slash_commands_list_in(
+    context: &CoreContext,
+    project_path: Option<&str>,
 ) -> Result<Vec<SlashCommand>, String> {
     info!("Discovering slash commands");
     let mut commands = Vec::new();
@@ -258,7 +269,7 @@ pub async fn slash_commands_list(
     
     // Load project commands if project path is provided
     if let Some(proj_path) = project_path {
-        let project_commands_dir = PathBuf::from(&proj_path).join(".claude").join("commands");
+        let project_commands_dir = PathBuf::from(proj_path).join(".claude").join("commands");
         if project_commands_dir.exists() {
             debug!("Scanning project commands at: {:?}", project_commands_dir);
             
@@ -282,24 +293,22 @@ pub async fn slash_commands_list(
     }
     
     // Load user commands
-    if let Some(home_dir) = dirs::home_dir() {
-        let user_commands_dir = home_dir.join(".claude").join("commands");
-        if user_commands_dir.exists() {
-            debug!("Scanning user commands at: {:?}", user_commands_dir);
-            
-            let mut md_files = Vec::new();
-            if let Err(e) = find_markdown_files(&user_commands_dir, &mut md_files) {
-                error!("Failed to find user command files: {}", e);
-            } else {
-                for file_path in md_files {
-                    match load_command_from_file(&file_path, &user_commands_dir, "user") {
-                        Ok(cmd) => {
-                            debug!("Loaded user command: {}", cmd.full_command);
-                            commands.push(cmd);
-                        }
-                        Err(e) => {
-                            error!("Failed to load command from {:?}: {}", file_path, e);
-                        }
+    let user_commands_dir = context.commands_dir();
+    if user_commands_dir.exists() {
+        debug!("Scanning user commands at: {:?}", user_commands_dir);
+        
+        let mut md_files = Vec::new();
+        if let Err(e) = find_markdown_files(&user_commands_dir, &mut md_files) {
+            error!("Failed to find user command files: {}", e);
+        } else {
+            for file_path in md_files {
+                match load_command_from_file(&file_path, &user_commands_dir, "user") {
+                    Ok(cmd) => {
+                        debug!("Loaded user command: {}", cmd.full_command);
+                        commands.push(cmd);
+                    }
+                    Err(e) => {
+                        error!("Failed to load command from {:?}: {}", file_path, e);
                     }
                 }
             }
@@ -312,7 +321,15 @@ pub async fn slash_commands_list(
 
 /// Get a single slash command by ID
 #[tauri::command]
-pub async fn slash_command_get(command_id: String) -> Result<SlashCommand, String> {
+pub async fn slash_command_get(
+    context: State<'_, CoreContext>,
+    command_id: String,
+) -> Result<SlashCommand, String> {
+    slash_command_get_in(&context, &command_id)
+}
+
+/// Finds a default or user slash command by ID
+pub fn slash_command_get_in(context: &CoreContext, command_id: &str) -> Result<SlashCommand, String> {
     debug!("Getting slash command: {}", command_id);
     
     // Parse the ID to determine scope and reconstruct file path
@@ -323,7 +340,7 @@ pub async fn slash_command_get(command_id: String) -> Result<SlashCommand, Strin
     
     // The actual implementation would need to reconstruct the path and reload the command
     // For now, we'll list all commands and find the matching one
-    let commands = slash_commands_list(None).await?;
+    let commands = slash_commands_list_in(context, None)?;
     
     commands
         .into_iter()
@@ -333,7 +350,33 @@ pub async fn slash_command_get(command_id: String) -> Result<SlashCommand, Strin
 
 /// Create or update a slash command
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn slash_command_save(
+    context: State<'_, CoreContext>,
+    scope: String,
+    name: String,
+    namespace: Option<String>,
+    content: String,
+    description: Option<String>,
+    allowed_tools: Vec<String>,
+    project_path: Option<String>,
+) -> Result<SlashCommand, String> {
+    slash_command_save_in(
+        &context,
+        scope,
+        name,
+        namespace,
+        content,
+        description,
+        allowed_tools,
+        project_path,
+    )
+}
+
+/// Writes a project or user slash command file
+#[allow(clippy::too_many_arguments)]
+pub fn slash_command_save_in(
+    context: &CoreContext,
     scope: String,
     name: String,
     namespace: Option<String>,
@@ -361,10 +404,7 @@ pub async fn slash_command_save(
             return Err("Project path required for project scope".to_string());
         }
     } else {
-        dirs::home_dir()
-            .ok_or_else(|| "Could not find home directory".to_string())?
-            .join(".claude")
-            .join("commands")
+        context.commands_dir()
     };
     
     // Build file path
@@ -416,7 +456,20 @@ pub async fn slash_command_save(
 
 /// Delete a slash command
 #[tauri::command]
-pub async fn slash_command_delete(command_id: String, project_path: Option<String>) -> Result<String, String> {
+pub async fn slash_command_delete(
+    context: State<'_, CoreContext>,
+    command_id: String,
+    project_path: Option<String>,
+) -> Result<String, String> {
+    slash_command_delete_in(&context, &command_id, project_path.as_deref())
+}
+
+/// Deletes a slash command file, removing namespace directories left empty
+pub fn slash_command_delete_in(
+    context: &CoreContext,
+    command_id: &str,
+    project_path: Option<&str>,
+) -> Result<String, String> {
     info!("Deleting slash command: {}", command_id);
     
     // First, we need to determine if this is a project command by parsing the ID
@@ -428,7 +481,7 @@ pub async fn slash_command_delete(command_id: String, project_path: Option<Strin
     }
     
     // List all commands (including project commands if applicable)
-    let commands = slash_commands_list(project_path).await?;
+    let commands = slash_commands_list_in(context, project_path)?;
     
     // Find the command by ID
     let command = commands
@@ -449,6 +449,14 @@ pub async fn storage_reset_database(app: AppHandle) -> Result<(), String> {
             .map_err(|e| format!("Failed to drop agents table: {}", e))?;
         conn.execute("DROP TABLE IF EXISTS app_settings", [])
             .map_err(|e| format!("Failed to drop app_settings table: {}", e))?;
+        conn.execute("DROP TABLE IF EXISTS session_metadata", [])
+            .map_err(|e| format!("Failed to drop session_metadata table: {}", e))?;
+        conn.execute("DROP TABLE IF EXISTS job_runs", [])
+            .map_err(|e| format!("Failed to drop job_runs table: {}", e))?;
+        conn.execute("DROP TABLE IF EXISTS jobs", [])
+            .map_err(|e| format!("Failed to drop jobs table: {}", e))?;
+        conn.execute("DROP TABLE IF EXISTS session_worktrees", [])
+            .map_err(|e| format!("Failed to drop session_worktrees table: {}", e))?;
         
         // Re-enable foreign key constraints
         conn.execute("PRAGMA foreign_keys = ON", [])
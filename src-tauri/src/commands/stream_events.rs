@@ -0,0 +1,445 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::api_server::ApiServerState;
+
+/// Version of the event schema; bumped on incompatible changes to `StreamEvent`
+pub const STREAM_EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// Token counts of an assistant message or a whole run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    #[serde(default)]
+    pub cache_creation_input_tokens: u64,
+    #[serde(default)]
+    pub cache_read_input_tokens: u64,
+}
+
+/// An MCP server as reported by the init message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct McpServerStatus {
+    pub name: String,
+    pub status: String,
+}
+
+/// A typed event parsed from Claude's stream-json output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamEvent {
+    /// The session started
+    Init {
+        model: Option<String>,
+        cwd: Option<String>,
+        tools: Vec<String>,
+        mcp_servers: Vec<McpServerStatus>,
+        permission_mode: Option<String>,
+        claude_code_version: Option<String>,
+    },
+    /// A piece of a message being streamed (only with `--include-partial-messages`)
+    AssistantDelta {
+        message_id: Option<String>,
+        /// Index of the content block the delta belongs to
+        index: u64,
+        /// "text", "thinking" or "tool_input"
+        kind: String,
+        text: String,
+    },
+    /// An assistant message; tool calls in it follow as `ToolUse` events. The
+    /// CLI may send one message per content block with the same message ID.
+    AssistantMessage {
+        message_id: Option<String>,
+        model: Option<String>,
+        text: String,
+        thinking: Option<String>,
+        stop_reason: Option<String>,
+        usage: Option<TokenUsage>,
+        /// Set for messages of a subagent, to the Task call that started it
+        parent_tool_use_id: Option<String>,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: Value,
+        parent_tool_use_id: Option<String>,
+    },
+    ToolResult {
+        tool_use_id: String,
+        /// Text of the result; images are shown as "[image]"
+        content: String,
+        is_error: bool,
+        parent_tool_use_id: Option<String>,
+    },
+    /// The run finished
+    Result {
+        /// "success", "error_max_turns", "error_during_execution", ...
+        subtype: String,
+        is_error: bool,
+        result: Option<String>,
+        duration_ms: Option<u64>,
+        duration_api_ms: Option<u64>,
+        num_turns: Option<u32>,
+        total_cost_usd: Option<f64>,
+        usage: Option<TokenUsage>,
+    },
+    /// Something went wrong: a failed result, a line that isn't JSON, or stderr output
+    Error {
+        /// "result", "stdout" or "stderr"
+        source: String,
+        message: String,
+    },
+    /// A system message other than init, e.g. "compact_boundary"
+    System { subtype: String },
+    /// A message type this schema version doesn't know
+    Unknown { message_type: String },
+}
+
+/// An event as emitted on `claude-event` and `claude-event:{session_id}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamEnvelope {
+    pub schema_version: u32,
+    /// Position of the event in the run, starting at 0
+    pub seq: u64,
+    pub session_id: Option<String>,
+    #[serde(flatten)]
+    pub event: StreamEvent,
+    /// The output line the event was parsed from; only set on the first event
+    /// of each line
+    pub raw: Option<String>,
+}
+
+/// Turns a run's output lines into numbered events
+#[derive(Debug, Default)]
+pub struct StreamParser {
+    seq: u64,
+    /// Message being streamed, from its `message_start` event
+    current_message_id: Option<String>,
+}
+
+fn text(value: &Value) -> Option<String> {
+    value.as_str().map(str::to_string)
+}
+
+fn usage(value: &Value) -> Option<TokenUsage> {
+    value
+        .is_object()
+        .then(|| serde_json::from_value(value.clone()).ok())
+        .flatten()
+}
+
+/// Flattens tool result content, which is a string or a list of blocks
+fn tool_result_text(content: &Value) -> String {
+    match content {
+        Value::String(content) => content.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .map(|block| match block["type"].as_str() {
+                Some("text") => block["text"].as_str().unwrap_or_default().to_string(),
+                Some("image") => "[image]".to_string(),
+                _ => block.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+impl StreamParser {
+    fn parse_message(&mut self, msg: &Value) -> Vec<StreamEvent> {
+        let parent_tool_use_id = text(&msg["parent_tool_use_id"]);
+        match msg["type"].as_str().unwrap_or_default() {
+            "system" if msg["subtype"] == "init" => vec![StreamEvent::Init {
+                model: text(&msg["model"]),
+                cwd: text(&msg["cwd"]),
+                tools: msg["tools"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(text)
+                    .collect(),
+                mcp_servers: msg["mcp_servers"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|server| serde_json::from_value(server.clone()).ok())
+                    .collect(),
+                permission_mode: text(&msg["permissionMode"]),
+                claude_code_version: text(&msg["claude_code_version"]),
+            }],
+            "system" => vec![StreamEvent::System {
+                subtype: text(&msg["subtype"]).unwrap_or_default(),
+            }],
+            "stream_event" => self.parse_partial(&msg["event"]),
+            "assistant" => {
+                let message = &msg["message"];
+                let blocks = message["content"].as_array().cloned().unwrap_or_default();
+                let joined = |kind: &str, field: &str| {
+                    blocks
+                        .iter()
+                        .filter(|block| block["type"] == kind)
+                        .filter_map(|block| block[field].as_str())
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                };
+                let thinking = joined("thinking", "thinking");
+                let mut events = vec![StreamEvent::AssistantMessage {
+                    message_id: text(&message["id"]),
+                    model: text(&message["model"]),
+                    text: joined("text", "text"),
+                    thinking: Some(thinking).filter(|t| !t.is_empty()),
+                    stop_reason: text(&message["stop_reason"]),
+                    usage: usage(&message["usage"]),
+                    parent_tool_use_id: parent_tool_use_id.clone(),
+                }];
+                events.extend(
+                    blocks
+                        .iter()
+                        .filter(|block| block["type"] == "tool_use")
+                        .map(|block| StreamEvent::ToolUse {
+                            id: text(&block["id"]).unwrap_or_default(),
+                            name: text(&block["name"]).unwrap_or_default(),
+                            input: block["input"].clone(),
+                            parent_tool_use_id: parent_tool_use_id.clone(),
+                        }),
+                );
+                events
+            }
+            "user" => msg["message"]["content"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|block| block["type"] == "tool_result")
+                .map(|block| StreamEvent::ToolResult {
+                    tool_use_id: text(&block["tool_use_id"]).unwrap_or_default(),
+                    content: tool_result_text(&block["content"]),
+                    is_error: block["is_error"].as_bool().unwrap_or(false),
+                    parent_tool_use_id: parent_tool_use_id.clone(),
+                })
+                .collect(),
+            "result" => {
+                let is_error = msg["is_error"].as_bool().unwrap_or(false);
+                let subtype = text(&msg["subtype"]).unwrap_or_default();
+                let result = text(&msg["result"]);
+                let mut events = vec![StreamEvent::Result {
+                    subtype: subtype.clone(),
+                    is_error,
+                    result: result.clone(),
+                    duration_ms: msg["duration_ms"].as_u64(),
+                    duration_api_ms: msg["duration_api_ms"].as_u64(),
+                    num_turns: msg["num_turns"].as_u64().map(|turns| turns as u32),
+                    total_cost_usd: msg["total_cost_usd"].as_f64(),
+                    usage: usage(&msg["usage"]),
+                }];
+                if is_error {
+                    events.push(StreamEvent::Error {
+                        source: "result".to_string(),
+                        message: result.unwrap_or(subtype),
+                    });
+                }
+                events
+            }
+            other => vec![StreamEvent::Unknown {
+                message_type: other.to_string(),
+            }],
+        }
+    }
+
+    /// Deltas of a partial message; other stream events carry nothing new
+    fn parse_partial(&mut self, event: &Value) -> Vec<StreamEvent> {
+        match event["type"].as_str() {
+            Some("message_start") => {
+                self.current_message_id = text(&event["message"]["id"]);
+                Vec::new()
+            }
+            Some("content_block_delta") => {
+                let delta = &event["delta"];
+                let (kind, field) = match delta["type"].as_str() {
+                    Some("text_delta") => ("text", "text"),
+                    Some("thinking_delta") => ("thinking", "thinking"),
+                    Some("input_json_delta") => ("tool_input", "partial_json"),
+                    _ => return Vec::new(),
+                };
+                vec![StreamEvent::AssistantDelta {
+                    message_id: self.current_message_id.clone(),
+                    index: event["index"].as_u64().unwrap_or(0),
+                    kind: kind.to_string(),
+                    text: text(&delta[field]).unwrap_or_default(),
+                }]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn envelopes(
+        &mut self,
+        events: Vec<StreamEvent>,
+        session_id: Option<&str>,
+        line: &str,
+    ) -> Vec<StreamEnvelope> {
+        events
+            .into_iter()
+            .enumerate()
+            .map(|(i, event)| {
+                let envelope = StreamEnvelope {
+                    schema_version: STREAM_EVENT_SCHEMA_VERSION,
+                    seq: self.seq,
+                    session_id: session_id.map(str::to_string),
+                    event,
+                    raw: (i == 0).then(|| line.to_string()),
+                };
+                self.seq += 1;
+                envelope
+            })
+            .collect()
+    }
+
+    /// Parses a stdout line into events
+    pub fn parse_line(&mut self, line: &str, session_id: Option<&str>) -> Vec<StreamEnvelope> {
+        if line.trim().is_empty() {
+            return Vec::new();
+        }
+        let events = match serde_json::from_str::<Value>(line) {
+            Ok(msg) => self.parse_message(&msg),
+            // The CLI prints some failures as plain text
+            Err(_) => vec![StreamEvent::Error {
+                source: "stdout".to_string(),
+                message: line.to_string(),
+            }],
+        };
+        self.envelopes(events, session_id, line)
+    }
+
+    /// Wraps a stderr line as an error event
+    pub fn parse_stderr(&mut self, line: &str, session_id: Option<&str>) -> Vec<StreamEnvelope> {
+        if line.trim().is_empty() {
+            return Vec::new();
+        }
+        let event = StreamEvent::Error {
+            source: "stderr".to_string(),
+            message: line.to_string(),
+        };
+        self.envelopes(vec![event], session_id, line)
+    }
+}
+
+/// Emits events on `claude-event:{session_id}` and, for interactive runs, on
+/// the generic `claude-event`, and streams them to local API clients
+pub fn emit_stream_events(app: &AppHandle, envelopes: &[StreamEnvelope], background: bool) {
+    if let Some(api_server) = app.try_state::<ApiServerState>() {
+        api_server.publish(envelopes);
+    }
+    for envelope in envelopes {
+        if let Some(ref session_id) = envelope.session_id {
+            let _ = app.emit(&format!("claude-event:{}", session_id), envelope);
+        }
+        if !background {
+            let _ = app.emit("claude-event", envelope);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_assistant_and_tool_result() {
+        let mut parser = StreamParser::default();
+        let line = json!({
+            "type": "assistant",
+            "parent_tool_use_id": null,
+            "message": {
+                "id": "msg_1",
+                "model": "claude-sonnet-4",
+                "content": [
+                    {"type": "text", "text": "Running the tests"},
+                    {"type": "tool_use", "id": "toolu_1", "name": "Bash", "input": {"command": "npm test"}}
+                ],
+                "usage": {"input_tokens": 10, "output_tokens": 5}
+            }
+        })
+        .to_string();
+        let envelopes = parser.parse_line(&line, Some("s1"));
+        assert_eq!(envelopes.len(), 2);
+        assert_eq!(envelopes[0].seq, 0);
+        assert_eq!(envelopes[0].raw.as_deref(), Some(line.as_str()));
+        assert!(envelopes[1].raw.is_none());
+        match &envelopes[0].event {
+            StreamEvent::AssistantMessage { text, usage, .. } => {
+                assert_eq!(text, "Running the tests");
+                assert_eq!(usage.as_ref().unwrap().input_tokens, 10);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+        assert!(matches!(&envelopes[1].event, StreamEvent::ToolUse { name, .. } if name == "Bash"));
+
+        let line = json!({"type": "user", "message": {"content": [
+            {"type": "tool_result", "tool_use_id": "toolu_1", "is_error": true,
+             "content": [{"type": "text", "text": "1 failed"}]}
+        ]}})
+        .to_string();
+        let envelopes = parser.parse_line(&line, Some("s1"));
+        assert_eq!(envelopes[0].seq, 2);
+        assert_eq!(
+            envelopes[0].event,
+            StreamEvent::ToolResult {
+                tool_use_id: "toolu_1".to_string(),
+                content: "1 failed".to_string(),
+                is_error: true,
+                parent_tool_use_id: None,
+            }
+        );
+
+        let json = serde_json::to_value(&envelopes[0]).unwrap();
+        assert_eq!(json["type"], "tool_result");
+        assert_eq!(json["schema_version"], STREAM_EVENT_SCHEMA_VERSION);
+        assert_eq!(json["session_id"], "s1");
+    }
+
+    #[test]
+    fn test_parse_deltas_results_and_errors() {
+        let mut parser = StreamParser::default();
+        let lines = [
+            json!({"type": "stream_event", "event": {"type": "message_start", "message": {"id": "msg_2"}}}),
+            json!({"type": "stream_event", "event": {"type": "content_block_delta", "index": 0,
+                   "delta": {"type": "text_delta", "text": "Hel"}}}),
+            json!({"type": "result", "subtype": "error_max_turns", "is_error": true,
+                   "num_turns": 3, "total_cost_usd": 0.25}),
+        ];
+        let events: Vec<StreamEvent> = lines
+            .iter()
+            .flat_map(|line| parser.parse_line(&line.to_string(), None))
+            .map(|envelope| envelope.event)
+            .collect();
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events[0],
+            StreamEvent::AssistantDelta {
+                message_id: Some("msg_2".to_string()),
+                index: 0,
+                kind: "text".to_string(),
+                text: "Hel".to_string(),
+            }
+        );
+        assert!(matches!(
+            events[1],
+            StreamEvent::Result {
+                num_turns: Some(3),
+                ..
+            }
+        ));
+        assert!(matches!(&events[2], StreamEvent::Error { source, .. } if source == "result"));
+
+        let envelopes = parser.parse_line("Error: Invalid API key", None);
+        assert!(
+            matches!(&envelopes[0].event, StreamEvent::Error { source, .. } if source == "stdout")
+        );
+        assert_eq!(envelopes[0].seq, 3);
+    }
+}
@@ -0,0 +1,471 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use super::claude::{decode_project_path, get_project_path_from_sessions};
+use crate::context::CoreContext;
+
+/// A transcript written within this window belongs to a session that is still going
+const ACTIVE_WINDOW: Duration = Duration::from_secs(120);
+
+/// State of a todo item, as written by the TodoWrite tool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoStatus {
+    Pending,
+    InProgress,
+    Completed,
+    /// A status written by a newer CLI, kept so the rest of the list still loads
+    #[serde(other)]
+    Unknown,
+}
+
+/// An item of a session's todo list in ~/.claude/todos
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoItem {
+    /// Item ID (only written by older CLI versions)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub content: String,
+    pub status: TodoStatus,
+    /// Present-tense form shown while the item is in progress
+    #[serde(
+        rename = "activeForm",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub active_form: Option<String>,
+    /// Priority (only written by older CLI versions)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+}
+
+impl TodoItem {
+    /// Key matching an item across TodoWrite calls
+    fn key(&self) -> &str {
+        self.id.as_deref().unwrap_or(&self.content)
+    }
+}
+
+/// A status change of a todo item, taken from a TodoWrite call in the transcript
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoTransition {
+    pub status: TodoStatus,
+    /// Timestamp of the transcript entry with the TodoWrite call
+    pub at: String,
+}
+
+/// A todo item with its status history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackedTodo {
+    #[serde(flatten)]
+    pub item: TodoItem,
+    /// Status changes in order; empty if the transcript has no TodoWrite call for it
+    pub history: Vec<TodoTransition>,
+}
+
+/// The todo list of a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTodos {
+    pub session_id: String,
+    /// The project ID, if the session's transcript was found
+    pub project_id: Option<String>,
+    pub project_path: Option<String>,
+    pub todos: Vec<TrackedTodo>,
+    /// Unix timestamp of the last write to the todo list
+    pub updated_at: u64,
+    /// Whether the session is running in Claudio or its transcript was written recently
+    pub active: bool,
+}
+
+impl SessionTodos {
+    fn count(&self, status: TodoStatus) -> usize {
+        self.todos
+            .iter()
+            .filter(|t| t.item.status == status)
+            .count()
+    }
+}
+
+/// Outstanding work of a project, across its sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectTodoSummary {
+    pub project_id: String,
+    pub project_path: String,
+    pub pending: usize,
+    pub in_progress: usize,
+    pub completed: usize,
+    /// Sessions with pending or in-progress items, most recently updated first
+    pub sessions: Vec<SessionTodos>,
+}
+
+/// Returns the session ID of a session's own todo file
+///
+/// The CLI writes `<session>-agent-<agent>.json`, where the agent is the
+/// session itself for the main list; older versions wrote `<session>.json`.
+/// Sub-agent lists return None.
+fn todo_file_session_id(file_name: &str) -> Option<&str> {
+    let stem = file_name.strip_suffix(".json")?;
+    match stem.split_once("-agent-") {
+        Some((session_id, agent_id)) if session_id == agent_id => Some(session_id),
+        Some(_) => None,
+        None => Some(stem),
+    }
+}
+
+/// Reads a session's todo items from ~/.claude/todos
+pub fn load_session_todo_items(todos_dir: &Path, session_id: &str) -> Option<Vec<TodoItem>> {
+    [
+        format!("{}-agent-{}.json", session_id, session_id),
+        format!("{}.json", session_id),
+    ]
+    .iter()
+    .map(|name| todos_dir.join(name))
+    .find(|path| path.exists())
+    .and_then(|path| fs::read_to_string(path).ok())
+    .and_then(|content| serde_json::from_str(&content).ok())
+}
+
+/// Replays the TodoWrite calls of a transcript into per-item status histories
+fn todo_history(transcript: &Path) -> HashMap<String, Vec<TodoTransition>> {
+    let mut history: HashMap<String, Vec<TodoTransition>> = HashMap::new();
+    let Ok(file) = fs::File::open(transcript) else {
+        return history;
+    };
+
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        // Skip lines that can't contain a TodoWrite call before parsing them
+        if !line.contains("\"TodoWrite\"") {
+            continue;
+        }
+        let Ok(entry) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+        let timestamp = entry["timestamp"].as_str().unwrap_or_default();
+        let Some(content) = entry["message"]["content"].as_array() else {
+            continue;
+        };
+
+        for block in content {
+            if block["type"] != "tool_use" || block["name"] != "TodoWrite" {
+                continue;
+            }
+            let Ok(todos) =
+                serde_json::from_value::<Vec<TodoItem>>(block["input"]["todos"].clone())
+            else {
+                continue;
+            };
+            for todo in todos {
+                let transitions = history.entry(todo.key().to_string()).or_default();
+                if transitions.last().map(|t| t.status) != Some(todo.status) {
+                    transitions.push(TodoTransition {
+                        status: todo.status,
+                        at: timestamp.to_string(),
+                    });
+                }
+            }
+        }
+    }
+    history
+}
+
+/// Maps session IDs to their project ID and transcript path
+fn index_sessions(projects_dir: &Path) -> HashMap<String, (String, PathBuf)> {
+    let mut index = HashMap::new();
+    let Ok(projects) = fs::read_dir(projects_dir) else {
+        return index;
+    };
+
+    for project in projects.flatten() {
+        let project_path = project.path();
+        let Some(project_id) = project.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        let Ok(sessions) = fs::read_dir(&project_path) else {
+            continue;
+        };
+        for session in sessions.flatten() {
+            let path = session.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("jsonl") {
+                continue;
+            }
+            if let Some(session_id) = path.file_stem().and_then(|s| s.to_str()) {
+                index.insert(session_id.to_string(), (project_id.clone(), path));
+            }
+        }
+    }
+    index
+}
+
+fn modified_secs(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn recently_written(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|elapsed| elapsed < ACTIVE_WINDOW)
+}
+
+/// Loads the todo lists of all sessions, skipping empty ones
+fn load_all_todos(
+    context: &CoreContext,
+    running: &HashSet<String>,
+) -> Result<Vec<SessionTodos>, String> {
+    let todos_dir = context.todos_dir();
+    let projects_dir = context.projects_dir();
+
+    let entries = match fs::read_dir(&todos_dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let sessions_index = index_sessions(&projects_dir);
+    let mut project_paths: HashMap<String, String> = HashMap::new();
+    let mut result = Vec::new();
+
+    for entry in entries.flatten() {
+        let file_name = entry.file_name();
+        let Some(session_id) = file_name.to_str().and_then(todo_file_session_id) else {
+            continue;
+        };
+        let Some(items) = fs::read_to_string(entry.path())
+            .ok()
+            .and_then(|content| serde_json::from_str::<Vec<TodoItem>>(&content).ok())
+        else {
+            continue;
+        };
+        if items.is_empty() {
+            continue;
+        }
+
+        let transcript = sessions_index.get(session_id);
+        let mut history = transcript
+            .map(|(_, path)| todo_history(path))
+            .unwrap_or_default();
+        let todos = items
+            .into_iter()
+            .map(|item| TrackedTodo {
+                history: history.remove(item.key()).unwrap_or_default(),
+                item,
+            })
+            .collect();
+
+        let project_id = transcript.map(|(project_id, _)| project_id.clone());
+        let project_path = project_id.as_ref().map(|project_id| {
+            project_paths
+                .entry(project_id.clone())
+                .or_insert_with(|| {
+                    get_project_path_from_sessions(&projects_dir.join(project_id))
+                        .unwrap_or_else(|_| decode_project_path(project_id))
+                })
+                .clone()
+        });
+
+        result.push(SessionTodos {
+            session_id: session_id.to_string(),
+            project_id,
+            project_path,
+            todos,
+            updated_at: modified_secs(&entry.path()),
+            active: running.contains(session_id)
+                || transcript.is_some_and(|(_, path)| recently_written(path)),
+        });
+    }
+
+    result.sort_by_key(|session| std::cmp::Reverse(session.updated_at));
+    Ok(result)
+}
+
+/// Session IDs of the Claude runs Claudio is managing
+fn running_session_ids(registry: &crate::process::ProcessRegistryState) -> HashSet<String> {
+    registry
+        .0
+        .get_running_claude_sessions()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|process| match process.process_type {
+            crate::process::ProcessType::ClaudeSession { session_id } => Some(session_id),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Lists todo lists across sessions
+///
+/// Items can be filtered by status; sessions left without items are omitted.
+#[tauri::command]
+pub async fn list_todos(
+    context: tauri::State<'_, CoreContext>,
+    registry: tauri::State<'_, crate::process::ProcessRegistryState>,
+    status: Option<TodoStatus>,
+    project_id: Option<String>,
+    active_only: Option<bool>,
+) -> Result<Vec<SessionTodos>, String> {
+    log::info!(
+        "Listing todos (status: {:?}, project: {:?})",
+        status,
+        project_id
+    );
+
+    let running = running_session_ids(&registry);
+    let mut sessions = load_all_todos(&context, &running)?;
+
+    sessions.retain_mut(|session| {
+        if project_id.is_some() && session.project_id != project_id {
+            return false;
+        }
+        if active_only.unwrap_or(false) && !session.active {
+            return false;
+        }
+        if let Some(status) = status {
+            session.todos.retain(|todo| todo.item.status == status);
+        }
+        !session.todos.is_empty()
+    });
+
+    Ok(sessions)
+}
+
+/// Gets the todo list of a session with each item's status history
+#[tauri::command]
+pub async fn get_session_todos(
+    context: tauri::State<'_, CoreContext>,
+    registry: tauri::State<'_, crate::process::ProcessRegistryState>,
+    session_id: String,
+) -> Result<Option<SessionTodos>, String> {
+    let running = running_session_ids(&registry);
+    Ok(load_all_todos(&context, &running)?
+        .into_iter()
+        .find(|session| session.session_id == session_id))
+}
+
+/// Summarizes pending and in-progress todos per project
+#[tauri::command]
+pub async fn get_outstanding_work(
+    context: tauri::State<'_, CoreContext>,
+    registry: tauri::State<'_, crate::process::ProcessRegistryState>,
+) -> Result<Vec<ProjectTodoSummary>, String> {
+    let running = running_session_ids(&registry);
+    let mut projects: HashMap<String, ProjectTodoSummary> = HashMap::new();
+
+    for session in load_all_todos(&context, &running)? {
+        let (Some(project_id), Some(project_path)) =
+            (session.project_id.clone(), session.project_path.clone())
+        else {
+            continue;
+        };
+        let pending = session.count(TodoStatus::Pending);
+        let in_progress = session.count(TodoStatus::InProgress);
+        if pending + in_progress == 0 {
+            continue;
+        }
+
+        let summary = projects
+            .entry(project_id.clone())
+            .or_insert_with(|| ProjectTodoSummary {
+                project_id,
+                project_path,
+                pending: 0,
+                in_progress: 0,
+                completed: 0,
+                sessions: Vec::new(),
+            });
+        summary.pending += pending;
+        summary.in_progress += in_progress;
+        summary.completed += session.count(TodoStatus::Completed);
+        summary.sessions.push(session);
+    }
+
+    let mut projects: Vec<_> = projects.into_values().collect();
+    projects.sort_by(|a, b| {
+        b.in_progress
+            .cmp(&a.in_progress)
+            .then_with(|| b.pending.cmp(&a.pending))
+    });
+    Ok(projects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_todo_file_session_id() {
+        assert_eq!(todo_file_session_id("abc-agent-abc.json"), Some("abc"));
+        assert_eq!(todo_file_session_id("abc.json"), Some("abc"));
+        assert_eq!(todo_file_session_id("abc-agent-def.json"), None);
+        assert_eq!(todo_file_session_id("abc.txt"), None);
+    }
+
+    #[test]
+    fn test_todo_history_records_status_changes() {
+        let write = |timestamp: &str, first: &str, second: &str| {
+            serde_json::json!({
+                "type": "assistant",
+                "timestamp": timestamp,
+                "message": { "content": [{
+                    "type": "tool_use",
+                    "name": "TodoWrite",
+                    "input": { "todos": [
+                        { "content": "Write parser", "status": first, "activeForm": "Writing parser" },
+                        { "content": "Add tests", "status": second, "activeForm": "Adding tests" }
+                    ]}
+                }]}
+            })
+            .to_string()
+        };
+
+        let mut transcript = tempfile::NamedTempFile::new().unwrap();
+        for line in [
+            write("t1", "in_progress", "pending"),
+            r#"{"type":"user","message":{"content":"keep going"}}"#.to_string(),
+            write("t2", "in_progress", "pending"),
+            write("t3", "completed", "in_progress"),
+        ] {
+            writeln!(transcript, "{}", line).unwrap();
+        }
+
+        let history = todo_history(transcript.path());
+        let statuses = |key: &str| -> Vec<(TodoStatus, String)> {
+            history[key]
+                .iter()
+                .map(|t| (t.status, t.at.clone()))
+                .collect()
+        };
+        assert_eq!(
+            statuses("Write parser"),
+            vec![
+                (TodoStatus::InProgress, "t1".to_string()),
+                (TodoStatus::Completed, "t3".to_string())
+            ]
+        );
+        assert_eq!(
+            statuses("Add tests"),
+            vec![
+                (TodoStatus::Pending, "t1".to_string()),
+                (TodoStatus::InProgress, "t3".to_string())
+            ]
+        );
+
+        // A status from a newer CLI doesn't drop the list
+        let items: Vec<TodoItem> = serde_json::from_str(
+            r#"[{"content":"Ship it","status":"blocked"},{"content":"Review","status":"pending"}]"#,
+        )
+        .unwrap();
+        assert_eq!(items[0].status, TodoStatus::Unknown);
+        assert_eq!(items[1].status, TodoStatus::Pending);
+    }
+}
@@ -3,64 +3,66 @@ use serde::{Deserialize, Serialize};
 use serde_json;
 use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
-use tauri::command;
+use std::path::{Path, PathBuf};
+use tauri::{command, State};
+
+use crate::context::CoreContext;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UsageEntry {
-    timestamp: String,
-    model: String,
-    input_tokens: u64,
-    output_tokens: u64,
-    cache_creation_tokens: u64,
-    cache_read_tokens: u64,
-    cost: f64,
-    session_id: String,
-    project_path: String,
+    pub timestamp: String,
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cost: f64,
+    pub session_id: String,
+    pub project_path: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UsageStats {
-    total_cost: f64,
-    total_tokens: u64,
-    total_input_tokens: u64,
-    total_output_tokens: u64,
-    total_cache_creation_tokens: u64,
-    total_cache_read_tokens: u64,
-    total_sessions: u64,
-    by_model: Vec<ModelUsage>,
-    by_date: Vec<DailyUsage>,
-    by_project: Vec<ProjectUsage>,
+    pub total_cost: f64,
+    pub total_tokens: u64,
+    pub total_input_tokens: u64,
+    pub total_output_tokens: u64,
+    pub total_cache_creation_tokens: u64,
+    pub total_cache_read_tokens: u64,
+    pub total_sessions: u64,
+    pub by_model: Vec<ModelUsage>,
+    pub by_date: Vec<DailyUsage>,
+    pub by_project: Vec<ProjectUsage>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ModelUsage {
-    model: String,
-    total_cost: f64,
-    total_tokens: u64,
-    input_tokens: u64,
-    output_tokens: u64,
-    cache_creation_tokens: u64,
-    cache_read_tokens: u64,
-    session_count: u64,
+    pub model: String,
+    pub total_cost: f64,
+    pub total_tokens: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub session_count: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DailyUsage {
-    date: String,
-    total_cost: f64,
-    total_tokens: u64,
-    models_used: Vec<String>,
+    pub date: String,
+    pub total_cost: f64,
+    pub total_tokens: u64,
+    pub models_used: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ProjectUsage {
-    project_path: String,
-    project_name: String,
-    total_cost: f64,
-    total_tokens: u64,
-    session_count: u64,
-    last_used: String,
+    pub project_path: String,
+    pub project_name: String,
+    pub total_cost: f64,
+    pub total_tokens: u64,
+    pub session_count: u64,
+    pub last_used: String,
 }
 
 // Claude 4 pricing constants (per million tokens)
@@ -94,14 +96,14 @@ struct MessageData {
 }
 
 #[derive(Debug, Deserialize)]
-struct UsageData {
-    input_tokens: Option<u64>,
-    output_tokens: Option<u64>,
-    cache_creation_input_tokens: Option<u64>,
-    cache_read_input_tokens: Option<u64>,
+pub(crate) struct UsageData {
+    pub(crate) input_tokens: Option<u64>,
+    pub(crate) output_tokens: Option<u64>,
+    pub(crate) cache_creation_input_tokens: Option<u64>,
+    pub(crate) cache_read_input_tokens: Option<u64>,
 }
 
-fn calculate_cost(model: &str, usage: &UsageData) -> f64 {
+pub(crate) fn calculate_cost(model: &str, usage: &UsageData) -> f64 {
     let input_tokens = usage.input_tokens.unwrap_or(0) as f64;
     let output_tokens = usage.output_tokens.unwrap_or(0) as f64;
     let cache_creation_tokens = usage.cache_creation_input_tokens.unwrap_or(0) as f64;
@@ -249,7 +251,7 @@ fn get_earliest_timestamp(path: &PathBuf) -> Option<String> {
     None
 }
 
-fn get_all_usage_entries(claude_path: &PathBuf) -> Vec<UsageEntry> {
+fn get_all_usage_entries(claude_path: &Path) -> Vec<UsageEntry> {
     let mut all_entries = Vec::new();
     let mut processed_hashes = HashSet::new();
     let projects_dir = claude_path.join("projects");
@@ -289,12 +291,13 @@ fn get_all_usage_entries(claude_path: &PathBuf) -> Vec<UsageEntry> {
 }
 
 #[command]
-pub fn get_usage_stats(days: Option<u32>) -> Result<UsageStats, String> {
-    let claude_path = dirs::home_dir()
-        .ok_or("Failed to get home directory")?
-        .join(".claude");
+pub fn get_usage_stats(context: State<'_, CoreContext>, days: Option<u32>) -> Result<UsageStats, String> {
+    get_usage_stats_in(&context, days)
+}
 
-    let all_entries = get_all_usage_entries(&claude_path);
+/// Summarizes usage over the last `days` days, or all time
+pub fn get_usage_stats_in(context: &CoreContext, days: Option<u32>) -> Result<UsageStats, String> {
+    let all_entries = get_all_usage_entries(&context.claude_dir);
 
     if all_entries.is_empty() {
         return Ok(UsageStats {
@@ -449,23 +452,32 @@ pub fn get_usage_stats(days: Option<u32>) -> Result<UsageStats, String> {
 }
 
 #[command]
-pub fn get_usage_by_date_range(start_date: String, end_date: String) -> Result<UsageStats, String> {
-    let claude_path = dirs::home_dir()
-        .ok_or("Failed to get home directory")?
-        .join(".claude");
+pub fn get_usage_by_date_range(
+    context: State<'_, CoreContext>,
+    start_date: String,
+    end_date: String,
+) -> Result<UsageStats, String> {
+    get_usage_by_date_range_in(&context, &start_date, &end_date)
+}
 
-    let all_entries = get_all_usage_entries(&claude_path);
+/// Summarizes usage between two dates, inclusive
+pub fn get_usage_by_date_range_in(
+    context: &CoreContext,
+    start_date: &str,
+    end_date: &str,
+) -> Result<UsageStats, String> {
+    let all_entries = get_all_usage_entries(&context.claude_dir);
 
     // Parse dates
-    let start = NaiveDate::parse_from_str(&start_date, "%Y-%m-%d").or_else(|_| {
+    let start = NaiveDate::parse_from_str(start_date, "%Y-%m-%d").or_else(|_| {
         // Try parsing ISO datetime format
-        DateTime::parse_from_rfc3339(&start_date)
+        DateTime::parse_from_rfc3339(start_date)
             .map(|dt| dt.naive_local().date())
             .map_err(|e| format!("Invalid start date: {}", e))
     })?;
-    let end = NaiveDate::parse_from_str(&end_date, "%Y-%m-%d").or_else(|_| {
+    let end = NaiveDate::parse_from_str(end_date, "%Y-%m-%d").or_else(|_| {
         // Try parsing ISO datetime format
-        DateTime::parse_from_rfc3339(&end_date)
+        DateTime::parse_from_rfc3339(end_date)
             .map(|dt| dt.naive_local().date())
             .map_err(|e| format!("Invalid end date: {}", e))
     })?;
@@ -620,14 +632,20 @@ pub fn get_usage_by_date_range(start_date: String, end_date: String) -> Result<U
 
 #[command]
 pub fn get_usage_details(
+    context: State<'_, CoreContext>,
     project_path: Option<String>,
     date: Option<String>,
 ) -> Result<Vec<UsageEntry>, String> {
-    let claude_path = dirs::home_dir()
-        .ok_or("Failed to get home directory")?
-        .join(".claude");
+    get_usage_details_in(&context, project_path, date)
+}
 
-    let mut all_entries = get_all_usage_entries(&claude_path);
+/// Lists usage entries, optionally for one project and one date (or date prefix)
+pub fn get_usage_details_in(
+    context: &CoreContext,
+    project_path: Option<String>,
+    date: Option<String>,
+) -> Result<Vec<UsageEntry>, String> {
+    let mut all_entries = get_all_usage_entries(&context.claude_dir);
 
     // Filter by project if specified
     if let Some(project) = project_path {
@@ -644,15 +662,22 @@ pub fn get_usage_details(
 
 #[command]
 pub fn get_session_stats(
+    context: State<'_, CoreContext>,
     since: Option<String>,
     until: Option<String>,
     order: Option<String>,
 ) -> Result<Vec<ProjectUsage>, String> {
-    let claude_path = dirs::home_dir()
-        .ok_or("Failed to get home directory")?
-        .join(".claude");
+    get_session_stats_in(&context, since, until, order)
+}
 
-    let all_entries = get_all_usage_entries(&claude_path);
+/// Summarizes usage per session between optional YYYYMMDD dates
+pub fn get_session_stats_in(
+    context: &CoreContext,
+    since: Option<String>,
+    until: Option<String>,
+    order: Option<String>,
+) -> Result<Vec<ProjectUsage>, String> {
+    let all_entries = get_all_usage_entries(&context.claude_dir);
 
     let since_date = since.and_then(|s| NaiveDate::parse_from_str(&s, "%Y%m%d").ok());
     let until_date = until.and_then(|s| NaiveDate::parse_from_str(&s, "%Y%m%d").ok());
@@ -0,0 +1,644 @@
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::{AppHandle, Manager, State};
+
+use super::agents::AgentDb;
+use super::claude::{start_headless_run, RunHandle, RunOptions, RunOutcome};
+
+/// A git worktree created to isolate a session's changes from the main checkout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionWorktree {
+    pub id: i64,
+    /// Root of the repository the worktree was created from
+    pub repo_path: String,
+    /// Directory of the worktree; the session runs here
+    pub worktree_path: String,
+    /// Branch checked out in the worktree
+    pub branch: String,
+    /// Commit the branch was created from
+    pub base_commit: String,
+    /// The session run in the worktree, once known
+    pub session_id: Option<String>,
+    /// active, merged or discarded
+    pub status: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// A file changed in a worktree relative to its base commit
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorktreeFileChange {
+    pub path: String,
+    /// added, modified, deleted, renamed or copied
+    pub status: String,
+    /// Previous path for renames and copies
+    pub old_path: Option<String>,
+    /// Lines added; None for binary files
+    pub additions: Option<u64>,
+    /// Lines deleted; None for binary files
+    pub deletions: Option<u64>,
+}
+
+/// Changes made in a worktree, including uncommitted and untracked files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorktreeDiff {
+    pub worktree: SessionWorktree,
+    pub files: Vec<WorktreeFileChange>,
+    /// Unified diff of all changes
+    pub patch: String,
+}
+
+/// Runs git in a directory and returns its stdout
+pub(crate) fn run_git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    log::debug!("Running git {:?} in {:?}", args, dir);
+
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+const SELECT_COLUMNS: &str = "SELECT id, repo_path, worktree_path, branch, base_commit, session_id, status, created_at, updated_at FROM session_worktrees";
+
+fn row_to_worktree(row: &rusqlite::Row) -> SqliteResult<SessionWorktree> {
+    Ok(SessionWorktree {
+        id: row.get(0)?,
+        repo_path: row.get(1)?,
+        worktree_path: row.get(2)?,
+        branch: row.get(3)?,
+        base_commit: row.get(4)?,
+        session_id: row.get(5)?,
+        status: row.get(6)?,
+        created_at: row.get(7)?,
+        updated_at: row.get(8)?,
+    })
+}
+
+fn load_worktree(conn: &Connection, worktree_id: i64) -> Result<SessionWorktree, String> {
+    conn.query_row(
+        &format!("{} WHERE id = ?1", SELECT_COLUMNS),
+        params![worktree_id],
+        row_to_worktree,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Worktree {} not found", worktree_id))
+}
+
+fn set_worktree_status(conn: &Connection, worktree_id: i64, status: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE session_worktrees SET status = ?1, updated_at = ?2 WHERE id = ?3",
+        params![status, chrono::Utc::now().to_rfc3339(), worktree_id],
+    )
+    .map_err(|e| format!("Failed to update worktree: {}", e))?;
+    Ok(())
+}
+
+/// Loads the worktrees relevant to a project's sessions, keyed by session ID
+///
+/// A worktree whose session isn't known yet is keyed by an empty string when
+/// `project_path` is the worktree itself.
+pub fn load_project_worktrees(
+    conn: &Connection,
+    project_path: &str,
+) -> Result<HashMap<String, SessionWorktree>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE worktree_path = ?1 OR repo_path = ?1",
+            SELECT_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let worktrees = stmt
+        .query_map(params![project_path], row_to_worktree)
+        .map_err(|e| e.to_string())?
+        .collect::<SqliteResult<Vec<_>>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(worktrees
+        .into_iter()
+        .filter_map(|worktree| match worktree.session_id.clone() {
+            Some(session_id) => Some((session_id, worktree)),
+            None if worktree.worktree_path == project_path => Some((String::new(), worktree)),
+            None => None,
+        })
+        .collect())
+}
+
+/// Parses `git diff --name-status` output
+fn parse_name_status(output: &str) -> Vec<WorktreeFileChange> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split('\t');
+            let code = parts.next()?;
+            let first = parts.next()?.to_string();
+            let second = parts.next().map(|s| s.to_string());
+
+            let status = match code.chars().next()? {
+                'A' => "added",
+                'D' => "deleted",
+                'R' => "renamed",
+                'C' => "copied",
+                _ => "modified",
+            };
+            let (path, old_path) = match second {
+                Some(new_path) => (new_path, Some(first)),
+                None => (first, None),
+            };
+
+            Some(WorktreeFileChange {
+                path,
+                status: status.to_string(),
+                old_path,
+                additions: None,
+                deletions: None,
+            })
+        })
+        .collect()
+}
+
+/// Parses `git diff --numstat -z` output into per-path line counts
+fn parse_numstat(output: &str) -> HashMap<String, (Option<u64>, Option<u64>)> {
+    let mut counts = HashMap::new();
+    let mut fields = output.split('\0');
+
+    while let Some(entry) = fields.next() {
+        let mut parts = entry.splitn(3, '\t');
+        let (Some(additions), Some(deletions), Some(path)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        // Renames have an empty path followed by the old and new paths
+        let path = if path.is_empty() {
+            let _old_path = fields.next();
+            fields.next().unwrap_or_default().to_string()
+        } else {
+            path.to_string()
+        };
+        counts.insert(path, (additions.parse().ok(), deletions.parse().ok()));
+    }
+
+    counts
+}
+
+/// Turns text into a slug usable as a directory name
+fn slugify(text: &str) -> String {
+    let slug: String = text
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    slug.split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Creates a worktree on a new branch for a session and records it
+pub fn create_worktree(
+    app: &AppHandle,
+    project_path: &str,
+    branch: Option<String>,
+) -> Result<SessionWorktree, String> {
+    let repo_path = run_git(Path::new(project_path), &["rev-parse", "--show-toplevel"])?
+        .trim()
+        .to_string();
+    let repo_root = PathBuf::from(&repo_path);
+    let base_commit = run_git(&repo_root, &["rev-parse", "HEAD"])
+        .map_err(|_| "The repository has no commits yet".to_string())?
+        .trim()
+        .to_string();
+
+    let now = chrono::Local::now();
+    let branch = branch
+        .map(|b| b.trim().to_string())
+        .filter(|b| !b.is_empty())
+        .unwrap_or_else(|| {
+            // The suffix keeps runs started within the same second apart
+            let suffix = uuid::Uuid::new_v4().simple().to_string();
+            format!(
+                "claudio/session-{}-{}",
+                now.format("%Y%m%d-%H%M%S"),
+                &suffix[..8]
+            )
+        });
+    run_git(&repo_root, &["check-ref-format", "--branch", &branch])
+        .map_err(|_| format!("Invalid branch name: {}", branch))?;
+
+    let repo_name = repo_root
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("repo");
+    let worktree_path = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("worktrees")
+        .join(slugify(repo_name))
+        .join(slugify(&branch));
+    if worktree_path.exists() {
+        return Err(format!(
+            "Worktree directory already exists: {:?}",
+            worktree_path
+        ));
+    }
+    if let Some(parent) = worktree_path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create worktree directory: {}", e))?;
+    }
+
+    let worktree_str = worktree_path.to_string_lossy().to_string();
+    log::info!(
+        "Creating worktree {} on branch {} for {}",
+        worktree_str,
+        branch,
+        repo_path
+    );
+    run_git(
+        &repo_root,
+        &[
+            "worktree",
+            "add",
+            "-b",
+            &branch,
+            &worktree_str,
+            &base_commit,
+        ],
+    )?;
+
+    let now = now.with_timezone(&chrono::Utc).to_rfc3339();
+    let recorded = (|| {
+        let db = app.state::<AgentDb>();
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO session_worktrees (repo_path, worktree_path, branch, base_commit, status, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, 'active', ?5, ?5)",
+            params![repo_path, worktree_str, branch, base_commit, now],
+        )
+        .map_err(|e| format!("Failed to record worktree: {}", e))?;
+        load_worktree(&conn, conn.last_insert_rowid())
+    })();
+
+    // Nothing could discard a worktree that isn't recorded, so don't keep it
+    if recorded.is_err() {
+        if let Err(e) = run_git(
+            &repo_root,
+            &["worktree", "remove", "--force", &worktree_str],
+        ) {
+            log::warn!(
+                "Failed to remove unrecorded worktree {}: {}",
+                worktree_str,
+                e
+            );
+        }
+        if let Err(e) = run_git(&repo_root, &["branch", "-D", &branch]) {
+            log::warn!("Failed to delete branch {}: {}", branch, e);
+        }
+    }
+    recorded
+}
+
+/// Starts a headless run in a new worktree of `project_path`. If the run fails
+/// to start, the worktree is removed and marked discarded, as no session used it.
+pub async fn start_run_in_worktree(
+    app: &AppHandle,
+    project_path: &str,
+    prompt: String,
+    model: String,
+    options: RunOptions,
+) -> Result<(SessionWorktree, RunHandle), String> {
+    let worktree = create_worktree(app, project_path, None)?;
+    let started = start_headless_run(
+        app.clone(),
+        worktree.worktree_path.clone(),
+        prompt,
+        model,
+        Vec::new(),
+        options,
+    )
+    .await;
+
+    match started {
+        Ok(handle) => Ok((worktree, handle)),
+        Err(e) => {
+            log::info!(
+                "Removing worktree {} of a run that failed to start",
+                worktree.worktree_path
+            );
+            if let Err(e) = remove_worktree(&worktree, true) {
+                log::warn!(
+                    "Failed to remove worktree {}: {}",
+                    worktree.worktree_path,
+                    e
+                );
+            }
+            let db = app.state::<AgentDb>();
+            if let Ok(conn) = db.0.lock() {
+                if let Err(e) = set_worktree_status(&conn, worktree.id, "discarded") {
+                    log::warn!("Failed to mark worktree {} discarded: {}", worktree.id, e);
+                }
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Records the session run in a worktree, unless one is recorded already
+pub fn bind_session(conn: &Connection, worktree_id: i64, session_id: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE session_worktrees SET session_id = ?1, updated_at = ?2
+         WHERE id = ?3 AND session_id IS NULL",
+        params![session_id, chrono::Utc::now().to_rfc3339(), worktree_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Records the session run in a worktree once the run has finished
+pub fn bind_session_on_completion(
+    app: &AppHandle,
+    worktree_id: i64,
+    outcome: tokio::sync::oneshot::Receiver<RunOutcome>,
+) {
+    let app = app.clone();
+    tokio::spawn(async move {
+        let Ok(outcome) = outcome.await else {
+            return;
+        };
+        let Some(session_id) = outcome.session_id else {
+            return;
+        };
+        let db = app.state::<AgentDb>();
+        let Ok(conn) = db.0.lock() else {
+            return;
+        };
+        if let Err(e) = bind_session(&conn, worktree_id, &session_id) {
+            log::error!("Failed to bind session to worktree {}: {}", worktree_id, e);
+        }
+    });
+}
+
+/// Removes a worktree's directory and branch
+fn remove_worktree(worktree: &SessionWorktree, force_branch: bool) -> Result<(), String> {
+    let repo_root = Path::new(&worktree.repo_path);
+
+    if Path::new(&worktree.worktree_path).exists() {
+        run_git(
+            repo_root,
+            &["worktree", "remove", "--force", &worktree.worktree_path],
+        )?;
+    } else {
+        run_git(repo_root, &["worktree", "prune"])?;
+    }
+
+    let delete_flag = if force_branch { "-D" } else { "-d" };
+    if let Err(e) = run_git(repo_root, &["branch", delete_flag, &worktree.branch]) {
+        log::warn!("Failed to delete branch {}: {}", worktree.branch, e);
+    }
+    Ok(())
+}
+
+/// Creates a worktree on a new branch to run a session in isolation
+///
+/// Without `branch`, a `claudio/session-<timestamp>-<id>` branch is created.
+#[tauri::command]
+pub async fn create_session_worktree(
+    app: AppHandle,
+    project_path: String,
+    branch: Option<String>,
+) -> Result<SessionWorktree, String> {
+    create_worktree(&app, &project_path, branch)
+}
+
+/// Lists session worktrees, newest first, optionally only those of one repository
+#[tauri::command]
+pub async fn list_session_worktrees(
+    db: State<'_, AgentDb>,
+    repo_path: Option<String>,
+) -> Result<Vec<SessionWorktree>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE (?1 IS NULL OR repo_path = ?1) ORDER BY id DESC",
+            SELECT_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let worktrees = stmt
+        .query_map(params![repo_path], row_to_worktree)
+        .map_err(|e| e.to_string())?
+        .collect::<SqliteResult<Vec<_>>>()
+        .map_err(|e| e.to_string())?;
+    Ok(worktrees)
+}
+
+/// Changed files and the unified diff of a checkout since a commit, including
+/// uncommitted and untracked files
+pub(crate) fn diff_since(
+    dir: &Path,
+    base: &str,
+) -> Result<(Vec<WorktreeFileChange>, String), String> {
+    let mut files = parse_name_status(&run_git(dir, &["diff", "--name-status", "-M", base])?);
+    let counts = parse_numstat(&run_git(dir, &["diff", "--numstat", "-z", "-M", base])?);
+    for file in &mut files {
+        if let Some((additions, deletions)) = counts.get(&file.path) {
+            file.additions = *additions;
+            file.deletions = *deletions;
+        }
+    }
+    let mut patch = run_git(dir, &["diff", "-M", base])?;
+
+    // Untracked files aren't in the index, so diff them against nothing without
+    // touching the index
+    let untracked = run_git(dir, &["ls-files", "--others", "--exclude-standard", "-z"])?;
+    for path in untracked.split('\0').filter(|path| !path.is_empty()) {
+        let numstat = diff_untracked(dir, &["--numstat"], path)?;
+        let mut counts = numstat.split('\t');
+        files.push(WorktreeFileChange {
+            path: path.to_string(),
+            status: "added".to_string(),
+            old_path: None,
+            additions: counts.next().and_then(|count| count.parse().ok()),
+            deletions: counts.next().and_then(|count| count.parse().ok()),
+        });
+        patch.push_str(&diff_untracked(dir, &[], path)?);
+    }
+    Ok((files, patch))
+}
+
+/// Runs `git diff --no-index` of an untracked file against /dev/null; unlike
+/// other git commands it exits with 1 when there are differences
+fn diff_untracked(dir: &Path, args: &[&str], path: &str) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(["diff", "--no-index"])
+        .args(args)
+        .args(["--", "/dev/null", path])
+        .current_dir(dir)
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+
+    match output.status.code() {
+        Some(0) | Some(1) => Ok(String::from_utf8_lossy(&output.stdout).to_string()),
+        _ => Err(format!(
+            "git diff failed for {}: {}",
+            path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+    }
+}
+
+/// Gets the changes made in a worktree since its base commit
+#[tauri::command]
+pub async fn get_worktree_diff(
+    db: State<'_, AgentDb>,
+    worktree_id: i64,
+) -> Result<WorktreeDiff, String> {
+    let worktree = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        load_worktree(&conn, worktree_id)?
+    };
+    if worktree.status != "active" {
+        return Err(format!("Worktree {} is {}", worktree_id, worktree.status));
+    }
+
+    let (files, patch) = diff_since(Path::new(&worktree.worktree_path), &worktree.base_commit)?;
+
+    Ok(WorktreeDiff {
+        worktree,
+        files,
+        patch,
+    })
+}
+
+/// Commits any pending changes in a worktree, merges its branch into the
+/// repository's current branch and removes the worktree
+///
+/// If the merge conflicts it is aborted and the worktree is kept.
+#[tauri::command]
+pub async fn merge_session_worktree(
+    db: State<'_, AgentDb>,
+    worktree_id: i64,
+    commit_message: Option<String>,
+) -> Result<SessionWorktree, String> {
+    let worktree = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        load_worktree(&conn, worktree_id)?
+    };
+    if worktree.status != "active" {
+        return Err(format!("Worktree {} is {}", worktree_id, worktree.status));
+    }
+
+    log::info!(
+        "Merging worktree branch {} into {}",
+        worktree.branch,
+        worktree.repo_path
+    );
+
+    let dir = Path::new(&worktree.worktree_path);
+    if !run_git(dir, &["status", "--porcelain"])?.trim().is_empty() {
+        let message = commit_message
+            .filter(|m| !m.trim().is_empty())
+            .unwrap_or_else(|| format!("Changes from Claude session on {}", worktree.branch));
+        run_git(dir, &["add", "--all"])?;
+        run_git(dir, &["commit", "-m", &message])?;
+    }
+
+    let range = format!("{}..{}", worktree.base_commit, worktree.branch);
+    let repo_root = Path::new(&worktree.repo_path);
+    if run_git(repo_root, &["rev-list", "--count", &range])?.trim() == "0" {
+        return Err("The worktree has no changes to merge".to_string());
+    }
+
+    let merge_message = format!("Merge branch '{}'", worktree.branch);
+    if let Err(e) = run_git(
+        repo_root,
+        &["merge", "--no-ff", "-m", &merge_message, &worktree.branch],
+    ) {
+        let _ = run_git(repo_root, &["merge", "--abort"]);
+        return Err(format!("Merge failed and was aborted: {}", e));
+    }
+
+    remove_worktree(&worktree, false)?;
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    set_worktree_status(&conn, worktree_id, "merged")?;
+    load_worktree(&conn, worktree_id)
+}
+
+/// Removes a worktree and deletes its branch, discarding all its changes
+#[tauri::command]
+pub async fn discard_session_worktree(
+    db: State<'_, AgentDb>,
+    worktree_id: i64,
+) -> Result<SessionWorktree, String> {
+    let worktree = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        load_worktree(&conn, worktree_id)?
+    };
+    if worktree.status != "active" {
+        return Err(format!("Worktree {} is {}", worktree_id, worktree.status));
+    }
+
+    log::info!(
+        "Discarding worktree {} (branch {})",
+        worktree.worktree_path,
+        worktree.branch
+    );
+    remove_worktree(&worktree, true)?;
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    set_worktree_status(&conn, worktree_id, "discarded")?;
+    load_worktree(&conn, worktree_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_diff_output() {
+        let files = parse_name_status("M\tsrc/main.rs\nA\tnew.txt\nR087\told.rs\tsrc/renamed.rs\n");
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[0].status, "modified");
+        assert_eq!(files[1].status, "added");
+        assert_eq!(files[2].path, "src/renamed.rs");
+        assert_eq!(files[2].old_path.as_deref(), Some("old.rs"));
+
+        let counts = parse_numstat(concat!(
+            "3\t1\tsrc/main.rs\0",
+            "-\t-\timage.png\0",
+            "2\t2\t\0old.rs\0src/renamed.rs\0",
+        ));
+        assert_eq!(counts["src/main.rs"], (Some(3), Some(1)));
+        assert_eq!(counts["image.png"], (None, None));
+        assert_eq!(counts["src/renamed.rs"], (Some(2), Some(2)));
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(
+            slugify("claudio/session-20240304"),
+            "claudio-session-20240304"
+        );
+        assert_eq!(slugify("My Repo!"), "my-repo");
+    }
+}
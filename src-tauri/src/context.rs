@@ -0,0 +1,131 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use crate::commands::capabilities::{ClaudeCapabilities, ClaudeCapabilitiesState};
+
+/// The app's bundle identifier, which names its data directory
+pub const APP_IDENTIFIER: &str = "claudio.oO.dev";
+
+/// Where commands find Claude Code's files, Claudio's data and the claude binary.
+/// Independent of a running app, so the same code serves the GUI, the CLI and tests.
+#[derive(Clone)]
+pub struct CoreContext {
+    /// Claude Code's config root, normally ~/.claude
+    pub claude_dir: PathBuf,
+    /// Claudio's data directory, holding agents.db with the stored binary path
+    pub app_data_dir: Option<PathBuf>,
+    /// Binary to use instead of searching for one
+    claude_binary: Option<String>,
+    capabilities: ClaudeCapabilitiesState,
+}
+
+impl CoreContext {
+    /// Creates a context over the given directories
+    pub fn new(claude_dir: impl Into<PathBuf>, app_data_dir: Option<PathBuf>) -> Self {
+        Self {
+            claude_dir: claude_dir.into(),
+            app_data_dir,
+            claude_binary: None,
+            capabilities: ClaudeCapabilitiesState::default(),
+        }
+    }
+
+    /// Creates a context over ~/.claude and the app's usual data directory,
+    /// for running without a window
+    pub fn from_home() -> Result<Self, String> {
+        let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
+        Ok(Self::new(
+            home_dir.join(".claude"),
+            dirs::data_dir().map(|dir| dir.join(APP_IDENTIFIER)),
+        ))
+    }
+
+    /// Creates the app's context, sharing its cache of probed binary capabilities.
+    /// Without a home directory it falls back to the one Tauri resolves, then to
+    /// the temp directory, so the app still starts.
+    pub fn for_app(app: &AppHandle) -> Self {
+        let mut context = Self::from_home().unwrap_or_else(|e| {
+            let home_dir = app
+                .path()
+                .home_dir()
+                .unwrap_or_else(|_| std::env::temp_dir());
+            log::error!("{}; looking for Claude's files in {:?}", e, home_dir);
+            Self::new(home_dir.join(".claude"), None)
+        });
+        context.app_data_dir = app.path().app_data_dir().ok();
+        if let Some(capabilities) = app.try_state::<ClaudeCapabilitiesState>() {
+            context.capabilities = capabilities.inner().clone();
+        }
+        context
+    }
+
+    /// Uses the given claude binary instead of searching for one
+    pub fn with_claude_binary(mut self, claude_path: impl Into<String>) -> Self {
+        self.claude_binary = Some(claude_path.into());
+        self
+    }
+
+    /// The home directory Claude Code's config root is in, which also holds ~/.claude.json
+    pub fn home_dir(&self) -> Option<&Path> {
+        self.claude_dir.parent()
+    }
+
+    /// ~/.claude/projects, holding one directory of session transcripts per project
+    pub fn projects_dir(&self) -> PathBuf {
+        self.claude_dir.join("projects")
+    }
+
+    /// The directory of a project's session transcripts
+    pub fn project_dir(&self, project_id: &str) -> PathBuf {
+        self.projects_dir().join(project_id)
+    }
+
+    /// A session's transcript within a project
+    pub fn session_path(&self, project_id: &str, session_id: &str) -> PathBuf {
+        self.project_dir(project_id)
+            .join(format!("{}.jsonl", session_id))
+    }
+
+    /// ~/.claude/agents, created if missing
+    pub fn agents_dir(&self) -> Result<PathBuf, String> {
+        let agents_dir = self.claude_dir.join("agents");
+        if !agents_dir.exists() {
+            fs::create_dir_all(&agents_dir)
+                .map_err(|e| format!("Failed to create agents directory: {}", e))?;
+        }
+        Ok(agents_dir)
+    }
+
+    /// ~/.claude/commands, holding user slash commands
+    pub fn commands_dir(&self) -> PathBuf {
+        self.claude_dir.join("commands")
+    }
+
+    /// ~/.claude/todos
+    pub fn todos_dir(&self) -> PathBuf {
+        self.claude_dir.join("todos")
+    }
+
+    /// Finds a session's transcript in any project
+    pub fn session_transcript(&self, session_id: &str) -> Option<PathBuf> {
+        fs::read_dir(self.projects_dir())
+            .ok()?
+            .flatten()
+            .map(|project| project.path().join(format!("{}.jsonl", session_id)))
+            .find(|path| path.is_file())
+    }
+
+    /// Resolves the claude binary: the configured one, else the stored or best installed one
+    pub fn claude_binary(&self) -> Result<String, String> {
+        match &self.claude_binary {
+            Some(claude_path) => Ok(claude_path.clone()),
+            None => crate::claude_binary::find_claude_binary_in(self.app_data_dir.as_deref()),
+        }
+    }
+
+    /// Gets what a claude binary supports, probing it once per change
+    pub fn claude_capabilities(&self, claude_path: &str) -> ClaudeCapabilities {
+        self.capabilities.get(claude_path)
+    }
+}
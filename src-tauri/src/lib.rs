@@ -3,7 +3,9 @@
 // Declare modules
 pub mod checkpoint;
 pub mod claude_binary;
+pub mod cli;
 pub mod commands;
+pub mod context;
 pub mod process;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -3,10 +3,13 @@
 
 mod checkpoint;
 mod claude_binary;
+mod cli;
 mod commands;
+mod context;
 mod process;
 
 use checkpoint::state::CheckpointState;
+use context::CoreContext;
 use commands::agents::{
     cleanup_finished_processes, create_agent, delete_agent, execute_agent, export_agent,
     export_agent_to_file, fetch_github_agent_content, fetch_github_agents, get_agent,
@@ -24,7 +27,7 @@ use commands::claude::{
     get_recently_modified_files, get_session_timeline, get_system_prompt, list_checkpoints,
     list_directory_contents, list_projects, list_running_claude_sessions, load_session_history,
     open_new_session, read_claude_md_file, restore_checkpoint, resume_claude_code,
-    save_claude_md_file, save_claude_settings, save_system_prompt, search_files,
+    save_claude_md_file, save_claude_settings, save_system_prompt,
     track_checkpoint_message, track_session_messages, update_checkpoint_settings,
     get_hooks_config, update_hooks_config, validate_hook_command,
     ClaudeProcessState,
@@ -43,7 +46,51 @@ use commands::storage::{
     storage_insert_row, storage_execute_sql, storage_reset_database,
 };
 use commands::proxy::{get_proxy_settings, save_proxy_settings, apply_proxy_settings, get_setting, save_setting};
-use process::ProcessRegistryState;
+use commands::external_sessions::{
+    list_external_claude_sessions, start_external_session_watcher, stop_external_session_watcher,
+    ExternalSessionWatcherState,
+};
+use commands::session_metadata::{
+    delete_session_metadata, get_session_metadata, list_session_tags, list_sessions_by_tag,
+    set_session_metadata,
+};
+use commands::scheduler::{
+    cancel_job, create_job, delete_job, list_job_runs, list_jobs, run_job_now, run_scheduler,
+    set_job_enabled, SchedulerState,
+};
+use commands::profiles::{
+    delete_execution_profile, get_project_execution_profile, list_execution_profiles,
+    save_execution_profile, set_default_execution_profile, set_project_execution_profile,
+};
+use commands::worktrees::{
+    create_session_worktree, discard_session_worktree, get_worktree_diff,
+    list_session_worktrees, merge_session_worktree,
+};
+use commands::files::{cancel_file_search, search_file_contents, search_files, FileSearchState};
+use commands::capabilities::{get_claude_capabilities, ClaudeCapabilitiesState};
+use commands::hook_activity::{get_hook_activity, HookActivityState};
+use commands::hooks::test_hook;
+use commands::replay::replay_session;
+use commands::session_changes::get_session_changes;
+use commands::process_monitor::{
+    get_monitoring_settings, get_process_stats, run_process_monitor, save_monitoring_settings,
+    ProcessMonitorState,
+};
+use commands::notifications::{
+    delete_webhook, get_notification_settings, list_webhooks, save_notification_settings,
+    save_webhook, test_webhook,
+};
+use commands::process_recovery::{
+    kill_orphaned_process, list_orphaned_processes, reattach_orphaned_process,
+};
+use commands::memory::resolve_claude_memory;
+use commands::settings::{get_effective_settings, validate_claude_settings};
+use commands::api_server::{
+    get_api_server_status, regenerate_api_server_token, set_api_server_enabled,
+    start_api_server_from_settings, ApiServerState,
+};
+use commands::todos::{get_outstanding_work, get_session_todos, list_todos};
+use process::{ProcessRegistryState, RegistryStore};
 use std::sync::Mutex;
 use tauri::Manager;
 
@@ -51,10 +98,17 @@ fn main() {
     // Initialize logger
     env_logger::init();
 
+    // Run headless CLI commands (`claudio agents list`, ...) without opening a window
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
+
 
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_notification::init())
         .setup(|app| {
             // Initialize agents database
             let conn = init_database(&app.handle()).expect("Failed to initialize agents database");
@@ -92,19 +146,17 @@ fn main() {
             let conn = init_database(&app.handle()).expect("Failed to initialize agents database");
             app.manage(AgentDb(Mutex::new(conn)));
 
+            // Initialize the cache of probed claude binary capabilities
+            app.manage(ClaudeCapabilitiesState::default());
+
+            // Initialize the paths and claude binary lookup shared by commands
+            let core_context = CoreContext::for_app(app.handle());
+
             // Initialize checkpoint state
             let checkpoint_state = CheckpointState::new();
 
             // Set the Claude directory path
-            if let Ok(claude_dir) = dirs::home_dir()
-                .ok_or_else(|| "Could not find home directory")
-                .and_then(|home| {
-                    let claude_path = home.join(".claude");
-                    claude_path
-                        .canonicalize()
-                        .map_err(|_| "Could not find ~/.claude directory")
-                })
-            {
+            if let Ok(claude_dir) = core_context.claude_dir.canonicalize() {
                 let state_clone = checkpoint_state.clone();
                 tauri::async_runtime::spawn(async move {
                     state_clone.set_claude_dir(claude_dir).await;
@@ -112,13 +164,39 @@ fn main() {
             }
 
             app.manage(checkpoint_state);
+            app.manage(core_context);
+
+            // Initialize process registry, saved to disk so runs outlive a restart
+            let app_data_dir = app.state::<CoreContext>().app_data_dir.clone();
+            app.manage(match app_data_dir {
+                Some(dir) => ProcessRegistryState::with_store(RegistryStore::new(dir.join("processes.json"))),
+                None => ProcessRegistryState::default(),
+            });
 
-            // Initialize process registry
-            app.manage(ProcessRegistryState::default());
+            // Initialize and start resource sampling of registered processes
+            app.manage(ProcessMonitorState::default());
+            tauri::async_runtime::spawn(run_process_monitor(app.handle().clone()));
 
             // Initialize Claude process state
             app.manage(ClaudeProcessState::default());
 
+            // Initialize watcher for sessions started outside Claudio
+            app.manage(ExternalSessionWatcherState::default());
+
+            // Initialize cancellation state for content searches
+            app.manage(FileSearchState::default());
+
+            // Initialize hook activity captured from runs
+            app.manage(HookActivityState::default());
+
+            // Initialize and start the job scheduler
+            app.manage(SchedulerState::default());
+            tauri::async_runtime::spawn(run_scheduler(app.handle().clone()));
+
+            // Initialize the local control API, started only if enabled in settings
+            app.manage(ApiServerState::default());
+            tauri::async_runtime::spawn(start_api_server_from_settings(app.handle().clone()));
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -129,9 +207,11 @@ fn main() {
             open_new_session,
             get_system_prompt,
             check_claude_version,
+            get_claude_capabilities,
             save_system_prompt,
             save_claude_settings,
             find_claude_md_files,
+            resolve_claude_memory,
             read_claude_md_file,
             save_claude_md_file,
             load_session_history,
@@ -143,10 +223,81 @@ fn main() {
             get_claude_session_output,
             list_directory_contents,
             search_files,
+            search_file_contents,
+            cancel_file_search,
             get_recently_modified_files,
+            get_effective_settings,
+            validate_claude_settings,
             get_hooks_config,
             update_hooks_config,
             validate_hook_command,
+            test_hook,
+            get_hook_activity,
+            
+            // Session Metadata
+            get_session_metadata,
+            set_session_metadata,
+            delete_session_metadata,
+            list_session_tags,
+            list_sessions_by_tag,
+            
+            // External Sessions
+            start_external_session_watcher,
+            stop_external_session_watcher,
+            list_external_claude_sessions,
+            
+            // Job Scheduler
+            create_job,
+            list_jobs,
+            list_job_runs,
+            delete_job,
+            set_job_enabled,
+            run_job_now,
+            cancel_job,
+            
+            // Execution Profiles
+            list_execution_profiles,
+            save_execution_profile,
+            delete_execution_profile,
+            set_default_execution_profile,
+            get_project_execution_profile,
+            set_project_execution_profile,
+            
+            // Session Worktrees
+            create_session_worktree,
+            list_session_worktrees,
+            get_worktree_diff,
+            merge_session_worktree,
+            discard_session_worktree,
+            
+            // Session Replay
+            replay_session,
+            
+            // Session Changes
+            get_session_changes,
+            
+            // Orphaned Runs
+            list_orphaned_processes,
+            kill_orphaned_process,
+            reattach_orphaned_process,
+            
+            // Process Monitoring
+            get_process_stats,
+            get_monitoring_settings,
+            save_monitoring_settings,
+            
+            // Notifications & Webhooks
+            get_notification_settings,
+            save_notification_settings,
+            list_webhooks,
+            save_webhook,
+            delete_webhook,
+            test_webhook,
+            
+            // Todos
+            list_todos,
+            get_session_todos,
+            get_outstanding_work,
             
             // Checkpoint Management
             create_checkpoint,
@@ -233,6 +384,11 @@ fn main() {
             get_proxy_settings,
             save_proxy_settings,
             
+            // Local Control API
+            get_api_server_status,
+            set_api_server_enabled,
+            regenerate_api_server_token,
+            
             // General Settings
             get_setting,
             save_setting,
@@ -1,3 +1,9 @@
+pub mod persistence;
 pub mod registry;
+pub mod stats;
+pub mod termination;
 
+pub use persistence::*;
 pub use registry::*;
+pub use stats::*;
+pub use termination::*;
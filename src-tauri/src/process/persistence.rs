@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::registry::ProcessInfo;
+
+/// What identifies a process beyond its PID, which the OS may hand to
+/// another process once it exits
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessIdentity {
+    /// The process's start time as the OS reports it: clock ticks since boot
+    /// on Linux, the `ps` start date elsewhere
+    pub start_time: String,
+    pub command_line: Vec<String>,
+}
+
+impl ProcessIdentity {
+    /// Reads the identity of a running process, or `None` if it isn't running
+    /// or the platform can't tell
+    pub fn of(pid: u32) -> Option<Self> {
+        Some(Self {
+            start_time: process_start_time(pid)?,
+            command_line: process_command_line(pid).unwrap_or_default(),
+        })
+    }
+
+    /// Whether `pid` is still the process this identity was read from
+    pub fn matches(&self, pid: u32) -> bool {
+        process_start_time(pid).is_some_and(|start_time| start_time == self.start_time)
+    }
+}
+
+/// A registry entry as saved to disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistedProcess {
+    #[serde(flatten)]
+    pub info: ProcessInfo,
+    /// Missing when the identity couldn't be read, in which case the entry
+    /// can't be trusted after a restart
+    pub identity: Option<ProcessIdentity>,
+}
+
+/// The file the registry is saved to, so runs outlive a crash or restart of Claudio
+#[derive(Debug, Clone)]
+pub struct RegistryStore {
+    path: PathBuf,
+}
+
+impl RegistryStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Loads the saved entries; a missing or unreadable file means none
+    pub fn load(&self) -> Vec<PersistedProcess> {
+        let Ok(content) = fs::read_to_string(&self.path) else {
+            return Vec::new();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            log::warn!(
+                "Ignoring unreadable process registry {:?}: {}",
+                self.path,
+                e
+            );
+            Vec::new()
+        })
+    }
+
+    /// Replaces the saved entries, writing through a temporary file so a crash
+    /// mid-write can't leave a truncated registry behind. Callers serialize their
+    /// saves; the temporary file is named after this process so another instance
+    /// of Claudio can't write into it.
+    pub fn save(&self, entries: &[PersistedProcess]) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+        }
+        let content = serde_json::to_string_pretty(entries)
+            .map_err(|e| format!("Failed to serialize process registry: {}", e))?;
+        let temp_path = self
+            .path
+            .with_extension(format!("json.{}.tmp", std::process::id()));
+        fs::write(&temp_path, content)
+            .map_err(|e| format!("Failed to write {:?}: {}", temp_path, e))?;
+        fs::rename(&temp_path, &self.path)
+            .map_err(|e| format!("Failed to replace {:?}: {}", self.path, e))
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn process_start_time(pid: u32) -> Option<String> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    parse_start_time(&stat)
+}
+
+#[cfg(target_os = "linux")]
+fn process_command_line(pid: u32) -> Option<Vec<String>> {
+    let cmdline = fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    Some(
+        cmdline
+            .split(|&b| b == 0)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| String::from_utf8_lossy(arg).to_string())
+            .collect(),
+    )
+}
+
+/// The start time field of /proc/<pid>/stat, skipping zombies, which have
+/// exited and only wait to be reaped
+#[cfg(any(target_os = "linux", test))]
+fn parse_start_time(stat: &str) -> Option<String> {
+    // The command name may contain spaces and parentheses; fields follow the last ')'
+    let fields: Vec<&str> = stat[stat.rfind(')')? + 1..].split_whitespace().collect();
+    if *fields.first()? == "Z" {
+        return None;
+    }
+    // starttime is field 22 of the whole line, the 20th after the command name
+    fields.get(19).map(|start| start.to_string())
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn process_start_time(pid: u32) -> Option<String> {
+    ps_field(pid, "lstart=")
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn process_command_line(pid: u32) -> Option<Vec<String>> {
+    ps_field(pid, "command=").map(|command| {
+        command
+            .split_whitespace()
+            .map(|arg| arg.to_string())
+            .collect()
+    })
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn ps_field(pid: u32, field: &str) -> Option<String> {
+    let output = std::process::Command::new("ps")
+        .args(["-o", field, "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !value.is_empty()).then_some(value)
+}
+
+/// Windows exposes no start time without extra APIs, so entries there can't be
+/// verified and aren't recovered
+#[cfg(windows)]
+fn process_start_time(_pid: u32) -> Option<String> {
+    None
+}
+
+#[cfg(windows)]
+fn process_command_line(_pid: u32) -> Option<Vec<String>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::ProcessType;
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_start_time() {
+        let stat =
+            "4242 (node (dev)) S 1 4242 4242 0 -1 4194560 0 0 0 0 0 0 0 0 20 0 1 0 987654 1000";
+        assert_eq!(parse_start_time(stat).as_deref(), Some("987654"));
+        let zombie = "4242 (node) Z 1 4242 4242 0 -1 4194560 0 0 0 0 0 0 0 0 20 0 1 0 987654 0";
+        assert_eq!(parse_start_time(zombie), None);
+    }
+
+    #[test]
+    fn test_store_round_trip_and_identity() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = RegistryStore::new(temp_dir.path().join("data/processes.json"));
+        assert!(store.load().is_empty());
+
+        let pid = std::process::id();
+        let entry = PersistedProcess {
+            info: ProcessInfo {
+                run_id: 1000000,
+                process_type: ProcessType::ClaudeSession {
+                    session_id: "session-1".to_string(),
+                },
+                pid,
+                started_at: Utc::now(),
+                project_path: "/work/app".to_string(),
+                task: "Add a login form".to_string(),
+                model: "sonnet".to_string(),
+            },
+            identity: ProcessIdentity::of(pid),
+        };
+        store.save(&[entry]).unwrap();
+
+        let loaded = store.load();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].info.task, "Add a login form");
+        if cfg!(unix) {
+            let identity = loaded[0].identity.as_ref().unwrap();
+            assert!(identity.matches(pid));
+            let reused = ProcessIdentity {
+                start_time: "0".to_string(),
+                ..identity.clone()
+            };
+            assert!(!reused.matches(pid));
+        }
+    }
+}
@@ -1,9 +1,14 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 use tokio::process::Child;
 
+use super::persistence::{PersistedProcess, ProcessIdentity, RegistryStore};
+use super::termination::{
+    terminate_process, TerminationReport, TerminationStage, TerminationTimeouts,
+};
+
 /// Type of process being tracked
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ProcessType {
@@ -34,12 +39,22 @@ pub struct ProcessHandle {
     pub info: ProcessInfo,
     pub child: Arc<Mutex<Option<Child>>>,
     pub live_output: Arc<Mutex<String>>,
+    /// Read at registration, to tell the process apart from a later one with its PID
+    pub identity: Option<ProcessIdentity>,
 }
 
 /// Registry for tracking active agent processes
 pub struct ProcessRegistry {
     processes: Arc<Mutex<HashMap<i64, ProcessHandle>>>, // run_id -> ProcessHandle
     next_id: Arc<Mutex<i64>>, // Auto-incrementing ID for non-agent processes
+    /// Runs left by a previous launch that are still running, run_id -> entry
+    orphans: Arc<Mutex<HashMap<i64, PersistedProcess>>>,
+    store: Option<RegistryStore>,
+    /// Runs stopped through `kill_process`, until whoever waits on them asks
+    cancelled: Mutex<HashSet<i64>>,
+    /// Held from snapshot to save, so concurrent saves can't interleave or write
+    /// an older snapshot over a newer one
+    save_lock: Mutex<()>,
 }
 
 impl ProcessRegistry {
@@ -47,6 +62,76 @@ impl ProcessRegistry {
         Self {
             processes: Arc::new(Mutex::new(HashMap::new())),
             next_id: Arc::new(Mutex::new(1000000)), // Start at high number to avoid conflicts
+            orphans: Arc::new(Mutex::new(HashMap::new())),
+            store: None,
+            cancelled: Mutex::new(HashSet::new()),
+            save_lock: Mutex::new(()),
+        }
+    }
+
+    /// Creates a registry saved to `store`, recovering the runs of a previous
+    /// launch that are still running as orphans. Entries whose PID is gone or
+    /// now belongs to another process (checked by start time) are dropped.
+    pub fn with_store(store: RegistryStore) -> Self {
+        let mut orphans = HashMap::new();
+        for entry in store.load() {
+            let alive = entry
+                .identity
+                .as_ref()
+                .is_some_and(|identity| identity.matches(entry.info.pid));
+            if alive {
+                log::info!(
+                    "Found orphaned run {} (PID: {}) from a previous launch",
+                    entry.info.run_id, entry.info.pid
+                );
+                orphans.insert(entry.info.run_id, entry);
+            } else {
+                log::info!(
+                    "Dropping run {} (PID: {}) from a previous launch: no longer running",
+                    entry.info.run_id, entry.info.pid
+                );
+            }
+        }
+
+        // Keep new IDs clear of the recovered ones
+        let next_id = orphans.keys().map(|run_id| run_id + 1).max().unwrap_or(0).max(1000000);
+
+        let registry = Self {
+            processes: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(Mutex::new(next_id)),
+            orphans: Arc::new(Mutex::new(orphans)),
+            store: Some(store),
+            cancelled: Mutex::new(HashSet::new()),
+            save_lock: Mutex::new(()),
+        };
+        registry.persist();
+        registry
+    }
+
+    /// Saves the registered processes and the remaining orphans
+    fn persist(&self) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        let Ok(_saving) = self.save_lock.lock() else {
+            return;
+        };
+        let mut entries: Vec<PersistedProcess> = match self.processes.lock() {
+            Ok(processes) => processes
+                .values()
+                .map(|handle| PersistedProcess {
+                    info: handle.info.clone(),
+                    identity: handle.identity.clone(),
+                })
+                .collect(),
+            Err(_) => return,
+        };
+        if let Ok(orphans) = self.orphans.lock() {
+            entries.extend(orphans.values().cloned());
+        }
+        entries.sort_by_key(|entry| entry.info.run_id);
+        if let Err(e) = store.save(&entries) {
+            log::warn!("Failed to save process registry to {:?}: {}", store.path(), e);
         }
     }
 
@@ -111,9 +196,12 @@ impl ProcessRegistry {
             info: process_info,
             child: Arc::new(Mutex::new(None)), // No tokio::process::Child handle for sidecar
             live_output: Arc::new(Mutex::new(String::new())),
+            identity: ProcessIdentity::of(pid),
         };
 
         processes.insert(run_id, process_handle);
+        drop(processes);
+        self.persist();
         Ok(())
     }
 
@@ -145,9 +233,12 @@ impl ProcessRegistry {
             info: process_info,
             child: Arc::new(Mutex::new(None)), // No child handle for Claude sessions
             live_output: Arc::new(Mutex::new(String::new())),
+            identity: ProcessIdentity::of(pid),
         };
 
         processes.insert(run_id, process_handle);
+        drop(processes);
+        self.persist();
         Ok(run_id)
     }
 
@@ -158,15 +249,19 @@ impl ProcessRegistry {
         process_info: ProcessInfo,
         child: Child,
     ) -> Result<(), String> {
+        let pid = process_info.pid;
         let mut processes = self.processes.lock().map_err(|e| e.to_string())?;
 
         let process_handle = ProcessHandle {
             info: process_info,
             child: Arc::new(Mutex::new(Some(child))),
             live_output: Arc::new(Mutex::new(String::new())),
+            identity: ProcessIdentity::of(pid),
         };
 
         processes.insert(run_id, process_handle);
+        drop(processes);
+        self.persist();
         Ok(())
     }
 
@@ -203,9 +298,69 @@ impl ProcessRegistry {
     pub fn unregister_process(&self, run_id: i64) -> Result<(), String> {
         let mut processes = self.processes.lock().map_err(|e| e.to_string())?;
         processes.remove(&run_id);
+        drop(processes);
+        self.persist();
         Ok(())
     }
 
+    /// Command lines of the registered and orphaned processes, as read when
+    /// they were registered
+    pub fn command_lines(&self) -> Vec<Vec<String>> {
+        let mut command_lines: Vec<Vec<String>> = match self.processes.lock() {
+            Ok(processes) => processes
+                .values()
+                .filter_map(|handle| Some(handle.identity.as_ref()?.command_line.clone()))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        if let Ok(orphans) = self.orphans.lock() {
+            command_lines.extend(
+                orphans
+                    .values()
+                    .filter_map(|orphan| Some(orphan.identity.as_ref()?.command_line.clone())),
+            );
+        }
+        command_lines
+    }
+
+    /// Get the runs left by a previous launch that are still running
+    pub fn get_orphaned_processes(&self) -> Result<Vec<PersistedProcess>, String> {
+        let orphans = self.orphans.lock().map_err(|e| e.to_string())?;
+        let mut list: Vec<PersistedProcess> = orphans.values().cloned().collect();
+        list.sort_by_key(|entry| entry.info.run_id);
+        Ok(list)
+    }
+
+    /// Forget an orphaned run, returning its entry
+    pub fn take_orphan(&self, run_id: i64) -> Result<Option<PersistedProcess>, String> {
+        let orphan = self.orphans.lock().map_err(|e| e.to_string())?.remove(&run_id);
+        if orphan.is_some() {
+            self.persist();
+        }
+        Ok(orphan)
+    }
+
+    /// Move an orphaned run back among the registered processes, so it is
+    /// listed and cancelled like a run of this launch
+    pub fn adopt_orphan(&self, run_id: i64) -> Result<Option<ProcessInfo>, String> {
+        let Some(orphan) = self.orphans.lock().map_err(|e| e.to_string())?.remove(&run_id) else {
+            return Ok(None);
+        };
+        let info = orphan.info.clone();
+        let process_handle = ProcessHandle {
+            info: orphan.info,
+            child: Arc::new(Mutex::new(None)), // Spawned by a previous launch
+            live_output: Arc::new(Mutex::new(String::new())),
+            identity: orphan.identity,
+        };
+        self.processes
+            .lock()
+            .map_err(|e| e.to_string())?
+            .insert(run_id, process_handle);
+        self.persist();
+        Ok(Some(info))
+    }
+
     /// Get all running processes
     #[allow(dead_code)]
     pub fn get_running_processes(&self) -> Result<Vec<ProcessInfo>, String> {
@@ -237,193 +392,73 @@ impl ProcessRegistry {
         Ok(processes.get(&run_id).map(|handle| handle.info.clone()))
     }
 
-    /// Kill a running process with proper cleanup
-    pub async fn kill_process(&self, run_id: i64) -> Result<bool, String> {
-        use log::{error, info, warn};
+    /// Whether a run was stopped through `kill_process`, forgetting it once asked
+    pub fn take_cancelled(&self, run_id: i64) -> bool {
+        self.cancelled
+            .lock()
+            .map(|mut cancelled| cancelled.remove(&run_id))
+            .unwrap_or(false)
+    }
+
+    /// Stops a running process and the processes it spawned: SIGINT, then
+    /// SIGTERM, then SIGKILL to its process group. Returns `None` if the
+    /// process isn't registered.
+    pub async fn kill_process(
+        &self,
+        run_id: i64,
+        timeouts: TerminationTimeouts,
+    ) -> Result<Option<TerminationReport>, String> {
+        use log::{info, warn};
 
         // First check if the process exists and get its PID
-        let (pid, child_arc) = {
+        let (pid, child_arc, identity) = {
             let processes = self.processes.lock().map_err(|e| e.to_string())?;
             if let Some(handle) = processes.get(&run_id) {
-                (handle.info.pid, handle.child.clone())
+                (handle.info.pid, handle.child.clone(), handle.identity.clone())
             } else {
                 warn!("Process {} not found in registry", run_id);
-                return Ok(false); // Process not found
+                return Ok(None); // Process not found
             }
         };
 
+        if let Ok(mut cancelled) = self.cancelled.lock() {
+            cancelled.insert(run_id);
+        }
+
+        // Never signal a process that has since been given the run's PID
+        if identity.is_some_and(|identity| !identity.matches(pid)) {
+            info!("Process {} (PID: {}) has already exited", run_id, pid);
+            self.unregister_process(run_id)?;
+            return Ok(Some(TerminationReport {
+                pid,
+                process_group: false,
+                stage: TerminationStage::NotRunning,
+                elapsed_ms: 0,
+            }));
+        }
+
         info!(
             "Attempting graceful shutdown of process {} (PID: {})",
             run_id, pid
         );
 
-        // Send kill signal to the process
-        let kill_sent = {
-            let mut child_guard = child_arc.lock().map_err(|e| e.to_string())?;
-            if let Some(child) = child_guard.as_mut() {
-                match child.start_kill() {
-                    Ok(_) => {
-                        info!("Successfully sent kill signal to process {}", run_id);
-                        true
-                    }
-                    Err(e) => {
-                        error!("Failed to send kill signal to process {}: {}", run_id, e);
-                        // Don't return error here, try fallback method
-                        false
-                    }
-                }
-            } else {
-                warn!("No child handle available for process {} (PID: {}), attempting system kill", run_id, pid);
-                false // Process handle not available, try fallback
-            }
-        };
+        let report = tokio::task::spawn_blocking(move || terminate_process(pid, &timeouts))
+            .await
+            .map_err(|e| format!("Failed to stop process {}: {}", run_id, e))??;
 
-        // If direct kill didn't work, try system command as fallback
-        if !kill_sent {
-            info!("Attempting fallback kill for process {} (PID: {})", run_id, pid);
-            match self.kill_process_by_pid(run_id, pid) {
-                Ok(true) => return Ok(true),
-                Ok(false) => warn!("Fallback kill also failed for process {} (PID: {})", run_id, pid),
-                Err(e) => error!("Error during fallback kill: {}", e),
-            }
-            // Continue with the rest of the cleanup even if fallback failed
-        }
-
-        // Wait for the process to exit (with timeout)
-        let wait_result = tokio::time::timeout(tokio::time::Duration::from_secs(5), async {
-            loop {
-                // Check if process has exited
-                let status = {
-                    let mut child_guard = child_arc.lock().map_err(|e| e.to_string())?;
-                    if let Some(child) = child_guard.as_mut() {
-                        match child.try_wait() {
-                            Ok(Some(status)) => {
-                                info!("Process {} exited with status: {:?}", run_id, status);
-                                *child_guard = None; // Clear the child handle
-                                Some(Ok::<(), String>(()))
-                            }
-                            Ok(None) => {
-                                // Still running
-                                None
-                            }
-                            Err(e) => {
-                                error!("Error checking process status: {}", e);
-                                Some(Err(e.to_string()))
-                            }
-                        }
-                    } else {
-                        // Process already gone
-                        Some(Ok(()))
-                    }
-                };
-
-                match status {
-                    Some(result) => return result,
-                    None => {
-                        // Still running, wait a bit
-                        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-                    }
-                }
-            }
-        })
-        .await;
-
-        match wait_result {
-            Ok(Ok(_)) => {
-                info!("Process {} exited gracefully", run_id);
-            }
-            Ok(Err(e)) => {
-                error!("Error waiting for process {}: {}", run_id, e);
-            }
-            Err(_) => {
-                warn!("Process {} didn't exit within 5 seconds after kill", run_id);
-                // Force clear the handle
-                if let Ok(mut child_guard) = child_arc.lock() {
-                    *child_guard = None;
+        // Reap the child if we own it
+        if report.stopped() {
+            if let Ok(mut child_guard) = child_arc.lock() {
+                if let Some(child) = child_guard.as_mut() {
+                    let _ = child.try_wait();
                 }
-                // One more attempt with system kill
-                let _ = self.kill_process_by_pid(run_id, pid);
+                *child_guard = None;
             }
+            // Remove from registry after killing
+            self.unregister_process(run_id)?;
         }
 
-        // Remove from registry after killing
-        self.unregister_process(run_id)?;
-
-        Ok(true)
-    }
-
-    /// Kill a process by PID using system commands (fallback method)
-    pub fn kill_process_by_pid(&self, run_id: i64, pid: u32) -> Result<bool, String> {
-        use log::{error, info, warn};
-
-        info!("Attempting to kill process {} by PID {}", run_id, pid);
-
-        let kill_result = if cfg!(target_os = "windows") {
-            std::process::Command::new("taskkill")
-                .args(["/F", "/PID", &pid.to_string()])
-                .output()
-        } else {
-            // First try SIGTERM
-            let term_result = std::process::Command::new("kill")
-                .args(["-TERM", &pid.to_string()])
-                .output();
-
-            match &term_result {
-                Ok(output) if output.status.success() => {
-                    info!("Sent SIGTERM to PID {}", pid);
-                    // Give it 2 seconds to exit gracefully
-                    std::thread::sleep(std::time::Duration::from_secs(2));
-
-                    // Check if still running
-                    let check_result = std::process::Command::new("kill")
-                        .args(["-0", &pid.to_string()])
-                        .output();
-
-                    if let Ok(output) = check_result {
-                        if output.status.success() {
-                            // Still running, send SIGKILL
-                            warn!(
-                                "Process {} still running after SIGTERM, sending SIGKILL",
-                                pid
-                            );
-                            std::process::Command::new("kill")
-                                .args(["-KILL", &pid.to_string()])
-                                .output()
-                        } else {
-                            term_result
-                        }
-                    } else {
-                        term_result
-                    }
-                }
-                _ => {
-                    // SIGTERM failed, try SIGKILL directly
-                    warn!("SIGTERM failed for PID {}, trying SIGKILL", pid);
-                    std::process::Command::new("kill")
-                        .args(["-KILL", &pid.to_string()])
-                        .output()
-                }
-            }
-        };
-
-        match kill_result {
-            Ok(output) => {
-                if output.status.success() {
-                    info!("Successfully killed process with PID {}", pid);
-                    // Remove from registry
-                    self.unregister_process(run_id)?;
-                    Ok(true)
-                } else {
-                    let error_msg = String::from_utf8_lossy(&output.stderr);
-                    warn!("Failed to kill PID {}: {}", pid, error_msg);
-                    Ok(false)
-                }
-            }
-            Err(e) => {
-                error!("Failed to execute kill command for PID {}: {}", pid, e);
-                Err(format!("Failed to execute kill command: {}", e))
-            }
-        }
+        Ok(Some(report))
     }
 
     /// Check if a process is still running by trying to get its status
@@ -523,6 +558,13 @@ impl Default for ProcessRegistry {
 /// Global process registry state
 pub struct ProcessRegistryState(pub Arc<ProcessRegistry>);
 
+impl ProcessRegistryState {
+    /// Creates the state over a registry saved to `store`
+    pub fn with_store(store: RegistryStore) -> Self {
+        Self(Arc::new(ProcessRegistry::with_store(store)))
+    }
+}
+
 impl Default for ProcessRegistryState {
     fn default() -> Self {
         Self(Arc::new(ProcessRegistry::new()))
@@ -0,0 +1,167 @@
+/// Resource usage of a run's process tree at one point in time
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TreeUsage {
+    /// CPU time used so far by the live processes, in clock ticks
+    pub cpu_ticks: u64,
+    pub rss_bytes: u64,
+    pub open_fds: u64,
+    /// Processes in the tree besides the run itself
+    pub child_processes: u32,
+}
+
+/// Clock ticks per second, the unit of `TreeUsage::cpu_ticks`
+#[cfg(unix)]
+pub fn clock_ticks_per_second() -> u64 {
+    match unsafe { libc::sysconf(libc::_SC_CLK_TCK) } {
+        ticks if ticks > 0 => ticks as u64,
+        _ => 100,
+    }
+}
+
+#[cfg(not(unix))]
+pub fn clock_ticks_per_second() -> u64 {
+    100
+}
+
+/// The fields of /proc/<pid>/stat used for sampling
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ProcStat {
+    pid: u32,
+    state: char,
+    parent: u32,
+    group: u32,
+    cpu_ticks: u64,
+    rss_pages: u64,
+}
+
+/// Samples a process together with its descendants and, if it leads its own
+/// group, everything in the group (which catches children that were
+/// reparented after their parent exited). `None` if the process is gone or
+/// /proc isn't available.
+#[cfg(target_os = "linux")]
+pub fn sample_process_tree(pid: u32) -> Option<TreeUsage> {
+    let stats: Vec<ProcStat> = std::fs::read_dir("/proc")
+        .ok()?
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .filter_map(|pid| {
+            let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+            parse_stat(pid, &stat)
+        })
+        .collect();
+
+    let page_size = match unsafe { libc::sysconf(libc::_SC_PAGESIZE) } {
+        size if size > 0 => size as u64,
+        _ => 4096,
+    };
+    let tree = process_tree(pid, &stats)?;
+    let mut usage = TreeUsage {
+        child_processes: tree.len().saturating_sub(1) as u32,
+        ..TreeUsage::default()
+    };
+    for stat in tree {
+        usage.cpu_ticks += stat.cpu_ticks;
+        usage.rss_bytes += stat.rss_pages * page_size;
+        usage.open_fds += std::fs::read_dir(format!("/proc/{}/fd", stat.pid))
+            .map(|fds| fds.count() as u64)
+            .unwrap_or(0);
+    }
+    Some(usage)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn sample_process_tree(_pid: u32) -> Option<TreeUsage> {
+    None
+}
+
+/// The live processes belonging to the run `pid`, itself first
+#[cfg(any(target_os = "linux", test))]
+fn process_tree(pid: u32, stats: &[ProcStat]) -> Option<Vec<ProcStat>> {
+    let live: Vec<&ProcStat> = stats.iter().filter(|stat| stat.state != 'Z').collect();
+    let root = **live.iter().find(|stat| stat.pid == pid)?;
+
+    let mut tree = vec![root];
+    let mut members: std::collections::HashSet<u32> = [pid].into();
+    // Add group members and children until nothing new turns up
+    loop {
+        let before = tree.len();
+        for stat in &live {
+            let belongs =
+                (root.group == pid && stat.group == pid) || members.contains(&stat.parent);
+            if belongs && members.insert(stat.pid) {
+                tree.push(**stat);
+            }
+        }
+        if tree.len() == before {
+            return Some(tree);
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", test))]
+fn parse_stat(pid: u32, stat: &str) -> Option<ProcStat> {
+    // The command name may contain spaces and parentheses; fields follow the last ')'
+    let fields: Vec<&str> = stat[stat.rfind(')')? + 1..].split_whitespace().collect();
+    let field = |index: usize| fields.get(index)?.parse::<u64>().ok();
+    Some(ProcStat {
+        pid,
+        state: fields.first()?.chars().next()?,
+        parent: field(1)? as u32,
+        group: field(2)? as u32,
+        // utime + stime, fields 14 and 15 of the whole line
+        cpu_ticks: field(11)? + field(12)?,
+        // rss, field 24
+        rss_pages: field(21)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stat(pid: u32, state: char, parent: u32, group: u32) -> ProcStat {
+        ProcStat {
+            pid,
+            state,
+            parent,
+            group,
+            cpu_ticks: 10,
+            rss_pages: 1,
+        }
+    }
+
+    #[test]
+    fn test_parse_stat() {
+        let line = "4242 (node (dev)) S 4200 4100 4100 0 -1 4194560 0 0 0 0 150 25 0 0 20 0 1 0 987654 1000 3072";
+        assert_eq!(
+            parse_stat(4242, line),
+            Some(ProcStat {
+                pid: 4242,
+                state: 'S',
+                parent: 4200,
+                group: 4100,
+                cpu_ticks: 175,
+                rss_pages: 3072,
+            })
+        );
+        assert_eq!(parse_stat(1, "garbage"), None);
+    }
+
+    #[test]
+    fn test_process_tree_follows_children_and_group() {
+        let stats = [
+            stat(100, 'S', 1, 100),   // the run, leading its group
+            stat(101, 'R', 100, 100), // its test runner
+            stat(102, 'S', 101, 102), // a worker that started its own group
+            stat(103, 'S', 1, 100),   // a dev server reparented to init
+            stat(104, 'Z', 100, 100), // exited, not yet reaped
+            stat(200, 'S', 1, 200),   // unrelated
+        ];
+        let tree = process_tree(100, &stats).unwrap();
+        let mut pids: Vec<u32> = tree.iter().map(|stat| stat.pid).collect();
+        pids.sort();
+        assert_eq!(pids, [100, 101, 102, 103]);
+        assert_eq!(tree[0].pid, 100);
+        assert!(process_tree(300, &stats).is_none());
+    }
+}
@@ -0,0 +1,318 @@
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// How often a stopping process is checked for having exited
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long to wait after each signal before escalating to the next one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TerminationTimeouts {
+    /// Wait after SIGINT, which lets the CLI clean up like Ctrl+C does
+    pub interrupt: Duration,
+    /// Wait after SIGTERM, before SIGKILL
+    pub terminate: Duration,
+}
+
+impl Default for TerminationTimeouts {
+    fn default() -> Self {
+        Self {
+            interrupt: Duration::from_secs(5),
+            terminate: Duration::from_secs(5),
+        }
+    }
+}
+
+/// The signal a process exited at
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TerminationStage {
+    /// It had already exited
+    NotRunning,
+    Interrupt,
+    Terminate,
+    Kill,
+    /// It was still running after SIGKILL
+    Failed,
+}
+
+/// How stopping a process went
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminationReport {
+    pub pid: u32,
+    /// Whether the process led its own group, which was signalled as a whole
+    pub process_group: bool,
+    pub stage: TerminationStage,
+    pub elapsed_ms: u64,
+}
+
+impl TerminationReport {
+    /// Whether the process is gone
+    pub fn stopped(&self) -> bool {
+        self.stage != TerminationStage::Failed
+    }
+}
+
+/// Makes a command start in a process group of its own, so it can be stopped
+/// together with the processes it spawns (test runners, dev servers, MCP servers)
+pub fn spawn_in_process_group(cmd: &mut tokio::process::Command) {
+    #[cfg(unix)]
+    cmd.process_group(0);
+    #[cfg(windows)]
+    {
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        cmd.creation_flags(CREATE_NEW_PROCESS_GROUP);
+    }
+}
+
+/// Stops a process and, if it leads its own group, everything in the group:
+/// SIGINT, then SIGTERM, then SIGKILL, each after the previous one's timeout.
+/// On Windows the process tree is ended with taskkill.
+///
+/// Blocks while waiting for the process to exit; call it from a blocking task
+/// in async code.
+pub fn terminate_process(
+    pid: u32,
+    timeouts: &TerminationTimeouts,
+) -> Result<TerminationReport, String> {
+    let started = Instant::now();
+    let process = Target::new(pid)?;
+
+    let stage = if !process.is_running() {
+        TerminationStage::NotRunning
+    } else {
+        let stages = [
+            (TerminationStage::Interrupt, timeouts.interrupt),
+            (TerminationStage::Terminate, timeouts.terminate),
+            (TerminationStage::Kill, timeouts.terminate),
+        ];
+        let mut exited_at = TerminationStage::Failed;
+        for (stage, timeout) in stages {
+            info!("Sending {:?} to {}", stage, process.describe());
+            if let Err(e) = process.signal(stage) {
+                warn!("Failed to signal {}: {}", process.describe(), e);
+            }
+            if process.wait_for_exit(timeout) {
+                exited_at = stage;
+                break;
+            }
+        }
+        exited_at
+    };
+
+    let report = TerminationReport {
+        pid,
+        process_group: process.group,
+        stage,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    };
+    match stage {
+        TerminationStage::Failed => warn!("{} is still running after SIGKILL", process.describe()),
+        _ => info!("{} stopped at {:?}", process.describe(), stage),
+    }
+    Ok(report)
+}
+
+/// A process, or the process group it leads
+struct Target {
+    pid: u32,
+    group: bool,
+}
+
+impl Target {
+    #[cfg(unix)]
+    fn new(pid: u32) -> Result<Self, String> {
+        if pid == 0 || pid > i32::MAX as u32 {
+            return Err(format!("Invalid PID: {}", pid));
+        }
+        // Only signal the group if the process leads it, so Claudio's own group
+        // is never hit by runs spawned without one
+        let group = unsafe { libc::getpgid(pid as libc::pid_t) } == pid as libc::pid_t;
+        Ok(Self { pid, group })
+    }
+
+    #[cfg(windows)]
+    fn new(pid: u32) -> Result<Self, String> {
+        Ok(Self { pid, group: true })
+    }
+
+    fn describe(&self) -> String {
+        if self.group {
+            format!("process group {}", self.pid)
+        } else {
+            format!("process {}", self.pid)
+        }
+    }
+
+    fn wait_for_exit(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if !self.is_running() {
+                return true;
+            }
+            if Instant::now() >= deadline {
+                return false;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    #[cfg(unix)]
+    fn signal(&self, stage: TerminationStage) -> Result<(), String> {
+        let signal = match stage {
+            TerminationStage::Interrupt => libc::SIGINT,
+            TerminationStage::Terminate => libc::SIGTERM,
+            _ => libc::SIGKILL,
+        };
+        let target = if self.group {
+            -(self.pid as libc::pid_t)
+        } else {
+            self.pid as libc::pid_t
+        };
+        if unsafe { libc::kill(target, signal) } == 0 {
+            Ok(())
+        } else {
+            Err(std::io::Error::last_os_error().to_string())
+        }
+    }
+
+    /// Whether the process, or any process of its group, is still running.
+    /// Zombies count as exited: they're gone and only wait to be reaped.
+    #[cfg(target_os = "linux")]
+    fn is_running(&self) -> bool {
+        let Ok(entries) = std::fs::read_dir("/proc") else {
+            return unsafe { libc::kill(self.pid as libc::pid_t, 0) } == 0;
+        };
+        if !self.group {
+            return proc_status(self.pid).is_some_and(|(state, _)| state != 'Z');
+        }
+        entries
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+            .filter_map(proc_status)
+            .any(|(state, group)| group == self.pid && state != 'Z')
+    }
+
+    /// Whether the process, or any process of its group, is still running.
+    /// Zombies count as exited, as on Linux: `kill(pid, 0)` succeeds for a
+    /// leader that exited but hasn't been reaped by whoever holds its `Child`.
+    #[cfg(all(unix, not(target_os = "linux")))]
+    fn is_running(&self) -> bool {
+        let Ok(output) = std::process::Command::new("ps")
+            .args(["-A", "-o", "pid=,pgid=,stat="])
+            .output()
+        else {
+            let target = if self.group {
+                -(self.pid as libc::pid_t)
+            } else {
+                self.pid as libc::pid_t
+            };
+            return unsafe { libc::kill(target, 0) == 0 };
+        };
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(parse_ps_line)
+            .any(|(pid, group, state)| {
+                let member = if self.group {
+                    group == self.pid
+                } else {
+                    pid == self.pid
+                };
+                member && state != 'Z'
+            })
+    }
+
+    /// Windows has no signals for console processes started without a
+    /// console; ask the tree to close, then force it
+    #[cfg(windows)]
+    fn signal(&self, stage: TerminationStage) -> Result<(), String> {
+        let pid = self.pid.to_string();
+        let mut args = vec!["/T", "/PID", pid.as_str()];
+        if stage != TerminationStage::Interrupt {
+            args.insert(0, "/F");
+        }
+        std::process::Command::new("taskkill")
+            .args(&args)
+            .output()
+            .map(|_| ())
+            .map_err(|e| format!("Failed to run taskkill: {}", e))
+    }
+
+    #[cfg(windows)]
+    fn is_running(&self) -> bool {
+        std::process::Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", self.pid), "/NH"])
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).contains(&self.pid.to_string()))
+            .unwrap_or(false)
+    }
+}
+
+/// A process's state letter and process group, from /proc/<pid>/stat
+#[cfg(target_os = "linux")]
+fn proc_status(pid: u32) -> Option<(char, u32)> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    parse_proc_stat(&stat)
+}
+
+#[cfg(any(target_os = "linux", test))]
+fn parse_proc_stat(stat: &str) -> Option<(char, u32)> {
+    // The command name may contain spaces and parentheses; fields follow the last ')'
+    let mut fields = stat[stat.rfind(')')? + 1..].split_whitespace();
+    let state = fields.next()?.chars().next()?;
+    let _parent = fields.next()?;
+    let group = fields.next()?.parse().ok()?;
+    Some((state, group))
+}
+
+/// A process's PID, process group and state letter, from a `ps -o pid=,pgid=,stat=` line
+#[cfg(any(all(unix, not(target_os = "linux")), test))]
+fn parse_ps_line(line: &str) -> Option<(u32, u32, char)> {
+    let mut fields = line.split_whitespace();
+    let pid = fields.next()?.parse().ok()?;
+    let group = fields.next()?.parse().ok()?;
+    let state = fields.next()?.chars().next()?;
+    Some((pid, group, state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_stat() {
+        let stat = "4242 (node (dev) server) S 4200 4100 4100 0 -1 4194560 1234";
+        assert_eq!(parse_proc_stat(stat), Some(('S', 4100)));
+        assert_eq!(
+            parse_proc_stat("4242 (zombie) Z 1 4242 4242"),
+            Some(('Z', 4242))
+        );
+        assert_eq!(parse_proc_stat("garbage"), None);
+        assert_eq!(parse_ps_line("  4242  4100 Z+"), Some((4242, 4100, 'Z')));
+        assert_eq!(parse_ps_line("4242"), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_terminate_process_group() {
+        // A shell that ignores SIGINT, with a child in the same group
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.args(["-c", "trap '' INT; sleep 30 & wait"]);
+        spawn_in_process_group(&mut cmd);
+        let mut child = cmd.spawn().unwrap();
+        let pid = child.id().unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let timeouts = TerminationTimeouts {
+            interrupt: Duration::from_millis(300),
+            terminate: Duration::from_secs(2),
+        };
+        let report = tokio::task::spawn_blocking(move || terminate_process(pid, &timeouts))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(report.process_group);
+        assert_eq!(report.stage, TerminationStage::Terminate);
+        assert!(child.wait().await.is_ok());
+    }
+}
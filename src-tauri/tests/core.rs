@@ -0,0 +1,231 @@
+//! Integration tests running the core commands against the fixture Claude tree
+//! in tests/fixtures/claude, through a `CoreContext` pointed at a copy of it.
+
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use claudio_lib::checkpoint::state::CheckpointState;
+use claudio_lib::commands::agents::{self, AgentDb};
+use claudio_lib::commands::{claude, slash_commands, usage};
+use claudio_lib::context::CoreContext;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+const APP_PROJECT: &str = "-work-app";
+const LOGIN_SESSION: &str = "aaaaaaaa-0000-4000-8000-000000000001";
+
+/// Copies the fixture tree into a temporary directory so tests can modify it
+fn fixture_context() -> (TempDir, CoreContext) {
+    let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/claude");
+    let temp = TempDir::new().unwrap();
+    let claude_dir = temp.path().join("claude");
+
+    for entry in walkdir::WalkDir::new(&source) {
+        let entry = entry.unwrap();
+        let target = claude_dir.join(entry.path().strip_prefix(&source).unwrap());
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target).unwrap();
+        } else {
+            fs::copy(entry.path(), &target).unwrap();
+        }
+    }
+
+    let context = CoreContext::new(claude_dir, None);
+    (temp, context)
+}
+
+#[test]
+fn agents_round_trip() {
+    let (_temp, context) = fixture_context();
+
+    // The agent without frontmatter is skipped
+    let listed = agents::list_agents_in(&context, None).unwrap();
+    let names: Vec<_> = listed.iter().map(|a| a.name.as_str()).collect();
+    assert_eq!(names, ["code-reviewer"]);
+
+    let reviewer = agents::get_agent_in(&context, None, "code-reviewer").unwrap();
+    assert_eq!(reviewer.model, "sonnet");
+    assert_eq!(reviewer.tools.as_deref(), Some("Read, Grep, Glob"));
+    assert!(reviewer.system_prompt.contains("careful code reviewer"));
+
+    // Importing an export of an existing agent keeps both
+    let export = agents::export_agent_in(&context, None, "code-reviewer").unwrap();
+    let imported = agents::import_agent_in(&context, None, &export).unwrap();
+    assert_eq!(imported.name, "code-reviewer (Imported)");
+    assert_eq!(agents::list_agents_in(&context, None).unwrap().len(), 2);
+    assert!(agents::create_agent_in(&context, None, reviewer).is_err());
+
+    agents::delete_agent_in(&context, None, "code-reviewer (Imported)").unwrap();
+    assert_eq!(agents::list_agents_in(&context, None).unwrap().len(), 1);
+}
+
+#[test]
+fn usage_totals_deduplicate_entries() {
+    let (_temp, context) = fixture_context();
+
+    let stats = usage::get_usage_stats_in(&context, None).unwrap();
+    assert_eq!(stats.total_tokens, 3800);
+    assert_eq!(stats.total_sessions, 3);
+    assert_eq!(stats.by_project.len(), 2);
+    assert!((stats.total_cost - 0.0498).abs() < 1e-9);
+
+    let range = usage::get_usage_by_date_range_in(&context, "2024-05-01", "2024-05-02").unwrap();
+    assert_eq!(range.total_tokens, 1700);
+
+    let details =
+        usage::get_usage_details_in(&context, Some("/work/lib".to_string()), None).unwrap();
+    assert_eq!(details.len(), 1);
+    assert_eq!(details[0].input_tokens, 100);
+}
+
+#[test]
+fn projects_and_sessions_are_listed() {
+    let (_temp, context) = fixture_context();
+
+    let projects = claude::list_projects_in(&context).unwrap();
+    assert_eq!(projects.len(), 2);
+    let app = projects.iter().find(|p| p.id == APP_PROJECT).unwrap();
+    assert_eq!(app.path, "/work/app");
+    assert_eq!(app.sessions.len(), 2);
+
+    // Without Claudio's tables the sessions come back without metadata
+    let db = AgentDb(Mutex::new(Connection::open_in_memory().unwrap()));
+    let sessions = claude::get_project_sessions_in(&context, &db, APP_PROJECT).unwrap();
+    let login = sessions.iter().find(|s| s.id == LOGIN_SESSION).unwrap();
+    assert_eq!(login.first_message.as_deref(), Some("Add a login form"));
+    assert_eq!(login.project_path, "/work/app");
+
+    let history = claude::load_session_history_in(&context, LOGIN_SESSION, APP_PROJECT).unwrap();
+    assert_eq!(history.len(), 3);
+    assert!(claude::load_session_history_in(&context, "missing", APP_PROJECT).is_err());
+}
+
+#[test]
+fn user_slash_commands_round_trip() {
+    let (_temp, context) = fixture_context();
+
+    let commands = slash_commands::slash_commands_list_in(&context, None).unwrap();
+    let review = commands
+        .iter()
+        .find(|c| c.scope == "user" && c.full_command == "/review")
+        .unwrap();
+    assert_eq!(
+        review.description.as_deref(),
+        Some("Review the current diff")
+    );
+    assert_eq!(review.allowed_tools, ["Bash(git diff:*)", "Read"]);
+    assert!(review.has_bash_commands && review.accepts_arguments);
+    let component = commands
+        .iter()
+        .find(|c| c.full_command == "/frontend:component")
+        .unwrap();
+    assert!(component.has_file_references);
+
+    let saved = slash_commands::slash_command_save_in(
+        &context,
+        "user".to_string(),
+        "deploy".to_string(),
+        None,
+        "Deploy $ARGUMENTS".to_string(),
+        Some("Deploy a service".to_string()),
+        vec![],
+        None,
+    )
+    .unwrap();
+    assert!(Path::new(&saved.file_path).starts_with(&context.claude_dir));
+
+    let loaded = slash_commands::slash_command_get_in(&context, &saved.id).unwrap();
+    assert_eq!(loaded.description.as_deref(), Some("Deploy a service"));
+    slash_commands::slash_command_delete_in(&context, &saved.id, None).unwrap();
+    assert!(!Path::new(&saved.file_path).exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn mcp_commands_run_the_configured_binary() {
+    use claudio_lib::commands::mcp;
+    use std::os::unix::fs::PermissionsExt;
+
+    let (temp, context) = fixture_context();
+    let script = temp.path().join("fake-claude");
+    fs::write(
+        &script,
+        r#"#!/bin/sh
+case "$1 $2 $3" in
+  "--version  ") echo "1.0.100 (Claude Code)" ;;
+  "mcp list ") printf 'github: npx -y @modelcontextprotocol/server-github\nsearch: uvx mcp-search\n' ;;
+  "mcp get github") printf 'github:\n  Scope: User config\n  Type: stdio\n  Command: npx\n  Args: -y @modelcontextprotocol/server-github\n' ;;
+  "mcp get "*) echo "No MCP server found with name: $3" >&2; exit 1 ;;
+esac
+"#,
+    )
+    .unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    let context = context.with_claude_binary(script.to_string_lossy());
+
+    let servers = mcp::mcp_list_in(&context).unwrap();
+    let names: Vec<_> = servers.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["github", "search"]);
+
+    let github = mcp::mcp_get_in(&context, "github").unwrap();
+    assert_eq!(github.scope, "user");
+    assert_eq!(github.command.as_deref(), Some("npx"));
+    assert_eq!(github.args, ["-y", "@modelcontextprotocol/server-github"]);
+
+    assert!(mcp::mcp_test_connection_in(&context, "github").is_ok());
+    let missing = mcp::mcp_test_connection_in(&context, "missing").unwrap_err();
+    assert!(missing.contains("No MCP server found"));
+}
+
+#[tokio::test]
+async fn checkpoints_restore_the_session_file() {
+    let (temp, context) = fixture_context();
+    let project_path = temp.path().join("work-app");
+    fs::create_dir_all(&project_path).unwrap();
+    let project_path = project_path.to_string_lossy().to_string();
+
+    let state = CheckpointState::new();
+    state.set_claude_dir(context.claude_dir.clone()).await;
+
+    let created = claude::create_checkpoint_in(
+        &state,
+        LOGIN_SESSION.to_string(),
+        APP_PROJECT.to_string(),
+        project_path.clone(),
+        None,
+        Some("before follow-up".to_string()),
+    )
+    .await
+    .unwrap();
+
+    // Continue the session, then go back to the checkpoint
+    let session_path = context.session_path(APP_PROJECT, LOGIN_SESSION);
+    let mut transcript = fs::read_to_string(&session_path).unwrap();
+    transcript.push_str("{\"type\":\"user\",\"message\":{\"role\":\"user\",\"content\":\"Also add a signup form\"}}\n");
+    fs::write(&session_path, transcript).unwrap();
+
+    claude::restore_checkpoint_in(
+        &state,
+        created.checkpoint.id.clone(),
+        LOGIN_SESSION.to_string(),
+        APP_PROJECT.to_string(),
+        project_path.clone(),
+    )
+    .await
+    .unwrap();
+
+    let restored = fs::read_to_string(&session_path).unwrap();
+    assert!(restored.contains("Add a login form"));
+    assert!(!restored.contains("signup form"));
+
+    let checkpoints = claude::list_checkpoints_in(
+        &state,
+        LOGIN_SESSION.to_string(),
+        APP_PROJECT.to_string(),
+        project_path,
+    )
+    .await
+    .unwrap();
+    assert_eq!(checkpoints.len(), 1);
+}
@@ -17,6 +17,12 @@ export interface ProcessInfo {
   model: string;
 }
 
+/** A run left by a previous launch of Claudio that is still running */
+export interface OrphanedProcess extends ProcessInfo {
+  /** Start time and command line, used to tell the run apart from a reused PID */
+  identity: { start_time: string; command_line: string[] } | null;
+}
+
 /**
  * Represents a project in the ~/.claude/projects directory
  */
@@ -41,14 +47,349 @@ export interface Session {
   project_id: string;
   /** The project path */
   project_path: string;
-  /** Optional todo data associated with this session */
-  todo_data?: any;
+  /** The session's todo list from ~/.claude/todos, if it has one */
+  todo_data?: TodoItem[];
   /** Unix timestamp when the session file was created */
   created_at: number;
   /** First user message content (if available) */
   first_message?: string;
   /** Timestamp of the first user message (if available) */
   message_timestamp?: string;
+  /** User metadata (title, tags, pinned flag, notes) stored by Claudio */
+  metadata?: SessionMetadata;
+  /** The git worktree the session was isolated in, if any */
+  worktree?: SessionWorktree;
+}
+
+/** "unknown" stands for a status written by a newer CLI */
+export type TodoStatus = "pending" | "in_progress" | "completed" | "unknown";
+
+/**
+ * An item of a session's todo list, as written by the TodoWrite tool
+ */
+export interface TodoItem {
+  /** Only written by older CLI versions */
+  id?: string;
+  content: string;
+  status: TodoStatus;
+  /** Present-tense form shown while the item is in progress */
+  activeForm?: string;
+  /** Only written by older CLI versions */
+  priority?: string;
+}
+
+/**
+ * A todo item with its status changes, taken from TodoWrite calls in the transcript
+ */
+export interface TrackedTodo extends TodoItem {
+  history: { status: TodoStatus; at: string }[];
+}
+
+/**
+ * The todo list of a session
+ */
+export interface SessionTodos {
+  session_id: string;
+  project_id?: string;
+  project_path?: string;
+  todos: TrackedTodo[];
+  /** Unix timestamp of the last write to the todo list */
+  updated_at: number;
+  /** Whether the session is running in Claudio or its transcript was written recently */
+  active: boolean;
+}
+
+/**
+ * Pending and in-progress todos of a project, across its sessions
+ */
+export interface ProjectTodoSummary {
+  project_id: string;
+  project_path: string;
+  pending: number;
+  in_progress: number;
+  completed: number;
+  sessions: SessionTodos[];
+}
+
+/**
+ * User-provided metadata for a session, stored in Claudio's database
+ */
+export interface SessionMetadata {
+  session_id: string;
+  project_id: string;
+  /** Custom title shown instead of the first message */
+  title?: string;
+  tags: string[];
+  pinned: boolean;
+  notes?: string;
+  /** RFC 3339 timestamp of the last metadata change */
+  updated_at: string;
+}
+
+/**
+ * A Claude Code session being written by a process Claudio did not spawn
+ */
+export interface ExternalSession {
+  session_id: string;
+  project_id: string;
+  /** The working directory of the session, once seen in the transcript */
+  project_path?: string;
+  file_path: string;
+  /** Unix timestamp of the last write to the transcript */
+  last_modified: number;
+  /** Number of lines streamed since the session was detected */
+  lines_streamed: number;
+}
+
+/**
+ * A git worktree created to isolate a session's changes from the main checkout
+ */
+export interface SessionWorktree {
+  id: number;
+  /** Root of the repository the worktree was created from */
+  repo_path: string;
+  /** Directory of the worktree; the session runs here */
+  worktree_path: string;
+  branch: string;
+  /** Commit the branch was created from */
+  base_commit: string;
+  session_id?: string;
+  status: "active" | "merged" | "discarded";
+  created_at: string;
+  updated_at: string;
+}
+
+/**
+ * Changes made in a worktree relative to its base commit
+ */
+export interface WorktreeDiff {
+  worktree: SessionWorktree;
+  files: {
+    path: string;
+    status: "added" | "modified" | "deleted" | "renamed" | "copied";
+    old_path?: string;
+    /** Absent for binary files */
+    additions?: number;
+    deletions?: number;
+  }[];
+  /** Unified diff of all changes */
+  patch: string;
+}
+
+/**
+ * How to replay a recorded session
+ */
+export interface ReplayRequest {
+  session_id: string;
+  project_id: string;
+  /** Model to replay with; the profile's default model, or the CLI's, if unset */
+  model?: string;
+  /** Execution profile to replay with */
+  profile?: string;
+  /** A new worktree from HEAD, or a copy of the project including uncommitted changes */
+  isolation?: "worktree" | "copy";
+  /** Also replay with the session's original model, so both sides start from the same files */
+  baseline?: boolean;
+}
+
+/**
+ * Cost and effort of a session, computed the same way for recorded and replayed sessions
+ */
+export interface SessionMetrics {
+  cost_usd: number;
+  /** Assistant messages */
+  turns: number;
+  tool_calls: number;
+  /** Active time: from each prompt to the last assistant message before the next one */
+  duration_ms: number;
+  input_tokens: number;
+  output_tokens: number;
+  /** Models that answered, most used first */
+  models: string[];
+}
+
+/**
+ * One replay of a recorded session's prompts
+ */
+export interface ReplayRun {
+  label: "candidate" | "baseline";
+  model?: string;
+  profile?: string;
+  session_ids: string[];
+  /** Directory the replay ran in; kept so its changes can be inspected. Empty if it couldn't be set up */
+  scratch_path: string;
+  /** Session worktree record, when replayed in a worktree */
+  worktree_id?: number;
+  prompts_replayed: number;
+  completed: boolean;
+  error?: string;
+  metrics: SessionMetrics;
+  files: WorktreeDiff["files"];
+  patch: string;
+}
+
+/**
+ * A file a session wrote to, with its diff
+ */
+export interface SessionFileChange {
+  /** Path relative to the project, or absolute for files outside it */
+  path: string;
+  status: "added" | "modified";
+  /** Number of successful Edit, Write and MultiEdit calls on the file */
+  operations: number;
+  first_message: number;
+  last_message: number;
+  additions: number;
+  deletions: number;
+  /** "fragments" when the file's content is unknown and only edited snippets are diffed */
+  source: "transcript" | "checkpoint" | "fragments";
+  diff: string;
+}
+
+/**
+ * Everything a session changed, computed from its transcript
+ */
+export interface SessionChanges {
+  session_id: string;
+  project_path?: string;
+  files: SessionFileChange[];
+  /** The diffs of all files, in order */
+  patch: string;
+}
+
+/**
+ * A recorded session side by side with its replays
+ */
+export interface ReplayComparison {
+  project_path: string;
+  /** The user prompts that were replayed, in order */
+  prompts: string[];
+  original: {
+    session_id: string;
+    metrics: SessionMetrics;
+    /** Files changed by edit tools, relative to the project */
+    files_touched: string[];
+  };
+  runs: ReplayRun[];
+}
+
+/**
+ * A named set of overrides applied to Claude runs (environment, API key, model, CLI args)
+ */
+export interface ExecutionProfile {
+  name: string;
+  /** Environment variables set for the run, e.g. ANTHROPIC_BASE_URL */
+  env: Record<string, string>;
+  /** Shell command whose output is used as ANTHROPIC_API_KEY */
+  api_key_helper?: string;
+  /** Model used when a run doesn't specify one */
+  default_model?: string;
+  /** Extra arguments passed to the claude CLI */
+  extra_args: string[];
+}
+
+/**
+ * Limits for a single Claude run; the run is stopped when any of them is crossed
+ */
+export interface RunBudget {
+  max_cost_usd?: number;
+  /** Input, output and cache tokens across all turns */
+  max_tokens?: number;
+  max_duration_secs?: number;
+  max_turns?: number;
+}
+
+/**
+ * A file attached to a prompt. Images (PNG, JPEG, GIF, WebP) may be up to 5 MB,
+ * text files up to 256 KB.
+ */
+export type Attachment =
+  | { kind: "file"; path: string }
+  /** Pasted image bytes, base64-encoded; a `data:` URL is accepted */
+  | { kind: "image_data"; data: string; media_type: string; name?: string }
+  | { kind: "text"; name: string; content: string };
+
+/**
+ * Payload of the `claude-budget-exceeded:{session_id}` event
+ */
+export interface BudgetExceeded {
+  limit: "cost" | "tokens" | "duration" | "turns";
+  reason: string;
+  usage: {
+    cost_usd: number;
+    total_tokens: number;
+    turns: number;
+    elapsed_secs: number;
+  };
+}
+
+/**
+ * A queued or scheduled headless Claude run
+ */
+export interface Job {
+  id: number;
+  name: string;
+  project_path: string;
+  kind: "prompt" | "agent";
+  /** The prompt; for agent jobs this overrides the agent's default task */
+  prompt?: string;
+  agent_name?: string;
+  model?: string;
+  /** Cron expression for recurring jobs */
+  schedule?: string;
+  /** Job that must complete successfully before this one is queued */
+  after_job_id?: number;
+  /** Limits after which each run is stopped */
+  budget?: RunBudget;
+  /** Run each execution in a fresh git worktree */
+  use_worktree: boolean;
+  enabled: boolean;
+  status: "waiting" | "queued" | "running" | "completed" | "failed" | "cancelled";
+  next_run_at?: string;
+  last_run_at?: string;
+  created_at: string;
+  updated_at: string;
+}
+
+/**
+ * A single execution of a job
+ */
+export interface JobRun {
+  id: number;
+  job_id: number;
+  session_id?: string;
+  status: "running" | "completed" | "failed" | "cancelled";
+  started_at: string;
+  finished_at?: string;
+  exit_code?: number;
+  /** The final result text reported by Claude */
+  result?: string;
+  cost_usd?: number;
+  error?: string;
+  /** The git worktree the run was isolated in, if any */
+  worktree_id?: number;
+}
+
+/**
+ * Options for creating a job
+ */
+export interface CreateJobOptions {
+  name: string;
+  projectPath: string;
+  kind: "prompt" | "agent";
+  prompt?: string;
+  agentName?: string;
+  model?: string;
+  /** Cron expression (e.g. "0 9 * * 1") for recurring jobs */
+  schedule?: string;
+  /** RFC 3339 time for a one-off run */
+  runAt?: string;
+  /** Queue the job once this job completes successfully */
+  afterJobId?: number;
+  /** Limits after which each run is stopped */
+  budget?: RunBudget;
+  /** Run each execution in a fresh git worktree */
+  useWorktree?: boolean;
 }
 
 /**
@@ -58,6 +399,144 @@ export interface ClaudeSettings {
   [key: string]: any;
 }
 
+export type SettingsScope = "global_config" | "user" | "project" | "local" | "managed";
+
+/**
+ * Where the effective value of a setting comes from
+ */
+export interface SettingOrigin {
+  /** Dotted key, e.g. "model", "env.HTTP_PROXY" or "permissions.allow" */
+  key: string;
+  /** Scope of the effective value; for merged lists, the highest scope contributing */
+  scope: SettingsScope;
+  /** Lower-precedence scopes whose value for the key was replaced */
+  overrides: SettingsScope[];
+  /** For lists merged across scopes (permission rules, hooks), the scope of each item */
+  items: { value: any; scope: SettingsScope }[];
+}
+
+/**
+ * Settings in effect for a project, merged across all scopes
+ */
+export interface EffectiveSettings {
+  project_path?: string;
+  settings: ClaudeSettings;
+  /** Files taking part, lowest precedence first */
+  sources: { scope: SettingsScope; path: string; exists: boolean; error?: string }[];
+  /** Origins by dotted key, sorted by key */
+  origins: SettingOrigin[];
+}
+
+/**
+ * A problem found when validating settings
+ */
+export interface SettingsIssue {
+  /** Location of the problem, e.g. "hooks.PreToolUse[0].matcher" */
+  path: string;
+  message: string;
+}
+
+/**
+ * Result of validating settings against the bundled Claude Code settings schema
+ */
+export interface SettingsValidation {
+  /** Whether there are no errors; warnings don't make settings invalid */
+  valid: boolean;
+  /** Problems that break Claude Code; saving is refused unless forced */
+  errors: SettingsIssue[];
+  /** Likely mistakes that Claude Code tolerates, such as unknown keys */
+  warnings: SettingsIssue[];
+}
+
+/**
+ * A hook to run against a sample event
+ */
+export interface HookTestRequest {
+  /** Hook event, e.g. "PreToolUse", "PostToolUse", "UserPromptSubmit" or "Stop" */
+  event: string;
+  command: string;
+  /** Tool name matcher of the hook (tool events only) */
+  matcher?: string;
+  /** Directory the hook runs in, as CLAUDE_PROJECT_DIR */
+  project_path: string;
+  /** Tool to simulate; defaults to the first sample tool the matcher accepts */
+  tool_name?: string;
+  /** Tool input to send instead of a sample one */
+  tool_input?: any;
+  /** Prompt sent with UserPromptSubmit */
+  prompt?: string;
+  /** Session whose transcript supplies a real tool call and the transcript path */
+  session_id?: string;
+  timeout_secs?: number;
+}
+
+/**
+ * What Claude Code would do with a hook's result
+ */
+export interface HookDecision {
+  outcome: "success" | "blocking_error" | "non_blocking_error" | "timeout";
+  decision?: "allow" | "deny" | "ask" | "approve" | "block";
+  reason?: string;
+  /** Text added to Claude's context */
+  additional_context?: string;
+  /** False if the hook asked Claude to stop */
+  continue_session: boolean;
+  stop_reason?: string;
+  /** The hook's stdout, if it was a JSON object */
+  json_output?: any;
+}
+
+/**
+ * Result of running a hook against a sample event
+ */
+export interface HookTestResult {
+  /** Whether the matcher accepts the simulated tool; the hook only runs if it does */
+  matched: boolean;
+  /** The JSON sent to the hook on stdin */
+  payload: any;
+  exit_code?: number;
+  stdout: string;
+  stderr: string;
+  duration_ms: number;
+  decision: HookDecision;
+}
+
+/**
+ * A hook execution seen in a session's output
+ */
+export interface HookActivity {
+  /** Hook event, e.g. "PreToolUse" */
+  event: string;
+  /** Tool the hook ran for (tool events only) */
+  tool_name?: string;
+  tool_use_id?: string;
+  command?: string;
+  status: "success" | "blocked" | "error" | "cancelled";
+  exit_code?: number;
+  duration_ms?: number;
+  /** Whether the hook ran for at least 5 seconds */
+  slow: boolean;
+  decision?: "allow" | "deny" | "ask" | "approve" | "block";
+  /** Reason, error output or context reported for the hook */
+  message?: string;
+  source: "stream" | "stderr" | "transcript";
+  timestamp: string;
+}
+
+/**
+ * Hook activity of a session
+ */
+export interface HookActivityReport {
+  session_id: string;
+  /** Whether the session was run by Claudio, so its output was captured */
+  captured: boolean;
+  /** Activity in chronological order */
+  entries: HookActivity[];
+  failed: number;
+  blocked: number;
+  slow: number;
+}
+
 /**
  * Represents the Claude Code version status
  */
@@ -70,6 +549,228 @@ export interface ClaudeVersionStatus {
   output: string;
 }
 
+/** Version of the `claude-event` schema these types describe */
+export const STREAM_EVENT_SCHEMA_VERSION = 1;
+
+/**
+ * Token counts of an assistant message or a whole run
+ */
+export interface TokenUsage {
+  input_tokens: number;
+  output_tokens: number;
+  cache_creation_input_tokens: number;
+  cache_read_input_tokens: number;
+}
+
+/**
+ * A typed event parsed from Claude's stream-json output
+ */
+export type StreamEvent =
+  | {
+      type: "init";
+      model?: string;
+      cwd?: string;
+      tools: string[];
+      mcp_servers: { name: string; status: string }[];
+      permission_mode?: string;
+      claude_code_version?: string;
+    }
+  | {
+      /** A piece of a message being streamed (only with `--include-partial-messages`) */
+      type: "assistant_delta";
+      message_id?: string;
+      index: number;
+      kind: "text" | "thinking" | "tool_input";
+      text: string;
+    }
+  | {
+      /** Tool calls in the message follow as `tool_use` events */
+      type: "assistant_message";
+      message_id?: string;
+      model?: string;
+      text: string;
+      thinking?: string;
+      stop_reason?: string;
+      usage?: TokenUsage;
+      parent_tool_use_id?: string;
+    }
+  | { type: "tool_use"; id: string; name: string; input: any; parent_tool_use_id?: string }
+  | {
+      type: "tool_result";
+      tool_use_id: string;
+      content: string;
+      is_error: boolean;
+      parent_tool_use_id?: string;
+    }
+  | {
+      type: "result";
+      subtype: string;
+      is_error: boolean;
+      result?: string;
+      duration_ms?: number;
+      duration_api_ms?: number;
+      num_turns?: number;
+      total_cost_usd?: number;
+      usage?: TokenUsage;
+    }
+  | { type: "error"; source: "result" | "stdout" | "stderr"; message: string }
+  | { type: "system"; subtype: string }
+  | { type: "unknown"; message_type: string };
+
+/**
+ * An event as emitted on `claude-event` and `claude-event:{session_id}`
+ */
+export type StreamEnvelope = StreamEvent & {
+  schema_version: number;
+  /** Position of the event in the run, starting at 0 */
+  seq: number;
+  session_id?: string;
+  /** The output line the event was parsed from; only set on the first event of each line */
+  raw?: string;
+};
+
+/**
+ * A feature of the claude CLI that Claudio relies on
+ */
+export type ClaudeFeature =
+  | "stream_json"
+  | "resume"
+  | "continue"
+  | "add_dir"
+  | "skip_permissions"
+  | "permission_mode"
+  | "append_system_prompt"
+  | "allowed_tools"
+  | "mcp_add_json"
+  | "mcp_add_from_claude_desktop"
+  | "mcp_serve";
+
+/**
+ * Whether a feature is available in the selected claude binary
+ */
+export interface FeatureSupport {
+  feature: ClaudeFeature;
+  description: string;
+  supported: boolean;
+  min_version: string;
+  /** Shown in place of the feature when it isn't supported, e.g. "... requires claude >= 1.0.18" */
+  message?: string;
+}
+
+/**
+ * What the selected claude binary supports, from its help and version output
+ */
+export interface ClaudeCapabilities {
+  binary_path: string;
+  version?: string;
+  /** Whether the help output could be parsed; if not, support is inferred from the version */
+  probed: boolean;
+  /** Long options of the main command, e.g. "--resume" */
+  flags: string[];
+  /** Subcommands, with nested ones as e.g. "mcp add-json" */
+  subcommands: string[];
+  /** Values accepted by `--permission-mode`; only offer these */
+  permission_modes: string[];
+  /** Values accepted by `--output-format` */
+  output_formats: string[];
+  features: FeatureSupport[];
+}
+
+/**
+ * Resource usage of a running process and everything it started,
+ * pushed every sampling interval as `process-stats` events
+ */
+export interface ProcessStats {
+  run_id: number;
+  pid: number;
+  session_id: string | null;
+  /** CPU use since the previous sample, where 100 is one core */
+  cpu_percent: number;
+  rss_bytes: number;
+  open_fds: number;
+  child_processes: number;
+  sampled_at: string;
+}
+
+/**
+ * Sampling interval and the thresholds that warn about or cancel a run
+ */
+export interface MonitoringSettings {
+  enabled: boolean;
+  interval_secs: number;
+  max_cpu_percent?: number | null;
+  max_rss_mb?: number | null;
+  max_open_fds?: number | null;
+  max_child_processes?: number | null;
+  /** Consecutive samples a threshold must be crossed in before the run is reported */
+  sustained_samples: number;
+  on_threshold: "warn" | "cancel";
+}
+
+/** Run events that notifications and webhooks can be sent for */
+export type RunEvent =
+  | "completed"
+  | "failed"
+  | "cancelled"
+  | "budget_exceeded"
+  | "permission_required"
+  /** Sent by testWebhook, never by a run */
+  | "test";
+
+/**
+ * Which run events show desktop notifications
+ */
+export interface NotificationSettings {
+  enabled: boolean;
+  events: RunEvent[];
+}
+
+/**
+ * A URL run events are POSTed to, signed with HMAC-SHA256 of
+ * `{X-Claudio-Timestamp}.{body}` in `X-Claudio-Signature: sha256=<hex>`
+ */
+export interface WebhookConfig {
+  name: string;
+  url: string;
+  /** Generated when saved without one */
+  secret?: string | null;
+  events: RunEvent[];
+  enabled: boolean;
+}
+
+/**
+ * Payload of `process-threshold-exceeded` events
+ */
+export interface ThresholdExceeded {
+  stats: ProcessStats;
+  reasons: string[];
+  action: "warn" | "cancel";
+}
+
+/**
+ * How a cancelled run was stopped: the signal it exited at
+ */
+export interface TerminationReport {
+  pid: number;
+  /** Whether the run's whole process group was signalled */
+  process_group: boolean;
+  stage: "not_running" | "interrupt" | "terminate" | "kill" | "failed";
+  elapsed_ms: number;
+}
+
+/**
+ * State of the local HTTP + WebSocket control API
+ */
+export interface ApiServerStatus {
+  enabled: boolean;
+  running: boolean;
+  port: number;
+  /** Base URL of the running server, e.g. "http://127.0.0.1:7878" */
+  url?: string;
+  /** Bearer token clients must send */
+  token?: string;
+}
+
 /**
  * Represents a CLAUDE.md file found in the project
  */
@@ -84,6 +785,45 @@ export interface ClaudeMdFile {
   modified: number;
 }
 
+export type MemoryKind = "managed" | "user" | "project" | "local";
+
+/**
+ * A memory file included in the resolved CLAUDE.md memory
+ */
+export interface MemorySource {
+  path: string;
+  /** Kind of the file, or of the file that imported it */
+  kind: MemoryKind;
+  /** The file whose `@path` import pulled this one in */
+  imported_from?: string;
+  /** Import depth; 0 for files loaded directly */
+  depth: number;
+  /** File size in bytes */
+  size: number;
+  /** Byte range of the file's content in the resolved text */
+  start: number;
+  end: number;
+}
+
+/**
+ * The CLAUDE.md memory Claude Code loads for a working directory
+ */
+export interface ResolvedMemory {
+  working_dir: string;
+  /** All memory files concatenated in load order */
+  text: string;
+  /** Size of the text in bytes */
+  size: number;
+  /** Files in load order */
+  sources: MemorySource[];
+  /** Imports that were not followed */
+  skipped_imports: {
+    import: string;
+    imported_from: string;
+    reason: "not_found" | "cycle" | "duplicate" | "max_depth";
+  }[];
+}
+
 /**
  * Represents a file or directory entry
  */
@@ -93,6 +833,48 @@ export interface FileEntry {
   is_directory: boolean;
   size: number;
   extension?: string;
+  /** Git status, if the entry is in a git work tree and changed */
+  git_status?: "modified" | "added" | "deleted" | "renamed" | "untracked" | "ignored" | "conflicted";
+}
+
+/**
+ * Options for a content search
+ */
+export interface ContentSearchOptions {
+  /** Treat the query as a regular expression instead of literal text */
+  regex?: boolean;
+  case_sensitive?: boolean;
+  /** Lines of context before and after each match */
+  context_lines?: number;
+  max_results?: number;
+  /** Only search files matching this glob, e.g. "*.rs" or "src/**" */
+  glob?: string;
+}
+
+/**
+ * A line matching a content search, sent in `file-search-results:{search_id}` events
+ */
+export interface ContentMatch {
+  path: string;
+  /** Path relative to the search base */
+  relative_path: string;
+  /** 1-based line number */
+  line_number: number;
+  line: string;
+  before: string[];
+  after: string[];
+}
+
+/**
+ * Totals of a finished content search, also sent as `file-search-complete:{search_id}`
+ */
+export interface ContentSearchSummary {
+  search_id: string;
+  files_searched: number;
+  matches: number;
+  /** Whether the search stopped at `max_results` */
+  truncated: boolean;
+  cancelled: boolean;
 }
 
 /**
@@ -477,6 +1259,64 @@ export const api = {
     }
   },
 
+  /**
+   * Gets the user metadata for a session
+   * @param sessionId - The session ID
+   * @returns Promise resolving to the metadata, or null if none is set
+   */
+  async getSessionMetadata(sessionId: string): Promise<SessionMetadata | null> {
+    return invoke<SessionMetadata | null>("get_session_metadata", { sessionId });
+  },
+
+  /**
+   * Sets the title, tags, pinned flag and notes of a session
+   * @param sessionId - The session ID
+   * @param projectId - The project ID the session belongs to
+   * @param metadata - The full metadata to store
+   * @returns Promise resolving to the stored metadata
+   */
+  async setSessionMetadata(
+    sessionId: string,
+    projectId: string,
+    metadata: { title?: string; tags?: string[]; pinned?: boolean; notes?: string }
+  ): Promise<SessionMetadata> {
+    try {
+      return await invoke<SessionMetadata>("set_session_metadata", {
+        sessionId,
+        projectId,
+        ...metadata,
+      });
+    } catch (error) {
+      console.error("Failed to set session metadata:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Removes all user metadata for a session
+   * @param sessionId - The session ID
+   */
+  async deleteSessionMetadata(sessionId: string): Promise<void> {
+    return invoke("delete_session_metadata", { sessionId });
+  },
+
+  /**
+   * Lists all session tags in use
+   * @returns Promise resolving to a sorted list of tags
+   */
+  async listSessionTags(): Promise<string[]> {
+    return invoke<string[]>("list_session_tags");
+  },
+
+  /**
+   * Lists sessions carrying a tag across all projects
+   * @param tag - The tag to filter by
+   * @returns Promise resolving to the tagged sessions
+   */
+  async listSessionsByTag(tag: string): Promise<Session[]> {
+    return invoke<Session[]>("list_sessions_by_tag", { tag });
+  },
+
   /**
    * Fetch list of agents from GitHub repository
    * @returns Promise resolving to list of available agents on GitHub
@@ -541,6 +1381,22 @@ export const api = {
     }
   },
 
+  /**
+   * Computes the settings in effect for a project from the managed, user, project
+   * and local settings files and ~/.claude.json, with the origin of each key
+   * @param projectPath - Project to compute settings for; user-level settings only if omitted
+   */
+  async getEffectiveSettings(projectPath?: string): Promise<EffectiveSettings> {
+    return invoke<EffectiveSettings>("get_effective_settings", { projectPath });
+  },
+
+  /**
+   * Validates settings against the bundled Claude Code settings schema
+   */
+  async validateClaudeSettings(settings: ClaudeSettings): Promise<SettingsValidation> {
+    return invoke<SettingsValidation>("validate_claude_settings", { settings });
+  },
+
   /**
    * Opens a new Claude Code session
    * @param path - Optional path to open the session in
@@ -581,6 +1437,21 @@ export const api = {
     }
   },
 
+  /**
+   * Gets what the selected claude binary supports. The binary is probed once
+   * and the result cached until it changes.
+   * @param refresh - Probe the binary again
+   * @returns Promise resolving to the binary's capabilities
+   */
+  async getClaudeCapabilities(refresh?: boolean): Promise<ClaudeCapabilities> {
+    try {
+      return await invoke<ClaudeCapabilities>("get_claude_capabilities", { refresh });
+    } catch (error) {
+      console.error("Failed to get Claude capabilities:", error);
+      throw error;
+    }
+  },
+
   /**
    * Saves the CLAUDE.md system prompt file
    * @param content - The new content for the system prompt
@@ -600,9 +1471,9 @@ export const api = {
    * @param settings - The settings object to save
    * @returns Promise resolving when the settings are saved
    */
-  async saveClaudeSettings(settings: ClaudeSettings): Promise<string> {
+  async saveClaudeSettings(settings: ClaudeSettings, force?: boolean): Promise<string> {
     try {
-      return await invoke<string>("save_claude_settings", { settings });
+      return await invoke<string>("save_claude_settings", { settings, force });
     } catch (error) {
       console.error("Failed to save Claude settings:", error);
       throw error;
@@ -652,6 +1523,15 @@ export const api = {
     }
   },
 
+  /**
+   * Resolves the CLAUDE.md memory Claude Code would load in a working directory,
+   * in load order with `@path` imports expanded
+   * @param workingDir - The directory Claude would run in
+   */
+  async resolveClaudeMemory(workingDir: string): Promise<ResolvedMemory> {
+    return invoke<ResolvedMemory>("resolve_claude_memory", { workingDir });
+  },
+
   // Agent API methods
   
   /**
@@ -1022,30 +1902,40 @@ export const api = {
   /**
    * Executes a new interactive Claude Code session with streaming output
    */
-  async executeClaudeCode(projectPath: string, prompt: string, model: string): Promise<void> {
-    return invoke("execute_claude_code", { projectPath, prompt, model });
+  async executeClaudeCode(
+    projectPath: string,
+    prompt: string,
+    model: string,
+    budget?: RunBudget,
+    profile?: string,
+    useWorktree?: boolean,
+    attachments?: Attachment[]
+  ): Promise<SessionWorktree | null> {
+    return invoke<SessionWorktree | null>("execute_claude_code", { projectPath, prompt, model, budget, profile, useWorktree, attachments });
   },
 
   /**
    * Continues an existing Claude Code conversation with streaming output
    */
-  async continueClaudeCode(projectPath: string, prompt: string, model: string): Promise<void> {
-    return invoke("continue_claude_code", { projectPath, prompt, model });
+  async continueClaudeCode(projectPath: string, prompt: string, model: string, budget?: RunBudget, profile?: string, attachments?: Attachment[]): Promise<void> {
+    return invoke("continue_claude_code", { projectPath, prompt, model, budget, profile, attachments });
   },
 
   /**
    * Resumes an existing Claude Code session by ID with streaming output
    */
-  async resumeClaudeCode(projectPath: string, sessionId: string, prompt: string, model: string): Promise<void> {
-    return invoke("resume_claude_code", { projectPath, sessionId, prompt, model });
+  async resumeClaudeCode(projectPath: string, sessionId: string, prompt: string, model: string, budget?: RunBudget, profile?: string, attachments?: Attachment[]): Promise<void> {
+    return invoke("resume_claude_code", { projectPath, sessionId, prompt, model, budget, profile, attachments });
   },
 
   /**
-   * Cancels the currently running Claude Code execution
+   * Cancels the currently running Claude Code execution. Its process group gets
+   * SIGINT, then SIGTERM, then SIGKILL on the configured timeouts.
    * @param sessionId - Optional session ID to cancel a specific session
+   * @returns How the run was stopped, or null if none was running
    */
-  async cancelClaudeExecution(sessionId?: string): Promise<void> {
-    return invoke("cancel_claude_execution", { sessionId });
+  async cancelClaudeExecution(sessionId?: string): Promise<TerminationReport | null> {
+    return invoke<TerminationReport | null>("cancel_claude_execution", { sessionId });
   },
 
   /**
@@ -1065,6 +1955,289 @@ export const api = {
     return invoke("get_claude_session_output", { sessionId });
   },
 
+  /**
+   * Starts watching ~/.claude/projects for sessions written by terminal `claude`
+   * instances. New lines are emitted as `claude-output:{session_id}` events.
+   */
+  async startExternalSessionWatcher(): Promise<void> {
+    return invoke("start_external_session_watcher");
+  },
+
+  /**
+   * Stops the external session watcher
+   */
+  async stopExternalSessionWatcher(): Promise<void> {
+    return invoke("stop_external_session_watcher");
+  },
+
+  /**
+   * Lists sessions currently being written outside Claudio
+   * @returns Promise resolving to the active external sessions, newest first
+   */
+  async listExternalClaudeSessions(): Promise<ExternalSession[]> {
+    return invoke<ExternalSession[]>("list_external_claude_sessions");
+  },
+
+  /**
+   * Creates a git worktree on a new branch to run a session in isolation
+   */
+  async createSessionWorktree(projectPath: string, branch?: string): Promise<SessionWorktree> {
+    return invoke<SessionWorktree>("create_session_worktree", { projectPath, branch });
+  },
+
+  /**
+   * Lists session worktrees, newest first, optionally only those of one repository
+   */
+  async listSessionWorktrees(repoPath?: string): Promise<SessionWorktree[]> {
+    return invoke<SessionWorktree[]>("list_session_worktrees", { repoPath });
+  },
+
+  /**
+   * Gets the changes made in a worktree, including uncommitted and untracked files
+   */
+  async getWorktreeDiff(worktreeId: number): Promise<WorktreeDiff> {
+    return invoke<WorktreeDiff>("get_worktree_diff", { worktreeId });
+  },
+
+  /**
+   * Commits pending changes, merges the worktree's branch back and removes the worktree
+   */
+  async mergeSessionWorktree(worktreeId: number, commitMessage?: string): Promise<SessionWorktree> {
+    return invoke<SessionWorktree>("merge_session_worktree", { worktreeId, commitMessage });
+  },
+
+  /**
+   * Removes a worktree and its branch, discarding its changes
+   */
+  async discardSessionWorktree(worktreeId: number): Promise<SessionWorktree> {
+    return invoke<SessionWorktree>("discard_session_worktree", { worktreeId });
+  },
+
+  /**
+   * Replays a recorded session's prompts with another model or profile in a
+   * scratch worktree or copy, and compares cost, turns, duration and changes.
+   * Progress is emitted as `session-replay-progress:{sessionId}`.
+   */
+  async replaySession(request: ReplayRequest): Promise<ReplayComparison> {
+    return invoke<ReplayComparison>("replay_session", { request });
+  },
+
+  /**
+   * Gets the files a session changed with a unified diff per file, computed
+   * from its Edit, Write and MultiEdit calls without running git
+   * @param sessionId - The session ID
+   * @param fromMessage - Optional first transcript line to include
+   * @param toMessage - Optional last transcript line to include
+   */
+  async getSessionChanges(
+    sessionId: string,
+    fromMessage?: number,
+    toMessage?: number
+  ): Promise<SessionChanges> {
+    return invoke<SessionChanges>("get_session_changes", { sessionId, fromMessage, toMessage });
+  },
+
+  /**
+   * Lists runs started by a previous launch that are still running
+   */
+  async listOrphanedProcesses(): Promise<OrphanedProcess[]> {
+    return invoke<OrphanedProcess[]>("list_orphaned_processes");
+  },
+
+  /**
+   * Stops an orphaned run and its process group
+   * @returns How it was stopped, or null if there is no such orphan
+   */
+  async killOrphanedProcess(runId: number): Promise<TerminationReport | null> {
+    return invoke<TerminationReport | null>("kill_orphaned_process", { runId });
+  },
+
+  /**
+   * Reattaches to an orphaned Claude session, streaming its transcript as
+   * `claude-output:{session_id}` until it exits
+   */
+  async reattachOrphanedProcess(runId: number): Promise<ProcessInfo> {
+    return invoke<ProcessInfo>("reattach_orphaned_process", { runId });
+  },
+
+  /**
+   * Gets the latest resource usage of a running process
+   * @returns null if the run isn't registered or can't be sampled here
+   */
+  async getProcessStats(runId: number): Promise<ProcessStats | null> {
+    return invoke<ProcessStats | null>("get_process_stats", { runId });
+  },
+
+  /**
+   * Gets the sampling interval and resource thresholds
+   */
+  async getMonitoringSettings(): Promise<MonitoringSettings> {
+    return invoke<MonitoringSettings>("get_monitoring_settings");
+  },
+
+  /**
+   * Saves the sampling interval and resource thresholds
+   */
+  async saveMonitoringSettings(settings: MonitoringSettings): Promise<void> {
+    return invoke("save_monitoring_settings", { settings });
+  },
+
+  /**
+   * Gets which run events show desktop notifications
+   */
+  async getNotificationSettings(): Promise<NotificationSettings> {
+    return invoke<NotificationSettings>("get_notification_settings");
+  },
+
+  /**
+   * Saves which run events show desktop notifications
+   */
+  async saveNotificationSettings(settings: NotificationSettings): Promise<void> {
+    return invoke("save_notification_settings", { settings });
+  },
+
+  /**
+   * Lists the configured webhooks
+   */
+  async listWebhooks(): Promise<WebhookConfig[]> {
+    return invoke<WebhookConfig[]>("list_webhooks");
+  },
+
+  /**
+   * Creates or replaces a webhook by name
+   * @returns The saved webhook, with its signing secret
+   */
+  async saveWebhook(webhook: WebhookConfig): Promise<WebhookConfig> {
+    return invoke<WebhookConfig>("save_webhook", { webhook });
+  },
+
+  /**
+   * Deletes a webhook
+   */
+  async deleteWebhook(name: string): Promise<void> {
+    return invoke("delete_webhook", { name });
+  },
+
+  /**
+   * Sends a sample event to a webhook; rejects if it isn't accepted
+   */
+  async testWebhook(name: string): Promise<void> {
+    return invoke("test_webhook", { name });
+  },
+
+  /**
+   * Lists todo lists across sessions, optionally filtered by item status,
+   * project, or to sessions that are still active
+   */
+  async listTodos(status?: TodoStatus, projectId?: string, activeOnly?: boolean): Promise<SessionTodos[]> {
+    return invoke<SessionTodos[]>("list_todos", { status, projectId, activeOnly });
+  },
+
+  /**
+   * Gets a session's todo list with each item's status history
+   */
+  async getSessionTodos(sessionId: string): Promise<SessionTodos | null> {
+    return invoke<SessionTodos | null>("get_session_todos", { sessionId });
+  },
+
+  /**
+   * Summarizes pending and in-progress todos per project
+   */
+  async getOutstandingWork(): Promise<ProjectTodoSummary[]> {
+    return invoke<ProjectTodoSummary[]>("get_outstanding_work");
+  },
+
+  /**
+   * Lists the configured execution profiles
+   */
+  async listExecutionProfiles(): Promise<ExecutionProfile[]> {
+    return invoke<ExecutionProfile[]>("list_execution_profiles");
+  },
+
+  /**
+   * Creates or replaces an execution profile by name
+   */
+  async saveExecutionProfile(profile: ExecutionProfile): Promise<void> {
+    return invoke("save_execution_profile", { profile });
+  },
+
+  /**
+   * Deletes an execution profile and any references to it
+   */
+  async deleteExecutionProfile(name: string): Promise<void> {
+    return invoke("delete_execution_profile", { name });
+  },
+
+  /**
+   * Sets the profile used when neither the run nor its project picks one
+   */
+  async setDefaultExecutionProfile(name: string | null): Promise<void> {
+    return invoke("set_default_execution_profile", { name });
+  },
+
+  /**
+   * Gets the profile assigned to a project
+   */
+  async getProjectExecutionProfile(projectPath: string): Promise<string | null> {
+    return invoke<string | null>("get_project_execution_profile", { projectPath });
+  },
+
+  /**
+   * Assigns a profile to a project, or clears the assignment with null
+   */
+  async setProjectExecutionProfile(projectPath: string, name: string | null): Promise<void> {
+    return invoke("set_project_execution_profile", { projectPath, name });
+  },
+
+  /**
+   * Creates a job; without a schedule, run time or dependency it is queued right away
+   */
+  async createJob(options: CreateJobOptions): Promise<Job> {
+    return invoke<Job>("create_job", { ...options });
+  },
+
+  /**
+   * Lists all jobs, newest first
+   */
+  async listJobs(): Promise<Job[]> {
+    return invoke<Job[]>("list_jobs");
+  },
+
+  /**
+   * Lists job runs, newest first, optionally only those of one job
+   */
+  async listJobRuns(jobId?: number, limit?: number): Promise<JobRun[]> {
+    return invoke<JobRun[]>("list_job_runs", { jobId, limit });
+  },
+
+  /**
+   * Deletes a job and its run history
+   */
+  async deleteJob(jobId: number): Promise<void> {
+    return invoke("delete_job", { jobId });
+  },
+
+  /**
+   * Enables or disables a job
+   */
+  async setJobEnabled(jobId: number, enabled: boolean): Promise<Job> {
+    return invoke<Job>("set_job_enabled", { jobId, enabled });
+  },
+
+  /**
+   * Starts a job immediately
+   */
+  async runJobNow(jobId: number): Promise<Job> {
+    return invoke<Job>("run_job_now", { jobId });
+  },
+
+  /**
+   * Cancels a job, killing its Claude process if it is running
+   */
+  async cancelJob(jobId: number): Promise<Job> {
+    return invoke<Job>("cancel_job", { jobId });
+  },
+
   /**
    * Lists files and directories in a given path
    */
@@ -1073,10 +2246,31 @@ export const api = {
   },
 
   /**
-   * Searches for files and directories matching a pattern
+   * Fuzzy-searches file and directory paths, skipping files ignored by
+   * .gitignore or .ignore, best matches first
    */
-  async searchFiles(basePath: string, query: string): Promise<FileEntry[]> {
-    return invoke("search_files", { basePath, query });
+  async searchFiles(basePath: string, query: string, limit?: number): Promise<FileEntry[]> {
+    return invoke("search_files", { basePath, query, limit });
+  },
+
+  /**
+   * Searches file contents. Matches are streamed as `file-search-results:{searchId}`
+   * events while the search runs; the returned promise resolves with the totals.
+   */
+  async searchFileContents(
+    searchId: string,
+    basePath: string,
+    query: string,
+    options?: ContentSearchOptions
+  ): Promise<ContentSearchSummary> {
+    return invoke<ContentSearchSummary>("search_file_contents", { searchId, basePath, query, options });
+  },
+
+  /**
+   * Cancels a running content search
+   */
+  async cancelFileSearch(searchId: string): Promise<boolean> {
+    return invoke<boolean>("cancel_file_search", { searchId });
   },
 
   /**
@@ -1781,10 +2975,11 @@ export const api = {
   async updateHooksConfig(
     scope: 'user' | 'project' | 'local',
     hooks: HooksConfiguration,
-    projectPath?: string
+    projectPath?: string,
+    force?: boolean
   ): Promise<string> {
     try {
-      return await invoke<string>("update_hooks_config", { scope, projectPath, hooks });
+      return await invoke<string>("update_hooks_config", { scope, projectPath, hooks, force });
     } catch (error) {
       console.error("Failed to update hooks config:", error);
       throw error;
@@ -1805,6 +3000,58 @@ export const api = {
     }
   },
 
+  /**
+   * Run a hook command against a sample event payload, without starting a session
+   * @param request - The hook and the event to simulate
+   * @returns Promise resolving to the hook's output and the decision Claude Code would make
+   */
+  async testHook(request: HookTestRequest): Promise<HookTestResult> {
+    try {
+      return await invoke<HookTestResult>("test_hook", { request });
+    } catch (error) {
+      console.error("Failed to test hook:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Get the hooks that fired in a session, with their timing and decisions.
+   * New activity of running sessions is also emitted as `hook-activity:{sessionId}`.
+   * @param sessionId - The session ID
+   * @returns Promise resolving to the session's hook activity
+   */
+  async getHookActivity(sessionId: string): Promise<HookActivityReport> {
+    try {
+      return await invoke<HookActivityReport>("get_hook_activity", { sessionId });
+    } catch (error) {
+      console.error("Failed to get hook activity:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets whether the local control API is enabled, where it listens and its token
+   */
+  async getApiServerStatus(): Promise<ApiServerStatus> {
+    return invoke<ApiServerStatus>("get_api_server_status");
+  },
+
+  /**
+   * Turns the local control API on or off. It only listens on 127.0.0.1.
+   * @param enabled - Whether to serve the API
+   * @param port - Optional port to move it to
+   */
+  async setApiServerEnabled(enabled: boolean, port?: number): Promise<ApiServerStatus> {
+    return invoke<ApiServerStatus>("set_api_server_enabled", { enabled, port });
+  },
+
+  /**
+   * Replaces the control API token; clients using the old one are rejected
+   */
+  async regenerateApiServerToken(): Promise<ApiServerStatus> {
+    return invoke<ApiServerStatus>("regenerate_api_server_token");
+  },
+
   /**
    * Get merged hooks configuration (respecting priority)
    * @param projectPath - The project path